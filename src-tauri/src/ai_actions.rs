@@ -0,0 +1,516 @@
+// src-tauri/src/ai_actions.rs
+//
+// User-defined AI actions and the prompt overrides for the built-in ones, stored in a
+// versioned ai_actions.json in the app config dir. The file replaces custom_prompts.json,
+// whose overrides are migrated on first load (the old file is kept as
+// custom_prompts.json.migrated). A migrated prompt has to pass the template check like any
+// other; one that doesn't is dropped, so the built-in default applies, and the user is told.
+//
+// An action can have a dedicated hotkey: holding or tapping it dictates exactly like the
+// main hotkey, and the transcript is then run through the action. The action's output
+// mode decides whether the result replaces the dictation, is appended to it, or only
+// goes to the clipboard.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use rdev::Key as RdevKey;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::config::SETTINGS;
+
+const AI_ACTIONS_FILENAME: &str = "ai_actions.json";
+const LEGACY_CUSTOM_PROMPTS_FILENAME: &str = "custom_prompts.json";
+const STORE_VERSION: u32 = 1;
+
+/// Actions with a built-in default template; user actions can't take these ids
+pub const BUILTIN_ACTION_IDS: &[&str] = &["written_form", "summarize", "email", "promptify", "translate"];
+
+const MAX_ACTION_NAME_CHARS: usize = 60;
+
+// Hotkeys the recording state machine already owns
+const RESERVED_HOTKEYS: &[&str] = &["AltGr", "Escape", "ShiftLeft", "ShiftRight"];
+
+// Action hotkeys currently registered (refreshed whenever the store is loaded or written)
+static ACTION_HOTKEYS: Lazy<Mutex<Vec<(RdevKey, String)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+// Action whose hotkey is physically held (set by the rdev callback)
+static PRESSED_ACTION: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+// Action the take currently being recorded will be run through
+static CURRENT_TAKE_ACTION: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// Where an action's result goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionOutputMode {
+    #[default]
+    Replace, // The result is pasted instead of the input
+    Append,    // The result is pasted after the input
+    Clipboard, // The result is copied to the clipboard, nothing is pasted
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserAction {
+    #[serde(default)]
+    pub id: String, // Assigned on save when empty
+    pub name: String,
+    pub template: String, // A prompt_template; must use ${text}
+    #[serde(default)]
+    pub output_mode: ActionOutputMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>, // None: the provider's default model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>, // Key name, see parse_hotkey
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiActionsStore {
+    pub version: u32,
+    #[serde(default)]
+    pub prompt_overrides: HashMap<String, String>, // Built-in action id -> template
+    #[serde(default)]
+    pub actions: Vec<UserAction>,
+    // Legacy prompts the migration left out, kept until the user has seen them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub migration_issues: Vec<String>,
+}
+
+impl Default for AiActionsStore {
+    fn default() -> Self {
+        Self { version: STORE_VERSION, prompt_overrides: HashMap::new(), actions: Vec::new(), migration_issues: Vec::new() }
+    }
+}
+
+impl AiActionsStore {
+    pub fn action(&self, action_id: &str) -> Option<&UserAction> {
+        self.actions.iter().find(|action| action.id == action_id)
+    }
+
+    /// A built-in action or one of the user's
+    pub fn has_action(&self, action_id: &str) -> bool {
+        BUILTIN_ACTION_IDS.contains(&action_id) || self.action(action_id).is_some()
+    }
+}
+
+/// Map a key name from the settings to an rdev key
+pub fn parse_hotkey(name: &str) -> Option<RdevKey> {
+    let key = match name.trim() {
+        "F1" => RdevKey::F1,
+        "F2" => RdevKey::F2,
+        "F3" => RdevKey::F3,
+        "F4" => RdevKey::F4,
+        "F5" => RdevKey::F5,
+        "F6" => RdevKey::F6,
+        "F7" => RdevKey::F7,
+        "F8" => RdevKey::F8,
+        "F9" => RdevKey::F9,
+        "F10" => RdevKey::F10,
+        "F11" => RdevKey::F11,
+        "F12" => RdevKey::F12,
+        "Insert" => RdevKey::Insert,
+        "Home" => RdevKey::Home,
+        "End" => RdevKey::End,
+        "PageUp" => RdevKey::PageUp,
+        "PageDown" => RdevKey::PageDown,
+        "PrintScreen" => RdevKey::PrintScreen,
+        "ScrollLock" => RdevKey::ScrollLock,
+        "NumLock" => RdevKey::NumLock,
+        "Pause" => RdevKey::Pause,
+        "ControlRight" => RdevKey::ControlRight,
+        "MetaRight" => RdevKey::MetaRight,
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn validate_action(action: &UserAction, others: &[UserAction]) -> Result<(), String> {
+    let name = action.name.trim();
+    if name.is_empty() {
+        return Err("Action name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_ACTION_NAME_CHARS {
+        return Err(format!("Action name is too long (maximum {} characters)", MAX_ACTION_NAME_CHARS));
+    }
+    if BUILTIN_ACTION_IDS.contains(&action.id.as_str()) {
+        return Err(format!("'{}' is a built-in action; override its prompt instead", action.id));
+    }
+    crate::prompt_template::validate(&action.template)?;
+    if action.model.as_deref().is_some_and(|model| model.trim().is_empty()) {
+        return Err("Model cannot be blank; leave it unset to use the default".to_string());
+    }
+    if let Some(hotkey) = action.hotkey.as_deref() {
+        if RESERVED_HOTKEYS.contains(&hotkey) {
+            return Err(format!("{} is already used by Fethr", hotkey));
+        }
+        if parse_hotkey(hotkey).is_none() {
+            return Err(format!("Unsupported hotkey '{}'", hotkey));
+        }
+        if let Some(other) = others.iter().find(|other| other.id != action.id && other.hotkey.as_deref() == Some(hotkey)) {
+            return Err(format!("{} is already the hotkey of '{}'", hotkey, other.name));
+        }
+    }
+    Ok(())
+}
+
+/// Add or replace `action` (matched by id); returns the stored action
+pub fn upsert_action(store: &mut AiActionsStore, mut action: UserAction) -> Result<UserAction, String> {
+    if action.id.trim().is_empty() {
+        action.id = format!("user_{}", Uuid::new_v4().simple());
+    }
+    action.name = action.name.trim().to_string();
+    action.model = action.model.map(|model| model.trim().to_string());
+    action.hotkey = action.hotkey.map(|hotkey| hotkey.trim().to_string()).filter(|hotkey| !hotkey.is_empty());
+    validate_action(&action, &store.actions)?;
+
+    match store.actions.iter_mut().find(|existing| existing.id == action.id) {
+        Some(existing) => *existing = action.clone(),
+        None => store.actions.push(action.clone()),
+    }
+    Ok(action)
+}
+
+/// Parse ai_actions.json, refusing files written by a newer Fethr
+pub fn parse_store(data: &str) -> Result<AiActionsStore, String> {
+    if data.trim().is_empty() {
+        return Ok(AiActionsStore::default());
+    }
+    let store: AiActionsStore = serde_json::from_str(data).map_err(|e| format!("Failed to parse AI actions JSON: {}", e))?;
+    if store.version > STORE_VERSION {
+        return Err(format!(
+            "AI actions file is version {}, this version of Fethr only reads up to {}",
+            store.version, STORE_VERSION
+        ));
+    }
+    Ok(AiActionsStore { version: STORE_VERSION, ..store })
+}
+
+/// custom_prompts.json was a flat action_id -> template map. Prompts that fail the template
+/// check are left out and listed in `migration_issues`.
+fn migrate_legacy_prompts(data: &str) -> Result<AiActionsStore, String> {
+    if data.trim().is_empty() {
+        return Ok(AiActionsStore::default());
+    }
+    let legacy: HashMap<String, String> =
+        serde_json::from_str(data).map_err(|e| format!("Failed to parse custom prompts JSON: {}", e))?;
+    let mut prompt_overrides = HashMap::new();
+    let mut rejected = Vec::new();
+    for (action_id, prompt) in legacy {
+        let template = escape_legacy_template(&prompt);
+        match crate::prompt_template::validate(&template) {
+            Ok(_) => {
+                prompt_overrides.insert(action_id, template);
+            }
+            Err(e) => rejected.push(format!("{}: {}", action_id, e)),
+        }
+    }
+    rejected.sort();
+    Ok(AiActionsStore { prompt_overrides, migration_issues: rejected, ..AiActionsStore::default() })
+}
+
+// Legacy prompts predate the template language: a ${ that isn't one of its variables was
+// plain text there, so it's escaped as $${ to stay plain text
+fn escape_legacy_template(source: &str) -> String {
+    let mut escaped = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find("${") {
+        escaped.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let is_variable = after.find('}')
+            .map(|end| &after[..end])
+            .is_some_and(|name| !name.contains('\n') && crate::prompt_template::VARIABLES.contains(&name.trim()));
+        escaped.push_str(if is_variable { "${" } else { "$${" });
+        rest = after;
+    }
+    escaped.push_str(rest);
+    escaped
+}
+
+fn write_store_to(path: &Path, store: &AiActionsStore) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(store).map_err(|e| format!("Failed to serialize AI actions: {}", e))?;
+    fs::write(path, data).map_err(|e| format!("Failed to write AI actions file: {}", e))
+}
+
+/// Load the store from `config_dir`, migrating custom_prompts.json the first time
+pub fn load_store_from(config_dir: &Path) -> Result<AiActionsStore, String> {
+    let path = config_dir.join(AI_ACTIONS_FILENAME);
+    if path.exists() {
+        let data = fs::read_to_string(&path).map_err(|e| format!("Failed to read AI actions file: {}", e))?;
+        return parse_store(&data);
+    }
+
+    let legacy_path = config_dir.join(LEGACY_CUSTOM_PROMPTS_FILENAME);
+    if !legacy_path.exists() {
+        return Ok(AiActionsStore::default());
+    }
+    let data = fs::read_to_string(&legacy_path).map_err(|e| format!("Failed to read custom prompts file: {}", e))?;
+    let store = migrate_legacy_prompts(&data)?;
+    write_store_to(&path, &store)?;
+    if let Err(e) = fs::rename(&legacy_path, legacy_path.with_extension("json.migrated")) {
+        warn!("[AI Actions] Migrated custom prompts but could not rename the old file: {}", e);
+    }
+    info!("[AI Actions] Migrated {} custom prompt(s) from {}", store.prompt_overrides.len(), LEGACY_CUSTOM_PROMPTS_FILENAME);
+    if !store.migration_issues.is_empty() {
+        warn!("[AI Actions] {} custom prompt(s) couldn't be migrated: {}", store.migration_issues.len(), store.migration_issues.join("; "));
+    }
+    Ok(store)
+}
+
+fn config_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app_handle
+        .path_resolver()
+        .app_config_dir()
+        .ok_or_else(|| "Failed to get app config directory".to_string())?;
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    Ok(config_dir)
+}
+
+fn register_hotkeys(store: &AiActionsStore) {
+    let hotkeys: Vec<(RdevKey, String)> = store.actions.iter()
+        .filter_map(|action| {
+            let key = parse_hotkey(action.hotkey.as_deref()?)?;
+            Some((key, action.id.clone()))
+        })
+        .collect();
+    *ACTION_HOTKEYS.lock().unwrap() = hotkeys;
+}
+
+pub fn load_store(app_handle: &AppHandle) -> Result<AiActionsStore, String> {
+    let store = load_store_from(&config_dir(app_handle)?)?;
+    register_hotkeys(&store);
+    Ok(store)
+}
+
+pub fn write_store(app_handle: &AppHandle, store: &AiActionsStore) -> Result<(), String> {
+    write_store_to(&config_dir(app_handle)?.join(AI_ACTIONS_FILENAME), store)?;
+    register_hotkeys(store);
+    Ok(())
+}
+
+/// Called at startup: runs the migration and registers action hotkeys
+pub fn init(app_handle: &AppHandle) {
+    match load_store(app_handle) {
+        Ok(store) => info!("[AI Actions] Loaded {} user action(s)", store.actions.len()),
+        Err(e) => warn!("[AI Actions] Could not load AI actions: {}", e),
+    }
+}
+
+// --- Action hotkeys ---
+
+/// The action bound to `key`, if any
+pub fn action_for_key(key: RdevKey) -> Option<String> {
+    let hotkeys = ACTION_HOTKEYS.lock().unwrap();
+    hotkeys.iter().find(|(bound, _)| *bound == key).map(|(_, id)| id.clone())
+}
+
+pub fn set_pressed_action(action_id: Option<String>) {
+    *PRESSED_ACTION.lock().unwrap() = action_id;
+}
+
+/// Called when a recording starts: remember whether it was started by an action hotkey
+pub fn arm_for_new_take() -> Option<String> {
+    let action_id = PRESSED_ACTION.lock().unwrap().clone();
+    *CURRENT_TAKE_ACTION.lock().unwrap() = action_id.clone();
+    action_id
+}
+
+pub fn take_is_armed() -> bool {
+    CURRENT_TAKE_ACTION.lock().unwrap().is_some()
+}
+
+/// Consume the action for the take being stopped
+pub fn take_armed_action() -> Option<String> {
+    CURRENT_TAKE_ACTION.lock().unwrap().take()
+}
+
+/// A cancelled take must not leave the next one armed
+pub fn disarm() {
+    *CURRENT_TAKE_ACTION.lock().unwrap() = None;
+}
+
+/// Text to paste (if any) and text to leave on the clipboard for an action's result
+pub fn compose_output(mode: ActionOutputMode, input: &str, result: &str) -> (Option<String>, String) {
+    match mode {
+        ActionOutputMode::Replace => (Some(result.to_string()), result.to_string()),
+        ActionOutputMode::Append => {
+            let combined = format!("{}\n\n{}", input.trim_end(), result.trim_start());
+            (Some(combined.clone()), combined)
+        }
+        ActionOutputMode::Clipboard => (None, result.to_string()),
+    }
+}
+
+/// Run a dictation through its hotkey action
+pub async fn run_action_on_dictation(app_handle: &AppHandle, action_id: &str, text: &str) -> Result<(ActionOutputMode, String), String> {
+    let store = load_store(app_handle)?;
+    let action = store.action(action_id)
+        .ok_or_else(|| format!("AI action '{}' no longer exists", action_id))?;
+    info!("[AI Actions] Running '{}' on dictated text ({} chars)", action.name, text.len());
+    let result = crate::ai_actions_manager::run_template(app_handle, action_id, &action.template, text, action.model.as_deref(), None, None).await?;
+    Ok((action.output_mode, result.trim().to_string()))
+}
+
+/// Apply a hotkey action to a finished dictation. Returns whether to paste and the text
+/// for the clipboard; if the action fails the dictation is pasted unchanged and the UI
+/// is told why.
+pub async fn apply_to_dictation(app_handle: &AppHandle, action_id: &str, text: String) -> (bool, String) {
+    match run_action_on_dictation(app_handle, action_id, &text).await {
+        Ok((mode, output)) if !output.is_empty() => {
+            let (paste, clipboard) = compose_output(mode, &text, &output);
+            (paste.is_some(), clipboard)
+        }
+        Ok(_) => (true, text),
+        Err(e) => {
+            error!("[AI Actions] Action on dictation failed: {}", e);
+            let _ = app_handle.emit_all("fethr-ai-action-failed", e);
+            (true, text)
+        }
+    }
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub fn get_ai_actions(app_handle: AppHandle) -> Result<Vec<UserAction>, String> {
+    Ok(load_store(&app_handle)?.actions)
+}
+
+#[tauri::command]
+pub fn save_ai_action(app_handle: AppHandle, action: UserAction) -> Result<UserAction, String> {
+    println!("[RUST CMD] save_ai_action: '{}' (id: '{}')", action.name, action.id);
+    let mut store = load_store(&app_handle)?;
+    let saved = upsert_action(&mut store, action)?;
+    let (command_mode, pause_hotkey) = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        (settings_guard.command_mode.clone(), settings_guard.pause_hotkey.clone())
+    };
+    if command_mode.enabled && saved.hotkey.as_deref() == Some(command_mode.hotkey.trim()) {
+        return Err(format!("{} is the command mode hotkey", command_mode.hotkey.trim()));
+    }
+    if saved.hotkey.as_deref() == Some(pause_hotkey.trim()) {
+        return Err(format!("{} is the pause hotkey", pause_hotkey.trim()));
+    }
+    write_store(&app_handle, &store)?;
+    Ok(saved)
+}
+
+/// Custom prompts the migration from custom_prompts.json left out (they use the built-in
+/// default again), until dismissed. The originals stay in custom_prompts.json.migrated.
+#[tauri::command]
+pub fn get_ai_action_migration_issues(app_handle: AppHandle) -> Result<Vec<String>, String> {
+    Ok(load_store(&app_handle)?.migration_issues)
+}
+
+#[tauri::command]
+pub fn dismiss_ai_action_migration_issues(app_handle: AppHandle) -> Result<(), String> {
+    let mut store = load_store(&app_handle)?;
+    if store.migration_issues.is_empty() {
+        return Ok(());
+    }
+    store.migration_issues.clear();
+    write_store(&app_handle, &store)
+}
+
+#[tauri::command]
+pub fn delete_ai_action(app_handle: AppHandle, action_id: String) -> Result<(), String> {
+    println!("[RUST CMD] delete_ai_action: {}", action_id);
+    let mut store = load_store(&app_handle)?;
+    let before = store.actions.len();
+    store.actions.retain(|action| action.id != action_id);
+    if store.actions.len() == before {
+        return Ok(()); // Nothing to delete
+    }
+    write_store(&app_handle, &store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(name: &str, hotkey: Option<&str>) -> UserAction {
+        UserAction {
+            id: String::new(),
+            name: name.to_string(),
+            template: "Make this a haiku:\n${text}".to_string(),
+            output_mode: ActionOutputMode::Replace,
+            model: None,
+            hotkey: hotkey.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn migrates_custom_prompts_json() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(LEGACY_CUSTOM_PROMPTS_FILENAME), r#"{"summarize": "TL;DR: ${text}"}"#).unwrap();
+
+        let store = load_store_from(dir.path()).unwrap();
+        assert!(store.migration_issues.is_empty());
+        assert_eq!(store.version, STORE_VERSION);
+        assert_eq!(store.prompt_overrides.get("summarize").map(String::as_str), Some("TL;DR: ${text}"));
+        assert!(dir.path().join(AI_ACTIONS_FILENAME).exists());
+        assert!(!dir.path().join(LEGACY_CUSTOM_PROMPTS_FILENAME).exists());
+        assert!(dir.path().join("custom_prompts.json.migrated").exists());
+
+        // Later loads read the new file
+        assert_eq!(load_store_from(dir.path()).unwrap(), store);
+    }
+
+    #[test]
+    fn escapes_and_checks_migrated_prompts() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = r#"{"email": "Write an email for ${name}: ${text}", "summarize": "Summarize ${clipboard}"}"#;
+        fs::write(dir.path().join(LEGACY_CUSTOM_PROMPTS_FILENAME), legacy).unwrap();
+
+        let store = load_store_from(dir.path()).unwrap();
+        let email = store.prompt_overrides.get("email").unwrap();
+        assert_eq!(email, "Write an email for $${name}: ${text}");
+        assert!(crate::prompt_template::validate(email).is_ok());
+        // Without ${text} there's nowhere to put the text: left to the built-in default
+        assert!(!store.prompt_overrides.contains_key("summarize"));
+        assert_eq!(store.migration_issues.len(), 1);
+        assert!(store.migration_issues[0].starts_with("summarize: "));
+        // Kept until dismissed, so the UI can still show them after startup
+        assert_eq!(load_store_from(dir.path()).unwrap().migration_issues, store.migration_issues);
+    }
+
+    #[test]
+    fn refuses_a_newer_store_version() {
+        assert!(parse_store(r#"{"version": 99, "actions": []}"#).is_err());
+        assert_eq!(parse_store("").unwrap(), AiActionsStore::default());
+    }
+
+    #[test]
+    fn validates_actions_on_save() {
+        let mut store = AiActionsStore::default();
+        let saved = upsert_action(&mut store, action("  Haiku ", Some("F9"))).unwrap();
+        assert!(saved.id.starts_with("user_"));
+        assert_eq!(saved.name, "Haiku");
+
+        // Same id replaces, and may keep its own hotkey
+        let renamed = upsert_action(&mut store, UserAction { name: "Haiku 2".to_string(), ..saved.clone() }).unwrap();
+        assert_eq!(store.actions.len(), 1);
+        assert_eq!(store.action(&saved.id).unwrap().name, "Haiku 2");
+        assert_eq!(renamed.hotkey.as_deref(), Some("F9"));
+
+        assert!(upsert_action(&mut store, action("Other", Some("F9"))).is_err()); // Taken
+        assert!(upsert_action(&mut store, action("Other", Some("AltGr"))).is_err()); // Reserved
+        assert!(upsert_action(&mut store, action("Other", Some("Q"))).is_err()); // Unsupported
+        assert!(upsert_action(&mut store, action(" ", None)).is_err());
+        assert!(upsert_action(&mut store, UserAction { template: "No placeholder".to_string(), ..action("Other", None) }).is_err());
+        assert!(upsert_action(&mut store, UserAction { template: "${text} for ${ap_name}".to_string(), ..action("Other", None) }).is_err());
+        assert!(upsert_action(&mut store, UserAction { id: "summarize".to_string(), ..action("Other", None) }).is_err());
+        assert_eq!(store.actions.len(), 1);
+    }
+
+    #[test]
+    fn composes_output_per_mode() {
+        assert_eq!(compose_output(ActionOutputMode::Replace, "raw", "done"), (Some("done".to_string()), "done".to_string()));
+        assert_eq!(compose_output(ActionOutputMode::Append, "raw ", "done"), (Some("raw\n\ndone".to_string()), "raw\n\ndone".to_string()));
+        assert_eq!(compose_output(ActionOutputMode::Clipboard, "raw", "done"), (None, "done".to_string()));
+    }
+}
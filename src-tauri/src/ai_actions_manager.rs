@@ -1,13 +1,33 @@
-use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use log::{info, warn, error, debug};
-use reqwest;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::Notify;
+
+use crate::ai_cache::{self, CacheKey};
+use crate::ai_provider::AiError;
+use crate::prompt_context::PromptInputs;
+use crate::prompt_template::Template;
+use crate::transcription::DerivedVersion;
+use crate::transcription_queue::JobControl;
 // use tauri::AppHandle; // Kept commented as signature uses tauri::AppHandle
 
 // Assuming custom_prompts module exists and is separate, its functions are called with full path.
 // use crate::custom_prompts; 
 
-const CUSTOM_PROMPT_MAX_CHARS_AI: usize = 500;
-const VERCEL_PROXY_URL_LOCAL: &str = "https://fethr-ai-proxy.vercel.app/api/ai-proxy";
+const CHUNK_EVENT: &str = "fethr-ai-action-chunk";
+
+// In-flight perform_ai_action calls by request id; notified to cancel
+static ACTIVE_REQUESTS: Lazy<Mutex<HashMap<String, Arc<Notify>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// "fethr-ai-action-chunk" payload: the next piece of a streaming result
+#[derive(Debug, Clone, Serialize)]
+pub struct AiActionChunk {
+    pub request_id: String,
+    pub delta: String,
+}
 
 // Helper function to get default prompts (logic moved from main.rs)
 fn get_default_prompt_template_for_action_logic(action_id: &str) -> Result<String, String> {
@@ -32,6 +52,12 @@ fn get_default_prompt_template_for_action_logic(action_id: &str) -> Result<Strin
                 common_output_constraint
             )
         ),
+        "translate" => Ok(
+            format!(
+                r#"Translate the following spoken transcription into the language with ISO 639-1 code "${{target_language}}".\nPreserve the speaker's meaning, tone and register. Keep names, product names and code identifiers unchanged.\nFix obvious transcription slips, but do NOT summarize, add information or answer questions contained in the text.\n{}\n\nTranscription:\n"${{text}}"\n\nTranslation:"#,
+                common_output_constraint
+            )
+        ),
         "promptify" => Ok(
             format!(
                 r#"A user has provided the following spoken idea for a prompt they intend to give to an AI.\nYour task is to meticulously refine this idea into a highly effective, clear, and concise prompt, suitable for a large language model.\nApply prompt engineering best practices:\n- Be extremely specific about the desired output format if implied by the user's idea.\n- Clearly and unambiguously define the task, question, or desired outcome.\n- Suggest a specific role or persona for the target AI only if it clearly enhances the prompt's effectiveness for the user's stated goal.\n- If the user mentions constraints, specific details, a particular style, or examples, ensure these are precisely and clearly incorporated in the refined prompt.\n- Structure the refined prompt for optimal clarity and to guide the AI effectively.\n{}\n\nUser's Spoken Idea for a Prompt:\n"${{text}}"\n\nRefined Prompt:"#,
@@ -51,30 +77,54 @@ fn get_default_prompt_template_for_action_logic(action_id: &str) -> Result<Strin
 }
 
 
-#[derive(Serialize)]
-struct VercelProxyPayloadInternal<'a> {
-    prompt: &'a str,
-    #[serde(skip_serializing_if = "Option::is_none", rename = "apiKey")]
-    api_key: Option<&'a str>,
+/// Runs an AI action. The result streams in as "fethr-ai-action-chunk" events tagged with
+/// `request_id` (generated when the UI doesn't pass one); `cancel_ai_action` aborts it.
+/// Failures come back as an AiError, after retries for the transient ones.
+#[tauri::command]
+pub async fn perform_ai_action(
+    app_handle: tauri::AppHandle, 
+    action: String,
+    text: String,
+    user_api_key: Option<String>,
+    direct_prompt: Option<String>,
+    request_id: Option<String>,
+    history_timestamp: Option<String>, // History entry `text` came from; the result is kept with it
+) -> Result<String, AiError> {
+    let request = AiRequest { action, text, user_api_key, direct_prompt, request_id, text_is_selection: false, history_timestamp };
+    run_ai_request(app_handle, request).await
 }
 
-#[derive(Deserialize, Debug)]
-struct AiActionResponseInternal {
-    result: Option<String>,
-    error: Option<String>,
+/// Apply a spoken `instruction` to text selected in another app (command mode). The
+/// selection is also available to the instruction as `${selected_text}`.
+pub async fn apply_instruction_to_selection(app_handle: &tauri::AppHandle, selection: &str, instruction: &str) -> Result<String, String> {
+    let request = AiRequest {
+        action: "command".to_string(),
+        text: selection.to_string(),
+        user_api_key: None,
+        direct_prompt: Some(instruction.to_string()),
+        request_id: None,
+        text_is_selection: true,
+        history_timestamp: None,
+    };
+    run_ai_request(app_handle.clone(), request).await.map_err(|e| e.to_string())
 }
 
-
-#[tauri::command]
-pub fn perform_ai_action(
-    app_handle: tauri::AppHandle, 
+struct AiRequest {
     action: String,
     text: String,
     user_api_key: Option<String>,
-    direct_prompt: Option<String>
-) -> Result<String, String> {
+    direct_prompt: Option<String>,
+    request_id: Option<String>,
+    text_is_selection: bool,
+    history_timestamp: Option<String>,
+}
+
+async fn run_ai_request(app_handle: tauri::AppHandle, request: AiRequest) -> Result<String, AiError> {
+    let AiRequest { action, text, user_api_key, direct_prompt, request_id, text_is_selection, history_timestamp } = request;
+    let request_id = request_id.filter(|id| !id.trim().is_empty()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
     info!(
-        "[AI Action] Called. Action: '{}', Text length: {}, Has BYOK: {}, Has direct_prompt: {}",
+        "[AI Action] Called. Request: {}, Action: '{}', Text length: {}, Has BYOK: {}, Has direct_prompt: {}",
+        request_id,
         action,
         text.len(),
         user_api_key.is_some(),
@@ -83,132 +133,238 @@ pub fn perform_ai_action(
 
     if direct_prompt.is_some() && direct_prompt.as_ref().map_or(false, |dp| !dp.trim().is_empty()) && text.trim().is_empty() {
         warn!("[AI Action] Direct prompt received, but the transcription text to apply it to is empty.");
-        return Err("Cannot apply a custom prompt: The transcription text is empty.".to_string());
+        return Err(AiError::InvalidTemplate { message: "Cannot apply a custom prompt: The transcription text is empty.".to_string() });
     }
 
-    let final_prompt: String;
+    let mut final_prompt: String;
+    let mut fingerprint: String; // The prompt without the text, for the cache key
+    let uses_style_guide: bool;
+    let mut action_model: Option<String> = None;
 
     if let Some(dp_text_untrimmed) = direct_prompt.filter(|s| !s.trim().is_empty()) {
         let dp_text = dp_text_untrimmed.trim();
-
-        if dp_text.chars().count() > CUSTOM_PROMPT_MAX_CHARS_AI {
-            warn!(
-                "[AI Action] Direct prompt exceeds maximum length of {} characters. Length: {}",
-                CUSTOM_PROMPT_MAX_CHARS_AI,
-                dp_text.chars().count()
-            );
-            return Err(format!(
-                "Custom prompt is too long. Maximum allowed length is {} characters.",
-                CUSTOM_PROMPT_MAX_CHARS_AI
-            ));
-        }
+        // Length is checked against the provider's token budget when the prompt is sent
+        let instruction = Template::parse(dp_text).map_err(|e| AiError::InvalidTemplate { message: format!("Invalid custom prompt: {}", e) })?;
 
         info!("[AI Action] Using direct_prompt (length {}): {:.100}...", dp_text.chars().count(), dp_text);
-        if dp_text.contains("${text}") {
-            let user_prompt_with_text = dp_text.replace("${text}", &text);
-            final_prompt = format!(
+        let inputs = PromptInputs { text: &text, selected_text: text_is_selection.then_some(text.as_str()), ..Default::default() };
+        let rendered = crate::prompt_context::render_prompt(&app_handle, dp_text, &inputs).await.map_err(invalid_template)?;
+        uses_style_guide = rendered.uses_style_guide;
+        if instruction.uses("text") {
+            let frame = |user_prompt_with_text: &str| format!(
                 "Please process the following text according to the user's detailed instruction. Ensure your entire response consists ONLY of the processed text, without any additional conversational filler, introductions, or explanations, unless explicitly part of the transformed text.\n\nUser's Instruction with Embedded Text:\n{}",
                 user_prompt_with_text
             );
+            final_prompt = frame(&rendered.prompt);
+            fingerprint = frame(&rendered.fingerprint);
             info!("[AI Action] Direct prompt contained ${{text}}. Framed and text injected.");
         } else {
-            final_prompt = format!(
+            let frame = |user_instruction: &str, original_text: &str| format!(
                 "Please apply the following user instruction to the provided text. Ensure your entire response consists ONLY of the processed text, without any additional conversational filler, introductions, or explanations, unless explicitly part of the transformed text.\n\nUser's Instruction:\n{}\n\nOriginal Text:\n{}",
-                dp_text, 
-                text
+                user_instruction,
+                original_text
             );
+            final_prompt = frame(&rendered.prompt, &text);
+            fingerprint = frame(&rendered.fingerprint, crate::prompt_context::TEXT_MARKER);
             info!("[AI Action] Direct prompt did NOT contain ${{text}}. Framed prompt constructed.");
         }
     } else {
         info!("[AI Action] No direct_prompt. Looking up template for action: '{}'", action);
-        
-        let prompt_template = match crate::custom_prompts::get_custom_prompt(app_handle.clone(), action.clone()) {
-            Ok(Some(custom_template)) => {
-                info!("[AI Action] Using custom prompt template for action '{}'", action);
-                custom_template
-            }
-            Ok(None) => {
-                info!("[AI Action] No custom prompt template for action '{}'. Using default.", action);
-                match get_default_prompt_template_for_action_logic(&action) { 
-                    Ok(default_template) => default_template,
-                    Err(e) => {
-                        let err_msg = format!("Failed to get default prompt template (via local logic) for action '{}': {}", action, e);
-                        error!("[AI Action] {}", err_msg);
-                        return Err(err_msg);
-                    }
-                }
-            }
-            Err(e) => {
-                let err_msg = format!("Error fetching custom prompt template for action '{}': {}. Falling back to default.", action, e);
-                error!("[AI Action] {}", err_msg);
-                match get_default_prompt_template_for_action_logic(&action) { // Fallback
-                    Ok(default_template) => default_template,
-                    Err(e_default) => {
-                        let err_msg_default = format!("Failed to get ANY prompt template (via local logic) for action '{}': {}", action, e_default);
-                        error!("[AI Action] {}", err_msg_default);
-                        return Err(err_msg_default);
-                    }
-                }
-            }
-        };
-        final_prompt = prompt_template.replace("${text}", &text);
+        let (prompt_template, model) = resolve_action_template(&app_handle, &action).map_err(invalid_template)?;
+        let inputs = PromptInputs { text: &text, ..Default::default() };
+        let rendered = crate::prompt_context::render_prompt(&app_handle, &prompt_template, &inputs).await.map_err(invalid_template)?;
+        final_prompt = rendered.prompt;
+        fingerprint = rendered.fingerprint;
+        uses_style_guide = rendered.uses_style_guide;
+        action_model = model;
         info!("[AI Action] Using template-based prompt for action '{}'.", action);
     }
 
+    if !uses_style_guide {
+        final_prompt = with_style_guide(&app_handle, &action, final_prompt);
+        fingerprint = with_style_guide(&app_handle, &action, fingerprint); // A changed profile misses the cache
+    }
+
     debug!("[AI Action] Final assembled prompt (first 200 chars): {:.200}", final_prompt.chars().take(200).collect::<String>());
-    if user_api_key.is_some() && user_api_key.as_ref().map_or(false, |k| !k.trim().is_empty()) {
-        info!("[AI Action] Using user-provided API key for this request.");
-    } else {
-        info!("[AI Action] No user-provided API key; proxy will use fallback app key.");
+
+    let (provider, model) = crate::ai_provider::selected_model(action_model.as_deref());
+    let cache_key = CacheKey::new(&action, &fingerprint, &text, provider, &model);
+    let emit_chunk = |delta: &str| {
+        let chunk = AiActionChunk { request_id: request_id.clone(), delta: delta.to_string() };
+        if let Err(e) = app_handle.emit_all(CHUNK_EVENT, chunk) {
+            warn!("[AI Action] Failed to emit chunk event: {}", e);
+        }
+    };
+    if let Some(cached) = ai_cache::lookup(&app_handle, &cache_key) {
+        info!("[AI Action] Request {}: cached result for '{}' ({:?}, {})", request_id, action, provider, model);
+        emit_chunk(&cached);
+        keep_with_history_entry(&app_handle, history_timestamp.as_deref(), &cache_key, &cached);
+        return Ok(cached);
     }
 
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(60))
-        .build()
-        .map_err(|e| {
-            error!("[AI Action] Failed to build HTTP client: {}", e);
-            format!("Failed to build HTTP client: {}", e)
-        })?;
-
-    let request_payload = VercelProxyPayloadInternal {
-        prompt: &final_prompt,
-        api_key: user_api_key.as_ref().map(|s| s.as_str()).filter(|s| !s.trim().is_empty()),
+    let cancel = Arc::new(Notify::new());
+    ACTIVE_REQUESTS.lock().unwrap().insert(request_id.clone(), cancel.clone());
+    // Dropping the request future stops HTTP providers; a local model process has to be killed
+    let process_control = Arc::new(JobControl::default());
+    let result = tokio::select! {
+        result = send_prompt(&final_prompt, user_api_key.as_deref(), action_model.as_deref(), Some(process_control.clone()), &emit_chunk) => result,
+        _ = cancel.notified() => {
+            info!("[AI Action] Request {} cancelled", request_id);
+            process_control.cancel();
+            Err(AiError::Cancelled)
+        }
     };
+    ACTIVE_REQUESTS.lock().unwrap().remove(&request_id);
+    if let Ok(result_text) = &result {
+        ai_cache::store(&app_handle, cache_key.clone(), result_text);
+        keep_with_history_entry(&app_handle, history_timestamp.as_deref(), &cache_key, result_text);
+    }
+    result
+}
 
-    match client.post(VERCEL_PROXY_URL_LOCAL) 
-        .json(&request_payload)
-        .send()
+// The user's style guide goes after the prompt of the actions the profile applies to
+fn with_style_guide(app_handle: &tauri::AppHandle, action_id: &str, prompt: String) -> String {
+    match crate::style_profile::guide_for_action(app_handle, action_id) {
+        Some(guide) => format!("{}\n\n{}", prompt, guide),
+        None => prompt,
+    }
+}
+
+// The prompt for the request couldn't be built
+fn invalid_template(message: String) -> AiError {
+    AiError::InvalidTemplate { message }
+}
+
+// Store an action's result as a derived version of the history entry it was run on
+fn keep_with_history_entry(app_handle: &tauri::AppHandle, history_timestamp: Option<&str>, cache_key: &CacheKey, result_text: &str) {
+    let Some(timestamp) = history_timestamp.filter(|t| !t.trim().is_empty()) else {
+        return;
+    };
+    let version = DerivedVersion {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now(),
+        action: cache_key.action.clone(),
+        text: result_text.to_string(),
+        provider: Some(cache_key.provider),
+        model: Some(cache_key.model.clone()),
+        cache_key: Some(cache_key.id()),
+    };
+    if let Err(e) = crate::transcription::add_derived_version(app_handle, timestamp, version) {
+        warn!("[AI Action] Could not keep the result with history entry {}: {}", timestamp, e);
+    }
+}
+
+/// Abort a running perform_ai_action; the pending call returns an error
+#[tauri::command]
+pub fn cancel_ai_action(request_id: String) -> Result<(), String> {
+    let active = ACTIVE_REQUESTS.lock().unwrap();
+    let cancel = active.get(&request_id).ok_or_else(|| format!("No AI action request {}", request_id))?;
+    cancel.notify_one(); // Stored as a permit if the request isn't waiting yet
+    Ok(())
+}
+
+// Template and model for `action_id`: a user-defined action, else the built-in action's
+// template (the user's override if there is one, the default otherwise)
+fn resolve_action_template(app_handle: &tauri::AppHandle, action_id: &str) -> Result<(String, Option<String>), String> {
+    // The small offline models only get the built-in actions they handle well
+    let provider = crate::config::SETTINGS.lock().unwrap().ai_provider.provider;
+    if provider == crate::config::AiProviderKind::Local
+        && crate::ai_actions::BUILTIN_ACTION_IDS.contains(&action_id)
+        && !crate::local_llm::LOCAL_ACTIONS.contains(&action_id)
     {
-        Ok(response) => {
-            let status = response.status();
-            if status.is_success() {
-                match response.json::<AiActionResponseInternal>() { 
-                    Ok(ai_response) => {
-                        if let Some(result_text) = ai_response.result {
-                            info!("[AI Action] Successfully received AI response (length {}).", result_text.len());
-                            Ok(result_text)
-                        } else if let Some(err_msg) = ai_response.error {
-                            error!("[AI Action] AI service returned an error: {}", err_msg);
-                            Err(format!("AI service error: {}", err_msg))
-                        } else {
-                            error!("[AI Action] Invalid response structure from AI service (no result or error).");
-                            Err("Invalid response structure from AI service.".to_string())
-                        }
-                    }
-                    Err(e) => {
-                        error!("[AI Action] Failed to parse AI service response: {}", e);
-                        Err(format!("Failed to parse AI service response: {}", e))
-                    }
-                }
-            } else {
-                let error_text = response.text().unwrap_or_else(|_| "Could not read error body from AI service.".to_string());
-                error!("[AI Action] AI service request failed. Status: {}. Body: {}", status, error_text);
-                Err(format!("AI service request failed with status {}: {}", status, error_text))
-            }
+        return Err(format!(
+            "'{}' isn't available with the offline model, which handles {}. Choose another AI provider to use it.",
+            action_id,
+            crate::local_llm::LOCAL_ACTIONS.join(", ")
+        ));
+    }
+    let store = match crate::ai_actions::load_store(app_handle) {
+        Ok(store) => store,
+        Err(e) => {
+            error!("[AI Action] Error loading AI actions: {}. Using the default template for '{}'.", e, action_id);
+            return Ok((get_default_prompt_template_for_action_logic(action_id)?, None));
+        }
+    };
+    if let Some(user_action) = store.action(action_id) {
+        info!("[AI Action] Using user-defined action '{}'", user_action.name);
+        return Ok((user_action.template.clone(), user_action.model.clone()));
+    }
+    match store.prompt_overrides.get(action_id) {
+        Some(custom_template) => {
+            info!("[AI Action] Using custom prompt template for action '{}'", action_id);
+            Ok((custom_template.clone(), None))
+        }
+        None => {
+            info!("[AI Action] No custom prompt template for action '{}'. Using default.", action_id);
+            Ok((get_default_prompt_template_for_action_logic(action_id)?, None))
+        }
+    }
+}
+
+/// Fill `template` with `text` and its other variables and send it, without streaming events.
+/// `action_id` decides whether the user's style guide is added.
+pub async fn run_template(
+    app_handle: &tauri::AppHandle,
+    action_id: &str,
+    template: &str,
+    text: &str,
+    model: Option<&str>,
+    user_api_key: Option<&str>,
+    cancel: Option<Arc<JobControl>>, // Kills a local model process once cancelled
+) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Cannot run the AI action: the text is empty.".to_string());
+    }
+    let inputs = PromptInputs { text, ..Default::default() };
+    let rendered = crate::prompt_context::render_prompt(app_handle, template, &inputs).await?;
+    let final_prompt = if rendered.uses_style_guide {
+        rendered.prompt
+    } else {
+        with_style_guide(app_handle, action_id, rendered.prompt)
+    };
+    send_prompt(&final_prompt, user_api_key, model, cancel, &|_| {}).await.map_err(|e| e.to_string())
+}
+
+/// Run a built-in or user-defined action on `text`, without streaming events
+pub async fn run_action(app_handle: &tauri::AppHandle, action_id: &str, text: &str, cancel: Option<Arc<JobControl>>) -> Result<String, String> {
+    let (template, model) = resolve_action_template(app_handle, action_id)?;
+    run_template(app_handle, action_id, &template, text, model.as_deref(), None, cancel).await
+}
+
+/// Translate dictated text into `target_language` (ISO code) with the "translate" action.
+/// A user-customised "translate" prompt is honoured; `${target_language}` and `${text}` are filled in.
+pub async fn translate_text(app_handle: &tauri::AppHandle, text: &str, target_language: &str) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err("Cannot translate: the transcription text is empty.".to_string());
+    }
+    let (template, _) = resolve_action_template(app_handle, "translate")?;
+    let inputs = PromptInputs { text, target_language: Some(target_language), ..Default::default() };
+    let final_prompt = crate::prompt_context::render(app_handle, &template, &inputs).await?;
+    info!("[AI Action] Translating {} chars to '{}'", text.len(), target_language);
+    send_prompt(&final_prompt, None, None, None, &|_| {}).await.map_err(|e| e.to_string())
+}
+
+async fn send_prompt(
+    final_prompt: &str,
+    user_api_key: Option<&str>,
+    model: Option<&str>,
+    cancel: Option<Arc<JobControl>>,
+    on_chunk: crate::ai_provider::ChunkSink<'_>,
+) -> Result<String, AiError> {
+    let request = crate::ai_provider::ChatRequest {
+        prompt: final_prompt,
+        model,
+        api_key: user_api_key.filter(|k| !k.trim().is_empty()),
+        cancel,
+    };
+    match crate::ai_provider::complete(&request, on_chunk).await {
+        Ok(result_text) => {
+            info!("[AI Action] Successfully received AI response (length {}).", result_text.len());
+            Ok(result_text)
         }
         Err(e) => {
-            error!("[AI Action] Network error calling AI service: {}", e);
-            Err(format!("Network error calling AI service: {}", e))
+            error!("[AI Action] AI request failed: {}", e);
+            Err(e)
         }
     }
 }
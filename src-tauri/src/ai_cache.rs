@@ -0,0 +1,174 @@
+// src-tauri/src/ai_cache.rs
+//
+// Results of AI actions, so running the same action on the same text again doesn't make
+// another (paid) request. Keyed by action, template, text and provider/model. The template
+// part is the prompt rendered with a marker where the text goes, so the values of context
+// variables (clipboard, date, target language, ...) are part of the key too.
+//
+// Kept in ai_cache.json in the app data dir; past MAX_CACHE_ENTRIES the oldest results
+// are dropped.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::config::AiProviderKind;
+
+const CACHE_FILE_NAME: &str = "ai_cache.json";
+pub const MAX_CACHE_ENTRIES: usize = 500;
+
+// Loaded from disk on first use
+static CACHE: Lazy<Mutex<Option<AiCache>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn sha256_hex(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub action: String,
+    pub template_hash: String, // Of the prompt with the text left out
+    pub text_hash: String,
+    pub provider: AiProviderKind,
+    pub model: String,
+}
+
+impl CacheKey {
+    pub fn new(action: &str, template_fingerprint: &str, text: &str, provider: AiProviderKind, model: &str) -> Self {
+        Self {
+            action: action.to_string(),
+            template_hash: sha256_hex(template_fingerprint),
+            text_hash: sha256_hex(text),
+            provider,
+            model: model.to_string(),
+        }
+    }
+
+    /// Short stable id, stored on history entries' derived versions
+    pub fn id(&self) -> String {
+        let joined = format!("{}\n{}\n{}\n{:?}\n{}", self.action, self.template_hash, self.text_hash, self.provider, self.model);
+        sha256_hex(&joined)[..16].to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResult {
+    pub key: CacheKey,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AiCache {
+    entries: Vec<CachedResult>, // Oldest first
+}
+
+impl AiCache {
+    pub fn get(&self, key: &CacheKey) -> Option<&CachedResult> {
+        self.entries.iter().find(|entry| entry.key == *key)
+    }
+
+    /// Add or refresh a result; the oldest results go once the cache is full
+    pub fn insert(&mut self, key: CacheKey, text: String) {
+        self.entries.retain(|entry| entry.key != key);
+        self.entries.push(CachedResult { key, text, created_at: Utc::now() });
+        if self.entries.len() > MAX_CACHE_ENTRIES {
+            let excess = self.entries.len() - MAX_CACHE_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+fn cache_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle.path_resolver().app_data_dir()
+        .map(|dir| dir.join(CACHE_FILE_NAME))
+        .ok_or_else(|| "Failed to get app data directory".to_string())
+}
+
+fn read_cache(app_handle: &AppHandle) -> AiCache {
+    let Ok(path) = cache_path(app_handle) else {
+        return AiCache::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("[AI Cache] Ignoring unreadable {}: {}", CACHE_FILE_NAME, e);
+            AiCache::default()
+        }),
+        Err(_) => AiCache::default(),
+    }
+}
+
+fn write_cache(app_handle: &AppHandle, cache: &AiCache) -> Result<(), String> {
+    let path = cache_path(app_handle)?;
+    let json = serde_json::to_string(cache).map_err(|e| format!("Failed to serialize AI cache: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write AI cache: {}", e))
+}
+
+/// A cached result for `key`, if the action was run before
+pub fn lookup(app_handle: &AppHandle, key: &CacheKey) -> Option<String> {
+    let mut cache = CACHE.lock().unwrap();
+    let cache = cache.get_or_insert_with(|| read_cache(app_handle));
+    cache.get(key).map(|entry| entry.text.clone())
+}
+
+pub fn store(app_handle: &AppHandle, key: CacheKey, text: &str) {
+    let mut cache = CACHE.lock().unwrap();
+    let cache = cache.get_or_insert_with(|| read_cache(app_handle));
+    cache.insert(key, text.to_string());
+    if let Err(e) = write_cache(app_handle, cache) {
+        warn!("[AI Cache] {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn clear_ai_cache(app_handle: AppHandle) -> Result<(), String> {
+    *CACHE.lock().unwrap() = Some(AiCache::default());
+    write_cache(&app_handle, &AiCache::default())?;
+    info!("[AI Cache] Cleared");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(action: &str, text: &str, model: &str) -> CacheKey {
+        CacheKey::new(action, "Summarize: \u{1}text\u{1}", text, AiProviderKind::Anthropic, model)
+    }
+
+    #[test]
+    fn hits_only_for_the_same_action_text_and_model() {
+        let mut cache = AiCache::default();
+        cache.insert(key("summarize", "ship it", "haiku"), "Ship.".to_string());
+
+        assert_eq!(cache.get(&key("summarize", "ship it", "haiku")).map(|e| e.text.as_str()), Some("Ship."));
+        assert!(cache.get(&key("summarize", "ship it now", "haiku")).is_none());
+        assert!(cache.get(&key("email", "ship it", "haiku")).is_none());
+        assert!(cache.get(&key("summarize", "ship it", "sonnet")).is_none());
+        let other_template = CacheKey::new("summarize", "TL;DR: \u{1}text\u{1}", "ship it", AiProviderKind::Anthropic, "haiku");
+        assert!(cache.get(&other_template).is_none());
+        assert_ne!(key("summarize", "a", "m").id(), key("summarize", "b", "m").id());
+    }
+
+    #[test]
+    fn drops_the_oldest_results_when_full() {
+        let mut cache = AiCache::default();
+        for i in 0..MAX_CACHE_ENTRIES + 2 {
+            cache.insert(key("summarize", &i.to_string(), "m"), i.to_string());
+        }
+        assert_eq!(cache.entries.len(), MAX_CACHE_ENTRIES);
+        assert!(cache.get(&key("summarize", "0", "m")).is_none());
+        assert!(cache.get(&key("summarize", "2", "m")).is_some());
+
+        // Re-inserting refreshes instead of duplicating
+        cache.insert(key("summarize", "2", "m"), "again".to_string());
+        assert_eq!(cache.entries.len(), MAX_CACHE_ENTRIES);
+        assert_eq!(cache.get(&key("summarize", "2", "m")).unwrap().text, "again");
+    }
+}
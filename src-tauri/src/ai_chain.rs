@@ -0,0 +1,139 @@
+// src-tauri/src/ai_chain.rs
+//
+// Optional AI actions applied to every dictation between transcription and paste, e.g.
+// "written_form" then "translate". The chain gets a latency budget: if it fails or runs
+// over, the raw transcription is pasted and the UI is told why. The history entry keeps
+// the raw text and records the processed output next to it.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::config::{AiChainSettings, SETTINGS};
+use crate::transcription_queue::JobControl;
+
+/// AI chain output stored on the history entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiChainRecord {
+    pub actions: Vec<String>, // Applied in this order
+    pub text: String,
+    pub elapsed_ms: u64,
+}
+
+/// Run `steps` in order, each on the previous step's output, within `budget`
+pub async fn run_steps<F, Fut>(text: &str, steps: &[String], budget: Duration, mut run_step: F) -> Result<String, String>
+where
+    F: FnMut(String, String) -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let chain = async {
+        let mut current = text.to_string();
+        for step in steps {
+            let output = run_step(step.clone(), current).await
+                .map_err(|e| format!("'{}' failed: {}", step, e))?;
+            let output = output.trim().to_string();
+            if output.is_empty() {
+                return Err(format!("'{}' returned no text", step));
+            }
+            current = output;
+        }
+        Ok(current)
+    };
+    tokio::time::timeout(budget, chain)
+        .await
+        .map_err(|_| format!("took longer than the {} ms budget", budget.as_millis()))?
+}
+
+/// Apply the configured chain to a finished dictation. None when the chain is off or the
+/// raw text should be pasted.
+pub async fn process_dictation(app_handle: &AppHandle, text: &str) -> Option<AiChainRecord> {
+    let AiChainSettings { enabled, actions, latency_budget_ms } = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        settings_guard.ai_chain.clone()
+    };
+    if !enabled || actions.is_empty() || text.trim().is_empty() {
+        return None;
+    }
+
+    // Unknown ids would otherwise fall back to written_form
+    let store = crate::ai_actions::load_store(app_handle).unwrap_or_default();
+    if let Some(missing) = actions.iter().find(|action| !store.has_action(action)) {
+        report_fallback(app_handle, format!("AI action '{}' doesn't exist", missing));
+        return None;
+    }
+
+    info!("[AI Chain] Running {:?} on {} chars (budget {} ms)", actions, text.len(), latency_budget_ms);
+    let started = Instant::now();
+    // Going over the budget drops the step's request; a local model process has to be killed
+    let control = Arc::new(JobControl::default());
+    let result = run_steps(text, &actions, Duration::from_millis(latency_budget_ms), |action, input| {
+        let control = control.clone();
+        async move { crate::ai_actions_manager::run_action(app_handle, &action, &input, Some(control)).await }
+    })
+    .await;
+    if result.is_err() {
+        control.cancel();
+    }
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(processed) => {
+            info!("[AI Chain] Finished in {} ms", elapsed_ms);
+            Some(AiChainRecord { actions, text: processed, elapsed_ms })
+        }
+        Err(e) => {
+            report_fallback(app_handle, e);
+            None
+        }
+    }
+}
+
+fn report_fallback(app_handle: &AppHandle, reason: String) {
+    warn!("[AI Chain] Pasting the raw transcription: {}", reason);
+    let _ = app_handle.emit_all("fethr-ai-chain-fallback", reason);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn feeds_each_step_the_previous_output() {
+        let result = run_steps("um ship it", &steps(&["written_form", "translate"]), Duration::from_secs(1), |action, input| async move {
+            Ok(format!(" {}({}) ", action, input))
+        })
+        .await;
+        assert_eq!(result.unwrap(), "translate(written_form(um ship it))");
+    }
+
+    #[tokio::test]
+    async fn a_failing_step_fails_the_chain() {
+        let result = run_steps("ship it", &steps(&["written_form", "translate"]), Duration::from_secs(1), |action, input| async move {
+            if action == "translate" { Err("HTTP 500".to_string()) } else { Ok(input) }
+        })
+        .await;
+        assert_eq!(result.unwrap_err(), "'translate' failed: HTTP 500");
+
+        let empty = run_steps("ship it", &steps(&["summarize"]), Duration::from_secs(1), |_, _| async { Ok("  ".to_string()) }).await;
+        assert!(empty.is_err());
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_over_budget() {
+        let started = Instant::now();
+        let result = run_steps("ship it", &steps(&["written_form"]), Duration::from_millis(50), |_, input| async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(input)
+        })
+        .await;
+        assert!(result.unwrap_err().contains("50 ms budget"));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
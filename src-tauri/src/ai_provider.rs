@@ -0,0 +1,888 @@
+// src-tauri/src/ai_provider.rs
+//
+// Where AI action prompts are sent. Besides Fethr's hosted relay, prompts can go directly
+// to an OpenAI-compatible chat API (OpenAI itself, or a local Ollama / llama.cpp server)
+// or to Anthropic, so text never passes through a third party. A BYOK key given with a
+// request is sent to the selected provider only, in place of the configured key.
+//
+// The local provider runs a small model through llama.cpp for offline use (local_llm.rs).
+//
+// The direct providers ask for a streamed (SSE) answer and hand each text delta to the
+// caller as it arrives; a server that answers with plain JSON instead still works. The
+// timeout is an idle timeout: it applies to the response headers and to every chunk, so
+// a long answer that keeps streaming isn't cut off.
+//
+// Failures are classified as AiError so the UI can tell a rate limit from a bad key or a
+// network problem. Transient ones (network, timeouts, rate limits, 5xx) are retried with
+// exponential backoff, unless part of the answer has already been streamed.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config::{AiProviderKind, AiProviderSettings, AnthropicProviderSettings, LocalProviderSettings, OpenAiChatProviderSettings, ProxyProviderSettings};
+use crate::transcription_queue::JobControl;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1); // Doubled after each attempt
+// A longer Retry-After is reported to the user rather than waited out
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Why an AI request failed; returned to the UI by perform_ai_action
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AiError {
+    Network { message: String },
+    Timeout { message: String },
+    RateLimited { retry_after: Option<u64>, message: String }, // Seconds, from the Retry-After header
+    Unauthorized { message: String },
+    QuotaExceeded { message: String },
+    ProviderError { status: Option<u16>, message: String },
+    InvalidTemplate { message: String }, // The prompt couldn't be built or is too long
+    Cancelled,
+}
+
+impl AiError {
+    /// Classify a failure status; `body` is the response body
+    pub fn from_response(provider: &str, status: reqwest::StatusCode, retry_after: Option<u64>, body: &str) -> Self {
+        let summary = crate::auth_manager::get_error_message(status, "Request failed");
+        let message = format!("{} ({}, status {}): {}", summary, provider, status.as_u16(), error_message(body));
+        if status == reqwest::StatusCode::PAYMENT_REQUIRED || is_quota_error(body) {
+            return AiError::QuotaExceeded { message };
+        }
+        match status.as_u16() {
+            401 | 403 => AiError::Unauthorized { message },
+            408 => AiError::Timeout { message },
+            429 => AiError::RateLimited { retry_after, message },
+            code => AiError::ProviderError { status: Some(code), message },
+        }
+    }
+
+    /// Worth sending the same request again
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AiError::Network { .. } | AiError::Timeout { .. } | AiError::RateLimited { .. } => true,
+            AiError::ProviderError { status: Some(status), .. } => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for AiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiError::Cancelled => write!(f, "AI action cancelled"),
+            AiError::Network { message }
+            | AiError::Timeout { message }
+            | AiError::RateLimited { message, .. }
+            | AiError::Unauthorized { message }
+            | AiError::QuotaExceeded { message }
+            | AiError::ProviderError { message, .. }
+            | AiError::InvalidTemplate { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for AiError {
+    fn from(message: String) -> Self {
+        AiError::ProviderError { status: None, message }
+    }
+}
+
+// OpenAI reports an empty balance as a 429 with code "insufficient_quota"; others say so in the message
+fn is_quota_error(body: &str) -> bool {
+    let value = serde_json::from_str::<serde_json::Value>(body).unwrap_or_default();
+    let code_is_quota = ["/error/code", "/error/type"].iter()
+        .any(|pointer| value.pointer(pointer).and_then(|v| v.as_str()) == Some("insufficient_quota"));
+    let text = error_message(body).to_lowercase();
+    code_is_quota || text.contains("quota") || text.contains("credit balance")
+}
+
+/// One prompt to complete
+pub struct ChatRequest<'a> {
+    pub prompt: &'a str,
+    pub model: Option<&'a str>, // Overrides the provider's configured model
+    pub api_key: Option<&'a str>, // BYOK key from the UI; overrides the configured key
+    pub cancel: Option<Arc<JobControl>>, // Kills a local model process when the request is cancelled
+}
+
+/// Receives text deltas while a response streams in
+pub type ChunkSink<'a> = &'a (dyn Fn(&str) + Send + Sync);
+
+pub trait AiProvider: Send + Sync {
+    fn kind(&self) -> AiProviderKind;
+    /// Largest prompt the provider is sent, in (estimated) tokens
+    fn max_prompt_tokens(&self) -> u32;
+    /// Configured model; empty when the provider picks one itself
+    fn model(&self) -> &str;
+    /// The complete response text. Streamed deltas go to `on_chunk` as they arrive;
+    /// providers that can't stream send the whole text as one chunk.
+    fn complete<'a>(&'a self, request: &'a ChatRequest<'a>, on_chunk: ChunkSink<'a>) -> BoxFuture<'a, Result<String, AiError>>;
+}
+
+pub fn build_provider(settings: &AiProviderSettings) -> Box<dyn AiProvider> {
+    match settings.provider {
+        AiProviderKind::Proxy => Box::new(ProxyProvider { settings: settings.proxy.clone() }),
+        AiProviderKind::OpenAiCompatible => Box::new(OpenAiChatProvider { settings: settings.openai_compatible.clone() }),
+        AiProviderKind::Anthropic => Box::new(AnthropicProvider { settings: settings.anthropic.clone() }),
+        AiProviderKind::Local => Box::new(LocalProvider { settings: settings.local.clone() }),
+    }
+}
+
+/// Rough token count: about four characters per token for English text
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn check_prompt_budget(provider: &dyn AiProvider, prompt: &str) -> Result<(), AiError> {
+    let tokens = estimate_tokens(prompt);
+    let limit = provider.max_prompt_tokens() as usize;
+    if tokens > limit {
+        return Err(AiError::InvalidTemplate { message: format!(
+            "Prompt is too long for the {:?} provider: about {} tokens, the limit is {}. Shorten the text or raise the limit in the AI provider settings.",
+            provider.kind(), tokens, limit
+        ) });
+    }
+    Ok(())
+}
+
+/// Provider selected in the settings and the model a request with `model_override` goes to
+pub fn selected_model(model_override: Option<&str>) -> (AiProviderKind, String) {
+    let settings = crate::config::SETTINGS.lock().unwrap().ai_provider.clone();
+    let provider = build_provider(&settings);
+    let model = non_empty(model_override).or(non_empty(Some(provider.model()))).unwrap_or("default");
+    (provider.kind(), model.to_string())
+}
+
+/// Send `request` to the provider selected in the settings
+pub async fn complete(request: &ChatRequest<'_>, on_chunk: ChunkSink<'_>) -> Result<String, AiError> {
+    let settings = {
+        let settings_guard = crate::config::SETTINGS.lock().unwrap();
+        settings_guard.ai_provider.clone()
+    };
+    let provider = build_provider(&settings);
+    info!(
+        "[AI Provider] Sending prompt ({} chars) to {:?}, model override: {:?}, BYOK: {}",
+        request.prompt.len(),
+        provider.kind(),
+        request.model,
+        request.api_key.is_some()
+    );
+    check_prompt_budget(provider.as_ref(), request.prompt)?;
+    complete_with_retry(provider.as_ref(), request, on_chunk, RETRY_BASE_DELAY).await
+}
+
+// Retry transient failures. Not once text has been streamed: the UI would see it twice.
+async fn complete_with_retry(
+    provider: &dyn AiProvider,
+    request: &ChatRequest<'_>,
+    on_chunk: ChunkSink<'_>,
+    base_delay: Duration,
+) -> Result<String, AiError> {
+    let streamed = AtomicBool::new(false);
+    let sink = |delta: &str| {
+        streamed.store(true, Ordering::SeqCst);
+        on_chunk(delta);
+    };
+    let mut attempt = 1;
+    loop {
+        let error = match provider.complete(request, &sink).await {
+            Ok(text) => return Ok(text),
+            Err(error) => error,
+        };
+        let delay = match &error {
+            AiError::RateLimited { retry_after: Some(secs), .. } => Duration::from_secs(*secs),
+            _ => base_delay * 2u32.pow(attempt - 1),
+        };
+        if attempt >= MAX_ATTEMPTS || !error.is_retryable() || streamed.load(Ordering::SeqCst) || delay > MAX_RETRY_DELAY {
+            return Err(error);
+        }
+        warn!("[AI Provider] Attempt {} of {} failed ({}); retrying in {:?}", attempt, MAX_ATTEMPTS, error, delay);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+// --- Server-sent events ---
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Splits a byte stream into events. Chunks may end anywhere, even inside a UTF-8 character.
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some((end, separator_len)) = find_event_end(&self.buffer) {
+            let block: Vec<u8> = self.buffer.drain(..end + separator_len).take(end).collect();
+            if let Some(event) = parse_event(&String::from_utf8_lossy(&block)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+// Events end with a blank line: "\n\n" or "\r\n\r\n"
+fn find_event_end(buffer: &[u8]) -> Option<(usize, usize)> {
+    (0..buffer.len()).find_map(|i| {
+        if buffer[i..].starts_with(b"\r\n\r\n") {
+            Some((i, 4))
+        } else if buffer[i..].starts_with(b"\n\n") {
+            Some((i, 2))
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_event(block: &str) -> Option<SseEvent> {
+    let mut event = SseEvent::default();
+    let mut data_lines = Vec::new();
+    for line in block.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event.event = Some(value.to_string()),
+            "data" => data_lines.push(value),
+            _ => {} // Comments (": keep-alive"), id and retry
+        }
+    }
+    if data_lines.is_empty() {
+        return None;
+    }
+    event.data = data_lines.join("\n");
+    Some(event)
+}
+
+/// What a provider makes of one event
+enum StreamItem {
+    Delta(String),
+    Done,
+    Skip,
+}
+
+// --- HTTP helpers ---
+
+fn http_client(timeout_secs: u64) -> Result<reqwest::Client, AiError> {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(timeout_secs))
+        .build()
+        .map_err(|e| AiError::Network { message: format!("Failed to build HTTP client: {}", e) })
+}
+
+fn timed_out(provider: &str, timeout_secs: u64) -> AiError {
+    AiError::Timeout { message: format!("{} did not answer within {} seconds", provider, timeout_secs) }
+}
+
+fn request_error(provider: &str, timeout_secs: u64, error: reqwest::Error) -> AiError {
+    if error.is_timeout() {
+        timed_out(provider, timeout_secs)
+    } else {
+        AiError::Network { message: format!("Network error calling {}: {}", provider, error) }
+    }
+}
+
+// Retry-After in seconds; the HTTP-date form isn't used by AI APIs
+fn retry_after(response: &reqwest::Response) -> Option<u64> {
+    response.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+// Error text from an OpenAI- or Anthropic-style `{"error": {"message": ...}}` body
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| value.pointer("/error/message").and_then(|m| m.as_str()).map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+/// Send the request and wait for the headers; a failure status becomes an error
+async fn send(provider: &str, timeout_secs: u64, builder: reqwest::RequestBuilder) -> Result<reqwest::Response, AiError> {
+    let response = tokio::time::timeout(Duration::from_secs(timeout_secs), builder.send())
+        .await
+        .map_err(|_| timed_out(provider, timeout_secs))?
+        .map_err(|e| request_error(provider, timeout_secs, e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let retry_after = retry_after(&response);
+        let body = read_body(provider, timeout_secs, response).await.unwrap_or_default();
+        error!("[AI Provider] {} request failed. Status: {}. Body: {}", provider, status, body);
+        return Err(AiError::from_response(provider, status, retry_after, &body));
+    }
+    Ok(response)
+}
+
+async fn read_body(provider: &str, timeout_secs: u64, response: reqwest::Response) -> Result<String, AiError> {
+    tokio::time::timeout(Duration::from_secs(timeout_secs), response.text())
+        .await
+        .map_err(|_| timed_out(provider, timeout_secs))?
+        .map_err(|e| AiError::Network { message: format!("Failed to read {} response: {}", provider, e) })
+}
+
+async fn read_json(provider: &str, timeout_secs: u64, response: reqwest::Response) -> Result<serde_json::Value, AiError> {
+    let body = read_body(provider, timeout_secs, response).await?;
+    serde_json::from_str(&body).map_err(|e| AiError::from(format!("Failed to parse {} response: {}", provider, e)))
+}
+
+fn is_event_stream(response: &reqwest::Response) -> bool {
+    response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"))
+}
+
+/// Read an SSE response to its end event, passing each delta on; returns the whole text.
+/// A stream that closes before the end event lost part of the answer: that is a network error.
+async fn read_stream(
+    provider: &str,
+    timeout_secs: u64,
+    response: reqwest::Response,
+    on_chunk: ChunkSink<'_>,
+    interpret: impl Fn(&SseEvent) -> Result<StreamItem, AiError>,
+) -> Result<String, AiError> {
+    let mut stream = response.bytes_stream();
+    let mut parser = SseParser::default();
+    let mut text = String::new();
+    loop {
+        let bytes = match tokio::time::timeout(Duration::from_secs(timeout_secs), stream.next()).await {
+            Err(_) => return Err(AiError::Timeout { message: format!("{} stopped responding for {} seconds", provider, timeout_secs) }),
+            Ok(None) => return Err(AiError::Network { message: format!("{} closed the stream before the answer was complete", provider) }),
+            Ok(Some(Err(e))) => return Err(AiError::Network { message: format!("Network error reading {} response: {}", provider, e) }),
+            Ok(Some(Ok(bytes))) => bytes,
+        };
+        for event in parser.push(&bytes) {
+            match interpret(&event)? {
+                StreamItem::Delta(delta) if !delta.is_empty() => {
+                    on_chunk(&delta);
+                    text.push_str(&delta);
+                }
+                StreamItem::Done => return Ok(text),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn non_empty(key: Option<&str>) -> Option<&str> {
+    key.map(str::trim).filter(|key| !key.is_empty())
+}
+
+fn require_text(provider: &str, text: String) -> Result<String, AiError> {
+    if text.is_empty() {
+        return Err(format!("{} returned no text", provider).into());
+    }
+    Ok(text)
+}
+
+// --- Fethr relay ---
+
+#[derive(Serialize)]
+struct ProxyPayload<'a> {
+    prompt: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "apiKey")]
+    api_key: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'a str>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProxyResponse {
+    result: Option<String>,
+    error: Option<String>,
+}
+
+struct ProxyProvider {
+    settings: ProxyProviderSettings,
+}
+
+impl AiProvider for ProxyProvider {
+    fn kind(&self) -> AiProviderKind {
+        AiProviderKind::Proxy
+    }
+
+    fn max_prompt_tokens(&self) -> u32 {
+        self.settings.max_prompt_tokens
+    }
+
+    fn model(&self) -> &str {
+        &self.settings.model
+    }
+
+    // The relay doesn't stream
+    fn complete<'a>(&'a self, request: &'a ChatRequest<'a>, on_chunk: ChunkSink<'a>) -> BoxFuture<'a, Result<String, AiError>> {
+        Box::pin(async move {
+            let payload = ProxyPayload {
+                prompt: request.prompt,
+                api_key: non_empty(request.api_key),
+                model: request.model.or(non_empty(Some(&self.settings.model))),
+            };
+            let timeout_secs = self.settings.timeout_secs;
+            let builder = http_client(timeout_secs)?.post(&self.settings.url).json(&payload);
+            let response = send("AI service", timeout_secs, builder).await?;
+            let response: ProxyResponse = serde_json::from_value(read_json("AI service", timeout_secs, response).await?)
+                .map_err(|e| AiError::from(format!("Failed to parse AI service response: {}", e)))?;
+            match (response.result, response.error) {
+                (Some(result), _) => {
+                    on_chunk(&result);
+                    Ok(result)
+                }
+                (None, Some(error)) if is_quota_error(&error) => Err(AiError::QuotaExceeded { message: format!("AI service error: {}", error) }),
+                (None, Some(error)) => Err(format!("AI service error: {}", error).into()),
+                (None, None) => Err("Invalid response structure from AI service.".to_string().into()),
+            }
+        })
+    }
+}
+
+// --- OpenAI-compatible chat completions ---
+
+struct OpenAiChatProvider {
+    settings: OpenAiChatProviderSettings,
+}
+
+impl OpenAiChatProvider {
+    fn endpoint(&self) -> String {
+        let base = self.settings.base_url.trim_end_matches('/');
+        let base = base.strip_suffix("/v1").unwrap_or(base);
+        format!("{}/v1/chat/completions", base)
+    }
+}
+
+fn openai_stream_item(event: &SseEvent) -> Result<StreamItem, AiError> {
+    if event.data.trim() == "[DONE]" {
+        return Ok(StreamItem::Done);
+    }
+    let value: serde_json::Value = serde_json::from_str(&event.data)
+        .map_err(|e| AiError::from(format!("Failed to parse OpenAI-compatible stream event: {}", e)))?;
+    if value.get("error").is_some() {
+        return Err(format!("OpenAI-compatible provider error: {}", error_message(&event.data)).into());
+    }
+    Ok(value.pointer("/choices/0/delta/content")
+        .and_then(|content| content.as_str())
+        .map_or(StreamItem::Skip, |delta| StreamItem::Delta(delta.to_string())))
+}
+
+impl AiProvider for OpenAiChatProvider {
+    fn kind(&self) -> AiProviderKind {
+        AiProviderKind::OpenAiCompatible
+    }
+
+    fn max_prompt_tokens(&self) -> u32 {
+        self.settings.max_prompt_tokens
+    }
+
+    fn model(&self) -> &str {
+        &self.settings.model
+    }
+
+    fn complete<'a>(&'a self, request: &'a ChatRequest<'a>, on_chunk: ChunkSink<'a>) -> BoxFuture<'a, Result<String, AiError>> {
+        Box::pin(async move {
+            const PROVIDER: &str = "OpenAI-compatible provider";
+            let body = json!({
+                "model": request.model.unwrap_or(&self.settings.model),
+                "messages": [{ "role": "user", "content": request.prompt }],
+                "stream": true,
+            });
+            let timeout_secs = self.settings.timeout_secs;
+            let mut builder = http_client(timeout_secs)?.post(self.endpoint()).json(&body);
+            if let Some(api_key) = non_empty(request.api_key).or(non_empty(Some(&self.settings.api_key))) {
+                builder = builder.bearer_auth(api_key);
+            }
+            let response = send(PROVIDER, timeout_secs, builder).await?;
+            if is_event_stream(&response) {
+                let text = read_stream(PROVIDER, timeout_secs, response, on_chunk, openai_stream_item).await?;
+                return require_text(PROVIDER, text);
+            }
+            let value = read_json(PROVIDER, timeout_secs, response).await?;
+            let text = value.pointer("/choices/0/message/content")
+                .and_then(|content| content.as_str())
+                .map(str::to_string)
+                .unwrap_or_default();
+            on_chunk(&text);
+            require_text(PROVIDER, text)
+        })
+    }
+}
+
+// --- Anthropic messages API ---
+
+struct AnthropicProvider {
+    settings: AnthropicProviderSettings,
+}
+
+// Errors Anthropic reports inside a stream, mapped like the equivalent HTTP statuses
+fn anthropic_stream_error(data: &str) -> AiError {
+    let message = format!("Anthropic error: {}", error_message(data));
+    let value = serde_json::from_str::<serde_json::Value>(data).unwrap_or_default();
+    match value.pointer("/error/type").and_then(|t| t.as_str()) {
+        Some("rate_limit_error") => AiError::RateLimited { retry_after: None, message },
+        Some("authentication_error") | Some("permission_error") => AiError::Unauthorized { message },
+        Some("overloaded_error") => AiError::ProviderError { status: Some(529), message },
+        Some("api_error") => AiError::ProviderError { status: Some(500), message },
+        _ => AiError::ProviderError { status: None, message },
+    }
+}
+
+fn anthropic_stream_item(event: &SseEvent) -> Result<StreamItem, AiError> {
+    match event.event.as_deref() {
+        Some("content_block_delta") => {
+            let value: serde_json::Value = serde_json::from_str(&event.data)
+                .map_err(|e| AiError::from(format!("Failed to parse Anthropic stream event: {}", e)))?;
+            Ok(value.pointer("/delta/text")
+                .and_then(|text| text.as_str())
+                .map_or(StreamItem::Skip, |delta| StreamItem::Delta(delta.to_string())))
+        }
+        Some("message_stop") => Ok(StreamItem::Done),
+        Some("error") => Err(anthropic_stream_error(&event.data)),
+        _ => Ok(StreamItem::Skip), // message_start, ping, content_block_start/stop, message_delta
+    }
+}
+
+impl AiProvider for AnthropicProvider {
+    fn kind(&self) -> AiProviderKind {
+        AiProviderKind::Anthropic
+    }
+
+    fn max_prompt_tokens(&self) -> u32 {
+        self.settings.max_prompt_tokens
+    }
+
+    fn model(&self) -> &str {
+        &self.settings.model
+    }
+
+    fn complete<'a>(&'a self, request: &'a ChatRequest<'a>, on_chunk: ChunkSink<'a>) -> BoxFuture<'a, Result<String, AiError>> {
+        Box::pin(async move {
+            const PROVIDER: &str = "Anthropic";
+            let api_key = non_empty(request.api_key)
+                .or(non_empty(Some(&self.settings.api_key)))
+                .ok_or_else(|| AiError::Unauthorized { message: "Anthropic provider needs an API key".to_string() })?;
+            let body = json!({
+                "model": request.model.unwrap_or(&self.settings.model),
+                "max_tokens": self.settings.max_tokens,
+                "messages": [{ "role": "user", "content": request.prompt }],
+                "stream": true,
+            });
+            let timeout_secs = self.settings.timeout_secs;
+            let builder = http_client(timeout_secs)?
+                .post(format!("{}/v1/messages", self.settings.base_url.trim_end_matches('/')))
+                .header("x-api-key", api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&body);
+            let response = send(PROVIDER, timeout_secs, builder).await?;
+            if is_event_stream(&response) {
+                let text = read_stream(PROVIDER, timeout_secs, response, on_chunk, anthropic_stream_item).await?;
+                return require_text(PROVIDER, text);
+            }
+            let value = read_json(PROVIDER, timeout_secs, response).await?;
+            let text: String = value["content"].as_array()
+                .map(|blocks| {
+                    blocks.iter()
+                        .filter(|block| block["type"] == "text")
+                        .filter_map(|block| block["text"].as_str())
+                        .collect()
+                })
+                .unwrap_or_default();
+            on_chunk(&text);
+            require_text(PROVIDER, text)
+        })
+    }
+}
+
+// --- Local llama.cpp model ---
+
+struct LocalProvider {
+    settings: LocalProviderSettings,
+}
+
+impl AiProvider for LocalProvider {
+    fn kind(&self) -> AiProviderKind {
+        AiProviderKind::Local
+    }
+
+    fn max_prompt_tokens(&self) -> u32 {
+        self.settings.max_prompt_tokens
+    }
+
+    fn model(&self) -> &str {
+        &self.settings.model_id
+    }
+
+    // llama-cli's output is read when it exits; no key is involved
+    fn complete<'a>(&'a self, request: &'a ChatRequest<'a>, on_chunk: ChunkSink<'a>) -> BoxFuture<'a, Result<String, AiError>> {
+        Box::pin(async move {
+            let text = crate::local_llm::complete(&self.settings, request.model, request.prompt, request.cancel.clone()).await?;
+            on_chunk(&text);
+            Ok(text)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::mock_http::{mock_sequence, mock_server, mock_server_with, mock_silent_server, MockResponse};
+
+    fn request<'a>(api_key: Option<&'a str>) -> ChatRequest<'a> {
+        ChatRequest { prompt: "Summarize: ship it", model: None, api_key, cancel: None }
+    }
+
+    fn ignore(_: &str) {}
+
+    #[test]
+    fn parses_events_split_across_chunks() {
+        let mut parser = SseParser::default();
+        let bytes = "event: content_block_delta\r\ndata: {\"t\":\"caf\u{e9}\"}\r\n\r\n: keep-alive\n\ndata: a\ndata: b\n\n".as_bytes();
+        let split = bytes.iter().position(|&b| b == 0xc3).unwrap() + 1; // Inside the é
+        let mut events = parser.push(&bytes[..split]);
+        assert!(events.is_empty());
+        events.extend(parser.push(&bytes[split..]));
+        assert_eq!(events, vec![
+            SseEvent { event: Some("content_block_delta".to_string()), data: "{\"t\":\"café\"}".to_string() },
+            SseEvent { event: None, data: "a\nb".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn refuses_prompts_over_the_provider_budget() {
+        let mut settings = AiProviderSettings::default();
+        settings.proxy.max_prompt_tokens = 100;
+        let provider = build_provider(&settings);
+        assert!(check_prompt_budget(provider.as_ref(), &"word ".repeat(80)).is_ok()); // 400 chars
+        let error = check_prompt_budget(provider.as_ref(), &"word ".repeat(81)).unwrap_err();
+        assert!(matches!(error, AiError::InvalidTemplate { .. }));
+        assert!(error.to_string().contains("about 102 tokens, the limit is 100"), "{}", error);
+
+        settings.provider = AiProviderKind::Anthropic;
+        assert!(check_prompt_budget(build_provider(&settings).as_ref(), &"word ".repeat(81)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_streams_deltas() {
+        let (url, received) = mock_server_with("200 OK", "text/event-stream", concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Ship \"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"it.\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        ));
+        let provider = OpenAiChatProvider {
+            settings: OpenAiChatProviderSettings { base_url: format!("{}/v1", url), model: "llama3.2".to_string(), ..Default::default() },
+        };
+
+        let chunks = Mutex::new(Vec::new());
+        let sink = |delta: &str| chunks.lock().unwrap().push(delta.to_string());
+        assert_eq!(provider.complete(&request(None), &sink).await.unwrap(), "Ship it.");
+        assert_eq!(*chunks.lock().unwrap(), vec!["Ship ", "it."]);
+
+        let raw = received.recv().unwrap();
+        assert!(raw.starts_with("POST /v1/chat/completions "));
+        assert!(!raw.to_ascii_lowercase().contains("authorization:")); // Local server, no key
+        assert!(raw.contains(r#""model":"llama3.2""#));
+        assert!(raw.contains(r#""stream":true"#));
+        assert!(raw.contains("Summarize: ship it"));
+    }
+
+    #[tokio::test]
+    async fn stream_without_end_event_is_a_network_error() {
+        let (url, _received) = mock_server_with("200 OK", "text/event-stream",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Ship \"}}]}\n\n");
+        let provider = OpenAiChatProvider { settings: OpenAiChatProviderSettings { base_url: url, ..Default::default() } };
+        let error = provider.complete(&request(None), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::Network { .. }), "{:?}", error);
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_accepts_a_plain_json_answer() {
+        let (url, _received) = mock_server("200 OK", r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"Ship it."}}]}"#);
+        let provider = OpenAiChatProvider { settings: OpenAiChatProviderSettings { base_url: url, ..Default::default() } };
+        assert_eq!(provider.complete(&request(None), &ignore).await.unwrap(), "Ship it.");
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_prefers_the_byok_key_and_reports_errors() {
+        let (url, received) = mock_server("401 Unauthorized", r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error"}}"#);
+        let provider = OpenAiChatProvider {
+            settings: OpenAiChatProviderSettings { base_url: url, api_key: "sk-configured".to_string(), ..Default::default() },
+        };
+
+        let error = provider.complete(&request(Some("sk-byok")), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::Unauthorized { .. }));
+        assert!(!error.is_retryable());
+        assert!(error.to_string().contains("401"));
+        assert!(error.to_string().contains("Incorrect API key provided"));
+        assert!(received.recv().unwrap().to_ascii_lowercase().contains("authorization: bearer sk-byok"));
+    }
+
+    #[tokio::test]
+    async fn anthropic_streams_text_deltas() {
+        let (url, received) = mock_server_with("200 OK", "text/event-stream", concat!(
+            "event: message_start\ndata: {\"type\":\"message_start\"}\n\n",
+            "event: ping\ndata: {\"type\":\"ping\"}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Ship \"}}\n\n",
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"it.\"}}\n\n",
+            "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        ));
+        let provider = AnthropicProvider {
+            settings: AnthropicProviderSettings { base_url: url, api_key: "sk-ant-test".to_string(), ..Default::default() },
+        };
+
+        let chunks = Mutex::new(Vec::new());
+        let sink = |delta: &str| chunks.lock().unwrap().push(delta.to_string());
+        let with_model = ChatRequest { model: Some("claude-test"), ..request(None) };
+        assert_eq!(provider.complete(&with_model, &sink).await.unwrap(), "Ship it.");
+        assert_eq!(chunks.lock().unwrap().len(), 2);
+
+        let raw = received.recv().unwrap();
+        let lower = raw.to_ascii_lowercase();
+        assert!(raw.starts_with("POST /v1/messages "));
+        assert!(lower.contains("x-api-key: sk-ant-test"));
+        assert!(lower.contains("anthropic-version: 2023-06-01"));
+        assert!(raw.contains(r#""model":"claude-test""#));
+        assert!(raw.contains(r#""max_tokens":4096"#));
+
+        let keyless = AnthropicProvider { settings: AnthropicProviderSettings::default() };
+        assert!(keyless.complete(&request(None), &ignore).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn anthropic_stream_errors_are_reported() {
+        let (url, _received) = mock_server_with("200 OK", "text/event-stream",
+            "event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n");
+        let provider = AnthropicProvider {
+            settings: AnthropicProviderSettings { base_url: url, api_key: "sk-ant-test".to_string(), ..Default::default() },
+        };
+        let error = provider.complete(&request(None), &ignore).await.unwrap_err();
+        assert_eq!(error, AiError::ProviderError { status: Some(529), message: "Anthropic error: Overloaded".to_string() });
+    }
+
+    #[tokio::test]
+    async fn proxy_forwards_prompt_and_key() {
+        let (url, received) = mock_server("200 OK", r#"{"result":"Ship it."}"#);
+        let provider = ProxyProvider { settings: ProxyProviderSettings { url, ..Default::default() } };
+
+        assert_eq!(provider.complete(&request(Some("sk-byok")), &ignore).await.unwrap(), "Ship it.");
+        let raw = received.recv().unwrap();
+        assert!(raw.contains(r#""apiKey":"sk-byok""#));
+        assert!(!raw.contains(r#""model""#));
+    }
+
+    fn openai(base_url: String) -> OpenAiChatProvider {
+        OpenAiChatProvider { settings: OpenAiChatProviderSettings { base_url, timeout_secs: 1, ..Default::default() } }
+    }
+
+    fn json_response(status: &'static str, headers: &'static str, body: &'static str) -> MockResponse {
+        MockResponse { status, headers, content_type: "application/json", body }
+    }
+
+    #[tokio::test]
+    async fn rate_limits_carry_retry_after() {
+        let (url, _received) = mock_sequence(vec![json_response("429 Too Many Requests", "Retry-After: 7\r\n",
+            r#"{"error":{"message":"Rate limit reached for requests","type":"requests","code":"rate_limit_exceeded"}}"#)]);
+        let error = openai(url).complete(&request(None), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::RateLimited { retry_after: Some(7), .. }), "{:?}", error);
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn an_empty_balance_is_a_quota_error() {
+        let (url, _received) = mock_server("429 Too Many Requests",
+            r#"{"error":{"message":"You exceeded your current quota","type":"insufficient_quota","code":"insufficient_quota"}}"#);
+        let error = openai(url).complete(&request(None), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::QuotaExceeded { .. }), "{:?}", error);
+        assert!(!error.is_retryable());
+
+        let (url, _received) = mock_server("200 OK", r#"{"error":"Monthly AI quota used up"}"#);
+        let proxy = ProxyProvider { settings: ProxyProviderSettings { url, ..Default::default() } };
+        assert!(matches!(proxy.complete(&request(None), &ignore).await.unwrap_err(), AiError::QuotaExceeded { .. }));
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retryable_and_client_errors_are_not() {
+        let (url, _received) = mock_server("503 Service Unavailable", r#"{"error":{"message":"Upstream down"}}"#);
+        let error = openai(url).complete(&request(None), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::ProviderError { status: Some(503), .. }), "{:?}", error);
+        assert!(error.is_retryable());
+
+        let (url, _received) = mock_server("400 Bad Request", r#"{"error":{"message":"Unknown model"}}"#);
+        let error = openai(url).complete(&request(None), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::ProviderError { status: Some(400), .. }), "{:?}", error);
+        assert!(!error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn connection_failures_and_silence_are_network_and_timeout_errors() {
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let error = openai(closed).complete(&request(None), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::Network { .. }), "{:?}", error);
+
+        let error = openai(mock_silent_server()).complete(&request(None), &ignore).await.unwrap_err();
+        assert!(matches!(error, AiError::Timeout { .. }), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures_with_backoff() {
+        let (url, received) = mock_sequence(vec![
+            json_response("503 Service Unavailable", "", r#"{"error":{"message":"Upstream down"}}"#),
+            json_response("429 Too Many Requests", "Retry-After: 0\r\n", r#"{"error":{"message":"Slow down"}}"#),
+            json_response("200 OK", "", r#"{"choices":[{"message":{"content":"Ship it."}}]}"#),
+        ]);
+        let provider = openai(url);
+        let text = complete_with_retry(&provider, &request(None), &ignore, Duration::from_millis(10)).await.unwrap();
+        assert_eq!(text, "Ship it.");
+        for _ in 0..3 {
+            received.recv().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_permanent_failures_or_streamed_answers() {
+        let (url, received) = mock_sequence(vec![
+            json_response("401 Unauthorized", "", r#"{"error":{"message":"Bad key"}}"#),
+            json_response("200 OK", "", r#"{"choices":[{"message":{"content":"Ship it."}}]}"#),
+        ]);
+        let error = complete_with_retry(&openai(url), &request(None), &ignore, Duration::from_millis(10)).await.unwrap_err();
+        assert!(matches!(error, AiError::Unauthorized { .. }));
+        assert_eq!(received.recv().unwrap().lines().next().unwrap(), "POST /v1/chat/completions HTTP/1.1");
+        assert!(received.recv_timeout(Duration::from_millis(200)).is_err());
+
+        // Half an answer was shown already; a retry would repeat it
+        let (url, received) = mock_sequence(vec![
+            MockResponse { status: "200 OK", headers: "", content_type: "text/event-stream", body: concat!(
+                "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Ship \"}}\n\n",
+                "event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n",
+            ) },
+            json_response("200 OK", "", r#"{"content":[{"type":"text","text":"Ship it."}]}"#),
+        ]);
+        let provider = AnthropicProvider {
+            settings: AnthropicProviderSettings { base_url: url, api_key: "sk-ant-test".to_string(), ..Default::default() },
+        };
+        let error = complete_with_retry(&provider, &request(None), &ignore, Duration::from_millis(10)).await.unwrap_err();
+        assert!(error.is_retryable());
+        received.recv().unwrap();
+        assert!(received.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+// src-tauri/src/audio_convert.rs
+//
+// Conversion of device-native capture buffers to the 16-bit mono samples we write to WAV.
+//
+// cpal hands us interleaved frames in whatever format and channel count the device
+// config uses. Writing those straight into a mono WavSpec plays stereo back at double
+// speed, so every callback goes through here: normalise each sample to f32, then either
+// average all channels of a frame or pick one channel (for multi-input interfaces).
+
+/// How to reduce an interleaved multi-channel frame to one sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelMode {
+    /// Average every channel in the frame
+    Downmix,
+    /// Take a single channel (0-based). Falls back to downmix if out of range.
+    Select(u16),
+}
+
+impl ChannelMode {
+    /// Build from the optional channel index stored in settings
+    pub fn from_setting(input_channel: Option<u16>) -> Self {
+        match input_channel {
+            Some(channel) => ChannelMode::Select(channel),
+            None => ChannelMode::Downmix,
+        }
+    }
+}
+
+/// Device-native sample types we can capture from
+pub trait CaptureSample: Copy {
+    /// Normalised to -1.0..=1.0
+    fn to_f32_sample(self) -> f32;
+
+    fn to_i16_sample(self) -> i16 {
+        f32_to_i16(self.to_f32_sample())
+    }
+}
+
+impl CaptureSample for i16 {
+    fn to_f32_sample(self) -> f32 {
+        self as f32 / 32768.0
+    }
+
+    fn to_i16_sample(self) -> i16 {
+        self // Native format, no round trip
+    }
+}
+
+impl CaptureSample for u16 {
+    fn to_f32_sample(self) -> f32 {
+        (self as f32 - 32768.0) / 32768.0
+    }
+}
+
+impl CaptureSample for i32 {
+    fn to_f32_sample(self) -> f32 {
+        (self as f64 / 2147483648.0) as f32
+    }
+}
+
+impl CaptureSample for f32 {
+    fn to_f32_sample(self) -> f32 {
+        self
+    }
+}
+
+impl CaptureSample for f64 {
+    fn to_f32_sample(self) -> f32 {
+        self as f32
+    }
+}
+
+/// Clamp and scale a normalised sample to i16
+pub fn f32_to_i16(sample: f32) -> i16 {
+    let clamped = sample.clamp(-1.0, 1.0);
+    (clamped * i16::MAX as f32) as i16
+}
+
+/// Convert an interleaved buffer to mono i16, appending to `out`.
+/// A trailing partial frame (shouldn't happen, but cpal doesn't promise) is dropped.
+pub fn interleaved_to_mono_i16<T: CaptureSample>(data: &[T], channels: u16, mode: ChannelMode, out: &mut Vec<i16>) {
+    let channels = channels.max(1) as usize;
+    if channels == 1 {
+        out.extend(data.iter().map(|&s| s.to_i16_sample()));
+        return;
+    }
+
+    let selected = match mode {
+        ChannelMode::Select(channel) if (channel as usize) < channels => Some(channel as usize),
+        _ => None,
+    };
+
+    out.reserve(data.len() / channels);
+    for frame in data.chunks_exact(channels) {
+        let mono = match selected {
+            Some(index) => frame[index].to_f32_sample(),
+            None => frame.iter().map(|&s| s.to_f32_sample()).sum::<f32>() / channels as f32,
+        };
+        out.push(f32_to_i16(mono));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert<T: CaptureSample>(data: &[T], channels: u16, mode: ChannelMode) -> Vec<i16> {
+        let mut out = Vec::new();
+        interleaved_to_mono_i16(data, channels, mode, &mut out);
+        out
+    }
+
+    #[test]
+    fn mono_i16_passes_through() {
+        let data: Vec<i16> = vec![0, 1000, -1000, i16::MAX, -i16::MAX];
+        assert_eq!(convert(&data, 1, ChannelMode::Downmix), data);
+    }
+
+    #[test]
+    fn stereo_downmix_halves_frame_count() {
+        // Left carries a signal, right is silent: 4 frames in, 4 samples out at half amplitude
+        let data: Vec<f32> = vec![0.5, 0.0, -0.5, 0.0, 1.0, 0.0, 0.0, 0.0];
+        let out = convert(&data, 2, ChannelMode::Downmix);
+        assert_eq!(out.len(), 4);
+        assert_eq!(out[0], f32_to_i16(0.25));
+        assert_eq!(out[1], f32_to_i16(-0.25));
+        assert_eq!(out[2], f32_to_i16(0.5));
+        assert_eq!(out[3], 0);
+    }
+
+    #[test]
+    fn select_channel_picks_one_input() {
+        // 4-channel interface, mic on input 3 (index 2)
+        let data: Vec<i32> = vec![
+            0, 0, i32::MAX / 2, 0,
+            0, 0, i32::MIN / 2, 0,
+        ];
+        let out = convert(&data, 4, ChannelMode::Select(2));
+        assert_eq!(out.len(), 2);
+        assert!((out[0] as i32 - i16::MAX as i32 / 2).abs() <= 1);
+        assert!((out[1] as i32 + i16::MAX as i32 / 2).abs() <= 1);
+    }
+
+    #[test]
+    fn out_of_range_channel_falls_back_to_downmix() {
+        let data: Vec<f64> = vec![0.4, 0.2, 0.4, 0.2];
+        let out = convert(&data, 2, ChannelMode::Select(5));
+        assert_eq!(out, vec![f32_to_i16(0.3), f32_to_i16(0.3)]);
+    }
+
+    #[test]
+    fn u16_midpoint_is_silence() {
+        let data: Vec<u16> = vec![32768, 32768, u16::MAX, u16::MAX, 0, 0];
+        let out = convert(&data, 2, ChannelMode::Downmix);
+        assert_eq!(out[0], 0);
+        assert!(out[1] > 32000);
+        assert_eq!(out[2], -i16::MAX);
+    }
+
+    #[test]
+    fn partial_trailing_frame_is_dropped() {
+        let data: Vec<i16> = vec![100, 100, 100, 100, 100];
+        assert_eq!(convert(&data, 2, ChannelMode::Downmix).len(), 2);
+    }
+
+    #[test]
+    fn out_of_range_floats_are_clamped() {
+        let data: Vec<f32> = vec![2.0, -3.0];
+        assert_eq!(convert(&data, 1, ChannelMode::Downmix), vec![i16::MAX, -i16::MAX]);
+    }
+}
@@ -153,7 +153,8 @@ impl AudioDeviceManager {
                             }
                         }
                     },
-                    |err| error!("[AudioDeviceManager] Stream error: {}", err)
+                    |err| error!("[AudioDeviceManager] Stream error: {}", err),
+                    None
                 )
             }
             SampleFormat::I16 => {
@@ -170,7 +171,8 @@ impl AudioDeviceManager {
                             }
                         }
                     },
-                    |err| error!("[AudioDeviceManager] Stream error: {}", err)
+                    |err| error!("[AudioDeviceManager] Stream error: {}", err),
+                    None
                 )
             }
             _ => return Err("Unsupported sample format".to_string()),
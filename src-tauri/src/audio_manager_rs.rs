@@ -11,6 +11,7 @@ use uuid::Uuid;
 use log::{error, info, warn};
 use crate::SharedRecordingState; // Import SharedRecordingState from main/lib
 use crate::transcription::{self, TranscriptionState}; // Import transcription state
+use crate::transcription_queue; // Ordered transcription jobs
 use cpal::{SupportedStreamConfig, SampleFormat, SampleRate};
 use std::fs::File;
 use std::io::BufWriter;
@@ -23,12 +24,53 @@ use serde::Deserialize;
 use crate::RECORDING_LIFECYCLE;
 use crate::RecordingLifecycle; // Import the enum itself
 use crate::config::SETTINGS; // Import the config settings
+use crate::audio_convert::{interleaved_to_mono_i16, CaptureSample, ChannelMode};
 
 // --- ADD THESE IMPORTS ---
 use crate::{write_to_clipboard_internal, paste_text_to_cursor}; // Import from main.rs
 use crate::{StateUpdatePayload, FrontendRecordingState}; // Import state types from main.rs
 // --- END IMPORTS ---
 
+type SharedWavWriter = Arc<Mutex<Option<hound::WavWriter<BufWriter<File>>>>>;
+
+// A cancel that came in after the hotkey asked for a recording but before
+// start_backend_recording ran; that start is then dropped instead of opening the mic
+static CANCEL_BEFORE_START: AtomicBool = AtomicBool::new(false);
+
+/// A new take was requested: a cancel left over from an earlier one no longer applies
+pub fn clear_cancel_before_start() {
+    CANCEL_BEFORE_START.store(false, Ordering::SeqCst);
+}
+
+// Build an input stream for any supported sample type, writing 16-bit mono to the WAV.
+// Interleaved multi-channel frames are downmixed or reduced to the selected channel.
+fn build_mono_input_stream<T, E>(
+    device: &cpal::Device,
+    stream_config: &cpal::StreamConfig,
+    writer: SharedWavWriter,
+    capture_paused: Arc<AtomicBool>,
+    channel_mode: ChannelMode,
+    error_callback: E,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample + CaptureSample,
+    E: FnMut(cpal::StreamError) + Send + 'static,
+{
+    let channels = stream_config.channels;
+    let mut mono_buffer: Vec<i16> = Vec::new(); // Reused across callbacks
+    let data_callback = move |data: &[T], _: &cpal::InputCallbackInfo| {
+        if capture_paused.load(Ordering::Relaxed) { return; } // Drop samples while paused
+        mono_buffer.clear();
+        interleaved_to_mono_i16(data, channels, channel_mode, &mut mono_buffer);
+        if let Ok(mut writer_opt_guard) = writer.lock() {
+            if let Some(writer_guard) = writer_opt_guard.as_mut() {
+                for &sample in mono_buffer.iter() { if writer_guard.write_sample(sample).is_err() { break; } }
+            }
+        }
+    };
+    device.build_input_stream::<T, _, _>(stream_config, data_callback, error_callback, None)
+}
+
 #[derive(Deserialize, Debug)]
 pub struct StopRecordingPayloadArgs {
     auto_paste: bool,
@@ -65,6 +107,11 @@ pub async fn start_backend_recording(
         let mut lifecycle_guard = RECORDING_LIFECYCLE.lock().unwrap();
         println!("[RUST AUDIO] Checking lifecycle state: {:?}", *lifecycle_guard);
         match *lifecycle_guard {
+            RecordingLifecycle::Idle if CANCEL_BEFORE_START.swap(false, Ordering::SeqCst) => {
+                // Escape/cancel beat us here; the UI is already back to Idle
+                println!("[RUST AUDIO] Recording was cancelled before it started. Not starting.");
+                return Ok(());
+            }
             RecordingLifecycle::Idle => {
                 // It's Idle, okay to proceed. Update lifecycle state.
                 println!("[RUST AUDIO] Lifecycle is Idle. Transitioning to Recording.");
@@ -84,8 +131,8 @@ pub async fn start_backend_recording(
     let mut audio_state_guard = audio_state.lock().map_err(|e| format!("Failed to lock audio state: {}", e))?;
 
     let unique_id = Uuid::new_v4().to_string();
-    let temp_dir = std::env::temp_dir();
-    let temp_wav_path = temp_dir.join(format!("fethr_rec_{}.wav", unique_id));
+    let recordings_dir = crate::recordings::recordings_dir_or_temp(&app_handle);
+    let temp_wav_path = recordings_dir.join(format!("{}{}.wav", crate::recordings::RECORDING_PREFIX, unique_id));
     println!("[RUST AUDIO] Recording path: {}", temp_wav_path.display());
     let (tx_stop, rx_stop) = mpsc::channel();
 
@@ -99,7 +146,8 @@ pub async fn start_backend_recording(
     let device_name = device.name().unwrap_or_else(|_| "Unnamed".to_string());
     
     // Device discovery logging removed for production
-    let preferred_format_order = [SampleFormat::I16, SampleFormat::F32];
+    // Prefer formats we can write without conversion loss; anything multi-channel is reduced to mono
+    let preferred_format_order = [SampleFormat::I16, SampleFormat::F32, SampleFormat::I32, SampleFormat::U16, SampleFormat::F64];
     let mut best_config: Option<cpal::SupportedStreamConfig> = None;
    
     // Keep the existing config finding logic
@@ -123,19 +171,28 @@ pub async fn start_backend_recording(
         }
     }
     
-    let supported_config = best_config.ok_or_else(|| "No supported I16 or F32 input config found".to_string())?;
+    let supported_config = best_config.ok_or_else(|| "No supported input config found (I16/F32/I32/U16/F64)".to_string())?;
     let actual_sample_rate = supported_config.sample_rate().0;
     let stream_config: cpal::StreamConfig = supported_config.config();
     let actual_format = supported_config.sample_format();
+    let input_channels = stream_config.channels;
+    let channel_mode = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        ChannelMode::from_setting(settings_guard.audio.input_channel)
+    };
     
-    println!("[RUST AUDIO] Recording started with {}", device_name);
+    println!("[RUST AUDIO] Recording started with {} ({:?}, {} ch, {} Hz, {:?})",
+             device_name, actual_format, input_channels, actual_sample_rate, channel_mode);
 
+    // Always mono: the data callback reduces device frames to one channel
     let spec = hound::WavSpec { channels: 1, sample_rate: actual_sample_rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
     let writer = hound::WavWriter::create(&temp_wav_path, spec).map_err(|e| format!("Failed to create WavWriter: {}", e))?;
-    let writer_mutex: Arc<Mutex<Option<hound::WavWriter<BufWriter<File>>>>> = Arc::new(Mutex::new(Some(writer)));
+    let writer_mutex: SharedWavWriter = Arc::new(Mutex::new(Some(writer)));
 
     // --- Recording Thread (Needs the flag) ---
     let writer_clone = Arc::clone(&writer_mutex);
+    let capture_paused = Arc::new(AtomicBool::new(false)); // Toggled by set_capture_paused
+    let capture_paused_clone = capture_paused.clone();
     let session_active_clone = session_active_flag.clone(); // Clone flag for the thread
     let _app_handle_for_error_cb = app_handle.clone();
     let _app_handle_for_build_err = app_handle.clone();
@@ -150,34 +207,15 @@ pub async fn start_backend_recording(
             session_active_clone.store(false, Ordering::SeqCst);
         });
 
-        let error_callback = move |_err| { /* Same as before */ };
+        let error_callback = move |err: cpal::StreamError| { println!("[RUST THREAD ERROR] Stream error: {}", err); };
 
-        // Building audio stream
+        // Building audio stream - every format goes through the same mono conversion
         let stream_result = match actual_format {
-            SampleFormat::I16 => {
-                let data_callback = move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    if let Ok(mut writer_opt_guard) = writer_clone.lock() {
-                        if let Some(writer_guard) = writer_opt_guard.as_mut() {
-                             for &sample in data.iter() { if writer_guard.write_sample(sample).is_err() { break; } }
-                        }
-                    }
-                };
-                device.build_input_stream::<i16, _, _>(&stream_config, data_callback, error_callback)
-            }
-            SampleFormat::F32 => {
-                let data_callback = move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    if let Ok(mut writer_opt_guard) = writer_clone.lock() {
-                        if let Some(writer_guard) = writer_opt_guard.as_mut() {
-                            for &sample_f32 in data.iter() {
-                                let clamped_f32 = sample_f32.max(-1.0).min(1.0);
-                                let sample_i16 = (clamped_f32 * std::i16::MAX as f32) as i16;
-                                if writer_guard.write_sample(sample_i16).is_err() { break; }
-                            }
-                        }
-                    }
-                };
-                device.build_input_stream::<f32, _, _>(&stream_config, data_callback, error_callback)
-            }
+            SampleFormat::I16 => build_mono_input_stream::<i16, _>(&device, &stream_config, writer_clone, capture_paused_clone, channel_mode, error_callback),
+            SampleFormat::U16 => build_mono_input_stream::<u16, _>(&device, &stream_config, writer_clone, capture_paused_clone, channel_mode, error_callback),
+            SampleFormat::I32 => build_mono_input_stream::<i32, _>(&device, &stream_config, writer_clone, capture_paused_clone, channel_mode, error_callback),
+            SampleFormat::F32 => build_mono_input_stream::<f32, _>(&device, &stream_config, writer_clone, capture_paused_clone, channel_mode, error_callback),
+            SampleFormat::F64 => build_mono_input_stream::<f64, _>(&device, &stream_config, writer_clone, capture_paused_clone, channel_mode, error_callback),
             _ => Err(cpal::BuildStreamError::StreamConfigNotSupported)
         };
 
@@ -229,6 +267,9 @@ pub async fn start_backend_recording(
     audio_state_guard.recording_thread_handle = Some(recording_handle); // Store JoinHandle
     audio_state_guard.writer = Some(writer_mutex);
     audio_state_guard.recording_start_time = Some(std::time::Instant::now()); // Track start time
+    audio_state_guard.capture_paused = capture_paused;
+    audio_state_guard.paused_since = None;
+    audio_state_guard.paused_duration = Duration::ZERO;
     // No need to store the Arc<AtomicBool> here anymore
 
     println!("[RUST AUDIO] Backend recording started successfully.");
@@ -252,7 +293,7 @@ pub async fn start_backend_recording(
 pub async fn stop_backend_recording(
     app_handle: AppHandle,
     audio_state: State<'_, SharedRecordingState>,
-    transcription_state: State<'_, TranscriptionState>,
+    _transcription_state: State<'_, TranscriptionState>,
     args: StopRecordingPayloadArgs,
 ) -> Result<String, String> {
     info!("[RUST AUDIO STOP] Received stop command. Payload: {:?}", args);
@@ -271,6 +312,10 @@ pub async fn stop_backend_recording(
         }
     };
     info!("[RUST AUDIO STOP] Effective auto_paste setting: {}", effective_auto_paste);
+    // Taken now, before the next take can be armed while this one waits in the queue
+    let dictation_action = crate::ai_actions::take_armed_action();
+    let voice_command = crate::command_mode::take_armed();
+    let translation_target = crate::translation::take_target_language();
 
     let session_active_flag: Arc<AtomicBool>; // Flag to signal thread
 
@@ -280,8 +325,8 @@ pub async fn stop_backend_recording(
         println!("[RUST AUDIO STOP] Checking lifecycle state: {:?}", *lifecycle_guard);
 
         match &*lifecycle_guard {
-            RecordingLifecycle::Recording(flag) => {
-                 println!("[RUST AUDIO STOP] Lifecycle is Recording. Transitioning to Stopping.");
+            RecordingLifecycle::Recording(flag) | RecordingLifecycle::Paused(flag) => {
+                 println!("[RUST AUDIO STOP] Lifecycle is {:?}. Transitioning to Stopping.", *lifecycle_guard);
                  session_active_flag = flag.clone(); // Get the flag for this session
                  *lifecycle_guard = RecordingLifecycle::Stopping; // Update state
             }
@@ -320,12 +365,17 @@ pub async fn stop_backend_recording(
         let mut audio_state_guard = audio_state.lock().unwrap();
          println!("[RUST AUDIO STOP] Acquired audio state lock (Signal/Join Phase).");
 
-        // Calculate recording duration
+        // Calculate recording duration, excluding time spent paused
         if let Some(start_time) = audio_state_guard.recording_start_time.take() {
-            let duration = start_time.elapsed();
+            let mut paused = audio_state_guard.paused_duration;
+            if let Some(paused_since) = audio_state_guard.paused_since.take() {
+                paused += paused_since.elapsed(); // Stopped while still paused
+            }
+            let duration = start_time.elapsed().saturating_sub(paused);
             recording_duration_seconds = duration.as_secs() as i32;
-            println!("[RUST AUDIO STOP] Recording duration: {} seconds", recording_duration_seconds);
+            println!("[RUST AUDIO STOP] Recording duration: {} seconds ({} seconds paused)", recording_duration_seconds, paused.as_secs());
         }
+        audio_state_guard.paused_duration = Duration::ZERO;
 
         println!("[RUST AUDIO STOP] Sending stop signal via channel...");
         if let Some(sender) = audio_state_guard.stop_signal_sender.take() {
@@ -400,28 +450,70 @@ pub async fn stop_backend_recording(
                 "[RUST AUDIO STOP] Path is valid. Proceeding to transcribe: {}",
                 temp_wav_path_str
             );
-            // Correctly get the transcription state
-            // let ts_state = transcription_state.inner().clone(); // REMOVE THIS LINE
+            // Queued behind any dictation still transcribing; the job's turn lasts
+            // until after the paste so results are pasted in dictation order
+            let mut job = match transcription_queue::submit(&app_handle) {
+                Ok(job) => job,
+                Err(e) => {
+                    let error_message = transcription::queue_full_error(&app_handle, e);
+                    let _ = crate::signal_reset_complete(app_handle.clone());
+                    return Err(error_message);
+                }
+            };
+            // The take is safely queued: the hotkey can start the next dictation right away
+            crate::hotkey_ready_for_next_take();
 
-            // Call transcribe_audio_file with the State wrapper directly
-            let transcription_result = transcription::transcribe_audio_file(
+            let transcription_result = transcription::transcribe_job(
+                &mut job,
                 app_handle.clone(),
-                transcription_state, // Pass the State wrapper directly
                 temp_wav_path_str,
                 args.auto_paste,   // From the new struct
                 args.user_id,      // New argument
                 args.access_token, // New argument
                 Some(recording_duration_seconds), // Pass the duration
                 args.timezone,     // Pass timezone
+                translation_target,
+                voice_command.is_some(),
             )
             .await;
 
+            // Command hotkey: the dictation is an instruction for the selected text, not text to paste
+            if let (Ok(instruction), Some(command)) = (&transcription_result, voice_command) {
+                let result = if job.is_cancelled() {
+                    Err("Transcription was cancelled".to_string())
+                } else {
+                    crate::command_mode::apply(&app_handle, command, instruction, effective_auto_paste).await
+                };
+                job.finish(&result);
+                return result;
+            }
+
             let transcription_result_to_return: Result<String, String>;
 
             match transcription_result {
                 Ok(transcribed_text) => {
                     info!("[RUST AUDIO STOP] Transcription successful: {}", transcribed_text);
 
+                    // Automatic AI chain; the raw text is pasted if it fails or runs over budget
+                    let transcribed_text = match crate::ai_chain::process_dictation(&app_handle, &transcribed_text).await {
+                        Some(record) if !job.is_cancelled() => {
+                            let processed = record.text.clone();
+                            if let Err(e) = transcription::record_ai_output(&app_handle, &transcribed_text, record) {
+                                warn!("[RUST AUDIO STOP] Could not store AI chain output in history: {}", e);
+                            }
+                            processed
+                        }
+                        _ => transcribed_text,
+                    };
+
+                    // Dictated with an action hotkey: run the action before anything is pasted
+                    let (paste_output, transcribed_text) = match dictation_action {
+                        Some(action_id) if !transcribed_text.trim().is_empty() && !job.is_cancelled() => {
+                            crate::ai_actions::apply_to_dictation(&app_handle, &action_id, transcribed_text).await
+                        }
+                        _ => (true, transcribed_text),
+                    };
+
                     // Attempt to write to clipboard first
                     match write_to_clipboard_internal(transcribed_text.clone()) {
                         Ok(_) => {
@@ -432,13 +524,15 @@ pub async fn stop_backend_recording(
                                 log::error!("[RUST AUDIO] Failed to emit 'fethr-copied-to-clipboard': {}", e);
                             }
 
-                            if effective_auto_paste {
+                            if effective_auto_paste && paste_output {
                                 info!("[RUST AUDIO STOP] Auto-paste is enabled. Attempting paste.");
                                 if let Err(e) = paste_text_to_cursor().await {
                                     error!("[RUST AUDIO STOP] Failed to paste text: {}. Transcription was: '{}'", e, transcribed_text);
                                     // Don't return error for paste failure, just log it.
                                     // Frontend will have the text on clipboard and can manage edit state.
                                 }
+                            } else if !paste_output {
+                                info!("[RUST AUDIO STOP] AI action output goes to the clipboard only.");
                             } else {
                                 info!("[RUST AUDIO STOP] Auto-paste is disabled. Clipboard write was successful.");
                             }
@@ -457,6 +551,7 @@ pub async fn stop_backend_recording(
                     transcription_result_to_return = Err(e.to_string());
                 }
             }
+            job.finish(&transcription_result_to_return);
             transcription_result_to_return
         },
         Err(e) => {
@@ -475,4 +570,122 @@ pub async fn stop_backend_recording(
              Err(e)
         }
     }
-}
\ No newline at end of file
+}
+
+/// Pause or resume sample capture for the current session without ending it.
+/// Called from the hotkey state thread after HOTKEY_STATE has been updated.
+pub fn set_capture_paused(app_handle: &AppHandle, paused: bool) -> Result<(), String> {
+    { // Scope for lifecycle lock
+        let mut lifecycle_guard = RECORDING_LIFECYCLE.lock().unwrap();
+        let next = match (&*lifecycle_guard, paused) {
+            (RecordingLifecycle::Recording(flag), true) => RecordingLifecycle::Paused(flag.clone()),
+            (RecordingLifecycle::Paused(flag), false) => RecordingLifecycle::Recording(flag.clone()),
+            (current, _) => {
+                println!("[RUST AUDIO PAUSE WARN] Cannot {} in lifecycle state {:?}", if paused { "pause" } else { "resume" }, current);
+                return Err(format!("Cannot {} recording, lifecycle state is: {:?}", if paused { "pause" } else { "resume" }, current));
+            }
+        };
+        *lifecycle_guard = next;
+    } // Lifecycle lock released
+
+    let audio_state = app_handle.state::<SharedRecordingState>();
+    let mut audio_state_guard = audio_state.lock().map_err(|e| format!("Failed to lock audio state: {}", e))?;
+    audio_state_guard.capture_paused.store(paused, Ordering::SeqCst);
+    if paused {
+        audio_state_guard.paused_since = Some(std::time::Instant::now());
+    } else if let Some(paused_since) = audio_state_guard.paused_since.take() {
+        audio_state_guard.paused_duration += paused_since.elapsed();
+    }
+    println!("[RUST AUDIO PAUSE] Capture {}. Total paused so far: {:?}", if paused { "paused" } else { "resumed" }, audio_state_guard.paused_duration);
+    Ok(())
+}
+
+/// Abort the current recording without transcribing: stops the capture thread, deletes the
+/// temp WAV and returns both HOTKEY_STATE and RECORDING_LIFECYCLE to Idle. No history, stats
+/// or paste side effects. `start_requested`: a take was asked for, so if the capture hasn't
+/// started yet the pending start is dropped when it arrives.
+pub fn cancel_recording_internal(app_handle: &AppHandle, start_requested: bool) -> Result<(), String> {
+    let session_active_flag = { // Scope for lifecycle lock
+        let mut lifecycle_guard = RECORDING_LIFECYCLE.lock().unwrap();
+        println!("[RUST AUDIO CANCEL] Checking lifecycle state: {:?}", *lifecycle_guard);
+        match &*lifecycle_guard {
+            RecordingLifecycle::Recording(flag) | RecordingLifecycle::Paused(flag) => {
+                let flag = flag.clone();
+                *lifecycle_guard = RecordingLifecycle::Stopping;
+                Some(flag)
+            }
+            RecordingLifecycle::Stopping => {
+                // Audio is already handed to whisper; too late to throw it away
+                println!("[RUST AUDIO CANCEL WARN] Cancel called while Stopping. Ignoring.");
+                return Err("Cannot cancel, transcription already started".to_string());
+            }
+            RecordingLifecycle::Idle => {
+                // Nothing captured yet; start_backend_recording may still be on its way
+                if start_requested {
+                    CANCEL_BEFORE_START.store(true, Ordering::SeqCst);
+                }
+                None
+            }
+        }
+    }; // Lifecycle lock released
+
+    if let Some(flag) = session_active_flag {
+        flag.store(false, Ordering::SeqCst);
+
+        let audio_state = app_handle.state::<SharedRecordingState>();
+        let (handle_opt, temp_path_opt, writer_arc_opt) = {
+            let mut audio_state_guard = audio_state.lock().unwrap();
+            if let Some(sender) = audio_state_guard.stop_signal_sender.take() {
+                let _ = sender.send(());
+            }
+            audio_state_guard.recording_start_time = None;
+            audio_state_guard.paused_since = None;
+            audio_state_guard.paused_duration = Duration::ZERO;
+            (
+                audio_state_guard.recording_thread_handle.take(),
+                audio_state_guard.temp_wav_path.take(),
+                audio_state_guard.writer.take(),
+            )
+        }; // Audio state lock released BEFORE joining thread
+
+        if let Some(handle) = handle_opt {
+            if handle.join().is_err() {
+                eprintln!("[RUST AUDIO CANCEL ERROR] Recording thread panicked during cancel.");
+            }
+        }
+
+        // Drop the writer so the file handle is closed before deleting (required on Windows)
+        if let Some(writer_arc) = writer_arc_opt {
+            if let Ok(mut writer_opt_guard) = writer_arc.lock() {
+                drop(writer_opt_guard.take());
+            }
+        }
+
+        if let Some(temp_path) = temp_path_opt {
+            match std::fs::remove_file(&temp_path) {
+                Ok(_) => println!("[RUST AUDIO CANCEL] Removed temp recording: {}", temp_path.display()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => println!("[RUST AUDIO CANCEL WARNING] Failed to delete {}: {}", temp_path.display(), e),
+            }
+        }
+
+        let mut lifecycle_guard = RECORDING_LIFECYCLE.lock().unwrap();
+        if *lifecycle_guard == RecordingLifecycle::Stopping {
+            *lifecycle_guard = RecordingLifecycle::Idle;
+        }
+    }
+
+    // Resets HOTKEY_STATE (keeping the physical key state) and emits the IDLE payload
+    crate::reset_after_cancel(app_handle);
+    if let Err(e) = app_handle.emit_all("fethr-recording-cancelled", ()) {
+        error!("[RUST AUDIO CANCEL] Failed to emit fethr-recording-cancelled: {}", e);
+    }
+    println!("[RUST AUDIO CANCEL] Recording cancelled.");
+    Ok(())
+}
+
+#[command]
+pub async fn cancel_backend_recording(app_handle: AppHandle) -> Result<(), String> {
+    info!("[RUST AUDIO CANCEL] Received cancel command.");
+    cancel_recording_internal(&app_handle, crate::hotkey_take_in_progress())
+}
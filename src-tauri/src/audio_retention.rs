@@ -0,0 +1,244 @@
+// src-tauri/src/audio_retention.rs
+//
+// Optional retention of the source audio behind history entries.
+//
+// When enabled, the 16 kHz mono WAV that whisper consumed is compressed with the
+// bundled ffmpeg (FLAC or Opus) into <app data>/audio and linked from the history
+// entry by file name. Retained audio can be re-transcribed later with a different
+// model. Size and age limits are enforced every time history is written.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn, error};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::config::{AudioRetentionFormat, SETTINGS};
+use crate::process_runner;
+use crate::transcription::{self, HistoryEntry};
+
+const AUDIO_DIR_NAME: &str = "audio";
+
+/// Directory holding retained audio files
+pub fn audio_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_handle.path_resolver().app_data_dir()
+        .ok_or_else(|| "Failed to get app data directory".to_string())?;
+    let dir = data_dir.join(AUDIO_DIR_NAME);
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create audio directory: {}", e))?;
+    }
+    Ok(dir)
+}
+
+/// Resolve a stored file name inside the audio dir, rejecting anything that could escape it
+pub fn retained_audio_path(app_handle: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    if file_name.is_empty() || file_name.contains('/') || file_name.contains('\\') || file_name.contains("..") {
+        return Err(format!("Invalid audio file name: {}", file_name));
+    }
+    Ok(audio_dir(app_handle)?.join(file_name))
+}
+
+fn format_extension(format: AudioRetentionFormat) -> &'static str {
+    match format {
+        AudioRetentionFormat::Flac => "flac",
+        AudioRetentionFormat::Opus => "ogg",
+    }
+}
+
+async fn encode_with_ffmpeg(input: &Path, output: &Path, format: AudioRetentionFormat) -> Result<(), String> {
+    let (ffmpeg_path, ffmpeg_cwd) = transcription::resolve_ffmpeg_path()?;
+
+    let mut command = Command::new(&ffmpeg_path);
+    command.current_dir(&ffmpeg_cwd)
+        .arg("-i").arg(input)
+        .arg("-ac").arg("1");
+    match format {
+        AudioRetentionFormat::Flac => { command.arg("-c:a").arg("flac"); }
+        AudioRetentionFormat::Opus => { command.arg("-c:a").arg("libopus").arg("-b:a").arg("24k"); }
+    }
+    command.arg("-y").arg(output);
+
+    let timeout = SETTINGS.lock().unwrap().process_timeouts.ffmpeg_timeout();
+    if let Err(e) = process_runner::run(command, timeout, None).await {
+        let _ = fs::remove_file(output);
+        return Err(format!("ffmpeg encode {}", e));
+    }
+    if fs::metadata(output).map(|m| m.len()).unwrap_or(0) == 0 {
+        let _ = fs::remove_file(output);
+        return Err(format!("ffmpeg encode produced an empty file: {}", output.display()));
+    }
+    Ok(())
+}
+
+/// Compress a WAV into the audio dir. Returns the stored file name, or None on failure -
+/// retention is best effort and never fails a transcription.
+pub async fn retain_audio(app_handle: &AppHandle, wav_path: &Path) -> Option<String> {
+    let format = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        settings_guard.audio_retention.format
+    };
+
+    let dir = match audio_dir(app_handle) {
+        Ok(dir) => dir,
+        Err(e) => {
+            error!("[AudioRetention] {}", e);
+            return None;
+        }
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let mut attempts = vec![format];
+    if format == AudioRetentionFormat::Opus {
+        // Not every ffmpeg build ships libopus - FLAC always works
+        attempts.push(AudioRetentionFormat::Flac);
+    }
+
+    for attempt in attempts {
+        let file_name = format!("{}.{}", id, format_extension(attempt));
+        let output = dir.join(&file_name);
+        match encode_with_ffmpeg(wav_path, &output, attempt).await {
+            Ok(()) => {
+                let size = fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+                info!("[AudioRetention] Retained audio as {} ({} bytes)", file_name, size);
+                return Some(file_name);
+            }
+            Err(e) => warn!("[AudioRetention] {:?} encode failed: {}", attempt, e),
+        }
+    }
+    None
+}
+
+/// Decode retained audio back to a 16 kHz mono WAV in the recordings dir.
+/// Uses the converted-file prefix so a crash mid-way is cleaned up on next launch.
+pub async fn decode_to_wav(app_handle: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    let source = retained_audio_path(app_handle, file_name)?;
+    if !source.exists() {
+        return Err(format!("Retained audio {} no longer exists", file_name));
+    }
+    let output = crate::recordings::recordings_dir_or_temp(app_handle)
+        .join(format!("{}{}.wav", crate::recordings::CONVERTED_PREFIX, Uuid::new_v4()));
+    transcription::run_ffmpeg_conversion(&source, &output, app_handle, None).await.map_err(|e| e.to_string())?;
+    Ok(output)
+}
+
+/// Work out which entries lose their audio: too old, or the oldest ones beyond the size budget.
+/// `entries` is (file name, entry timestamp, file size). A zero limit disables that check.
+pub fn plan_retention(
+    entries: &[(String, DateTime<Utc>, u64)],
+    now: DateTime<Utc>,
+    max_age_days: u32,
+    max_total_bytes: u64,
+) -> HashSet<String> {
+    let mut drop = HashSet::new();
+
+    let mut kept: Vec<&(String, DateTime<Utc>, u64)> = Vec::new();
+    for entry in entries {
+        if max_age_days > 0 && now - entry.1 > Duration::days(max_age_days as i64) {
+            drop.insert(entry.0.clone());
+        } else {
+            kept.push(entry);
+        }
+    }
+
+    if max_total_bytes > 0 {
+        kept.sort_by_key(|e| e.1); // Oldest first
+        let mut total: u64 = kept.iter().map(|e| e.2).sum();
+        for entry in kept {
+            if total <= max_total_bytes {
+                break;
+            }
+            total -= entry.2;
+            drop.insert(entry.0.clone());
+        }
+    }
+
+    drop
+}
+
+/// Apply the retention limits to history in place: delete expired audio, clear the links,
+/// and remove files no history entry points at (e.g. entries capped out of history).
+pub fn enforce_retention_limits(app_handle: &AppHandle, history: &mut [HistoryEntry]) {
+    let (max_age_days, max_total_mb) = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        (settings_guard.audio_retention.max_age_days, settings_guard.audio_retention.max_total_mb)
+    };
+
+    let dir = match audio_dir(app_handle) {
+        Ok(dir) => dir,
+        Err(e) => {
+            error!("[AudioRetention] {}", e);
+            return;
+        }
+    };
+
+    let mut linked = Vec::new();
+    for entry in history.iter_mut() {
+        if let Some(file_name) = entry.audio_file.clone() {
+            match fs::metadata(dir.join(&file_name)) {
+                Ok(meta) => linked.push((file_name, entry.timestamp, meta.len())),
+                Err(_) => {
+                    warn!("[AudioRetention] Audio {} is missing, unlinking", file_name);
+                    entry.audio_file = None;
+                }
+            }
+        }
+    }
+
+    let to_drop = plan_retention(&linked, Utc::now(), max_age_days, max_total_mb * 1024 * 1024);
+    for entry in history.iter_mut() {
+        if entry.audio_file.as_ref().map_or(false, |f| to_drop.contains(f)) {
+            entry.audio_file = None;
+        }
+    }
+
+    let still_linked: HashSet<&String> = history.iter().filter_map(|e| e.audio_file.as_ref()).collect();
+    if let Ok(read_dir) = fs::read_dir(&dir) {
+        for item in read_dir.flatten() {
+            let name = item.file_name().to_string_lossy().into_owned();
+            if !still_linked.contains(&name) {
+                match fs::remove_file(item.path()) {
+                    Ok(_) => info!("[AudioRetention] Deleted retained audio {}", name),
+                    Err(e) => warn!("[AudioRetention] Failed to delete {}: {}", name, e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, days_ago: i64, size: u64, now: DateTime<Utc>) -> (String, DateTime<Utc>, u64) {
+        (name.to_string(), now - Duration::days(days_ago), size)
+    }
+
+    #[test]
+    fn drops_entries_older_than_max_age() {
+        let now = Utc::now();
+        let entries = vec![entry("old", 40, 10, now), entry("new", 1, 10, now)];
+        let drop = plan_retention(&entries, now, 30, 0);
+        assert!(drop.contains("old"));
+        assert!(!drop.contains("new"));
+    }
+
+    #[test]
+    fn drops_oldest_first_when_over_size_budget() {
+        let now = Utc::now();
+        let entries = vec![entry("b", 2, 60, now), entry("a", 3, 60, now), entry("c", 1, 60, now)];
+        let drop = plan_retention(&entries, now, 0, 130);
+        assert_eq!(drop.len(), 1);
+        assert!(drop.contains("a"));
+    }
+
+    #[test]
+    fn zero_limits_keep_everything() {
+        let now = Utc::now();
+        let entries = vec![entry("a", 365, 1 << 30, now)];
+        assert!(plan_retention(&entries, now, 0, 0).is_empty());
+    }
+}
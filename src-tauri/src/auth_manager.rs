@@ -122,7 +122,7 @@ pub fn get_error_message(status: StatusCode, default_message: &str) -> String {
         StatusCode::INTERNAL_SERVER_ERROR | 
         StatusCode::BAD_GATEWAY | 
         StatusCode::SERVICE_UNAVAILABLE | 
-        StatusCode::GATEWAY_TIMEOUT => "Server error - stats will be retried later".to_string(),
+        StatusCode::GATEWAY_TIMEOUT => "Server error - please try again later".to_string(),
         _ => default_message.to_string(),
     }
 }
\ No newline at end of file
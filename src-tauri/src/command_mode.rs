@@ -0,0 +1,240 @@
+// src-tauri/src/command_mode.rs
+//
+// Voice commands on selected text: the user selects text in any app, holds (or taps) the
+// command hotkey and speaks an instruction such as "make this more formal". When the
+// recording starts, the selection is copied with a simulated Ctrl/Cmd+C while its app is
+// still focused; when it stops, the spoken instruction is applied to the selection as a
+// direct prompt and the result is pasted over it. Whatever was on the clipboard before is
+// put back after the copy and again after the paste. With auto-paste off the result is only
+// copied, and stays on the clipboard. The spoken instruction isn't saved to history.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use rdev::Key as RdevKey;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+
+use crate::ai_actions::AiActionsStore;
+use crate::config::{CommandModeSettings, SETTINGS};
+
+// How long the focused app gets to put the selection on the clipboard
+const COPY_TIMEOUT: Duration = Duration::from_millis(600);
+const COPY_POLL_INTERVAL: Duration = Duration::from_millis(20);
+// Apps read the clipboard after the paste shortcut; restoring too early pastes the old contents
+const RESTORE_DELAY: Duration = Duration::from_millis(400);
+
+// Registered command hotkey (None while command mode is off)
+static COMMAND_KEY: Lazy<Mutex<Option<RdevKey>>> = Lazy::new(|| Mutex::new(None));
+// The command hotkey is physically held (set by the rdev callback)
+static KEY_DOWN: AtomicBool = AtomicBool::new(false);
+// Selection capture for the take currently being recorded
+static CURRENT_TAKE: Lazy<Mutex<Option<PendingCommand>>> = Lazy::new(|| Mutex::new(None));
+
+// What the clipboard held before the copy
+enum ClipboardContents {
+    Text(String),
+    Image(arboard::ImageData<'static>),
+    Empty,
+}
+
+struct Capture {
+    selection: String,
+    original: ClipboardContents,
+}
+
+/// A command take; the selection is copied in the background while the user speaks
+pub struct PendingCommand {
+    capture: JoinHandle<Result<Capture, String>>,
+}
+
+/// The hotkey must be one action hotkeys can use and not taken by an action
+pub fn check_hotkey(settings: &CommandModeSettings, store: &AiActionsStore) -> Result<(), String> {
+    if !settings.enabled {
+        return Ok(());
+    }
+    let hotkey = settings.hotkey.trim();
+    if crate::ai_actions::parse_hotkey(hotkey).is_none() {
+        return Err(format!("Unsupported command hotkey '{}'", hotkey));
+    }
+    if let Some(action) = store.actions.iter().find(|action| action.hotkey.as_deref() == Some(hotkey)) {
+        return Err(format!("{} is already the hotkey of the AI action '{}'", hotkey, action.name));
+    }
+    Ok(())
+}
+
+/// Register the configured hotkey; called at startup and after the settings are saved
+pub fn refresh_hotkey() {
+    let settings = SETTINGS.lock().unwrap().command_mode.clone();
+    let key = if settings.enabled { crate::ai_actions::parse_hotkey(&settings.hotkey) } else { None };
+    if settings.enabled && key.is_none() {
+        warn!("[Command Mode] Unsupported hotkey '{}'; command mode is off", settings.hotkey);
+    }
+    *COMMAND_KEY.lock().unwrap() = key;
+}
+
+pub fn is_command_key(key: RdevKey) -> bool {
+    *COMMAND_KEY.lock().unwrap() == Some(key)
+}
+
+pub fn set_key_down(down: bool) {
+    KEY_DOWN.store(down, Ordering::SeqCst);
+}
+
+/// Called when a recording starts: a take started with the command hotkey copies the
+/// selection right away
+pub fn arm_for_new_take() -> bool {
+    let armed = KEY_DOWN.load(Ordering::SeqCst) && COMMAND_KEY.lock().unwrap().is_some();
+    let pending = armed.then(|| PendingCommand { capture: tauri::async_runtime::spawn_blocking(capture_selection) });
+    *CURRENT_TAKE.lock().unwrap() = pending;
+    armed
+}
+
+pub fn take_is_armed() -> bool {
+    CURRENT_TAKE.lock().unwrap().is_some()
+}
+
+/// Consume the command for the take being stopped
+pub fn take_armed() -> Option<PendingCommand> {
+    CURRENT_TAKE.lock().unwrap().take()
+}
+
+/// A cancelled take must not leave the next one armed
+pub fn disarm() {
+    *CURRENT_TAKE.lock().unwrap() = None;
+}
+
+// --- Clipboard ---
+
+fn read_contents(clipboard: &mut arboard::Clipboard) -> ClipboardContents {
+    if let Ok(text) = clipboard.get_text() {
+        return ClipboardContents::Text(text);
+    }
+    match clipboard.get_image() {
+        Ok(image) => ClipboardContents::Image(image),
+        Err(_) => ClipboardContents::Empty,
+    }
+}
+
+fn restore(clipboard: &mut arboard::Clipboard, original: &ClipboardContents) {
+    let result = match original {
+        ClipboardContents::Text(text) => clipboard.set_text(text.clone()),
+        ClipboardContents::Image(image) => clipboard.set_image(image.clone()),
+        ClipboardContents::Empty => clipboard.clear(),
+    };
+    if let Err(e) = result {
+        warn!("[Command Mode] Could not restore the clipboard: {}", e);
+    }
+}
+
+// Ctrl+C, or Cmd+C on macOS
+fn send_copy_shortcut() -> Result<(), String> {
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("Failed to initialize Enigo: {:?}", e))?;
+    #[cfg(target_os = "macos")]
+    let modifier = Key::Meta;
+    #[cfg(not(target_os = "macos"))]
+    let modifier = Key::Control;
+    enigo.key(modifier, Direction::Press).map_err(|e| format!("Failed to simulate the copy shortcut: {:?}", e))?;
+    let copied = enigo.key(Key::Unicode('c'), Direction::Click);
+    let _ = enigo.key(modifier, Direction::Release);
+    copied.map_err(|e| format!("Failed to simulate the copy shortcut: {:?}", e))
+}
+
+fn wait_for_text(clipboard: &mut arboard::Clipboard) -> Option<String> {
+    let deadline = Instant::now() + COPY_TIMEOUT;
+    loop {
+        match clipboard.get_text() {
+            Ok(text) if !text.trim().is_empty() => return Some(text),
+            _ if Instant::now() >= deadline => return None,
+            _ => std::thread::sleep(COPY_POLL_INTERVAL),
+        }
+    }
+}
+
+// Blocking: runs on the blocking pool while the recording starts
+fn capture_selection() -> Result<Capture, String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| format!("Clipboard unavailable: {}", e))?;
+    let original = read_contents(&mut clipboard);
+    // Cleared first, so an empty selection isn't mistaken for the old clipboard text
+    let _ = clipboard.clear();
+    let copied = send_copy_shortcut().map(|_| wait_for_text(&mut clipboard));
+    restore(&mut clipboard, &original);
+
+    let selection = copied?
+        .ok_or_else(|| "No text is selected. Select the text to edit, then hold the command hotkey and speak.".to_string())?;
+    info!("[Command Mode] Captured {} chars of selected text", selection.len());
+    Ok(Capture { selection, original })
+}
+
+// --- Applying the command ---
+
+async fn run(app_handle: &AppHandle, command: PendingCommand, instruction: &str, auto_paste: bool) -> Result<String, String> {
+    let capture = command.capture.await.map_err(|e| format!("Copying the selection failed: {}", e))??;
+    let instruction = instruction.trim();
+    if instruction.is_empty() {
+        return Err("No instruction was heard; the selection was left unchanged".to_string());
+    }
+
+    info!("[Command Mode] Applying \"{}\" to {} chars", instruction, capture.selection.len());
+    let edited = crate::ai_actions_manager::apply_instruction_to_selection(app_handle, &capture.selection, instruction).await?;
+    let edited = edited.trim().to_string();
+    if edited.is_empty() {
+        return Err("The AI returned no text; the selection was left unchanged".to_string());
+    }
+
+    crate::write_to_clipboard_internal(edited.clone())?;
+    if !auto_paste {
+        let _ = app_handle.emit_all("fethr-copied-to-clipboard", ());
+        info!("[Command Mode] Auto-paste is off; the result is on the clipboard");
+        return Ok(edited);
+    }
+    // The selection is still selected in its app, so pasting replaces it
+    let pasted = crate::paste_text_to_cursor().await;
+    tokio::time::sleep(RESTORE_DELAY).await;
+    match arboard::Clipboard::new() {
+        Ok(mut clipboard) => restore(&mut clipboard, &capture.original),
+        Err(e) => warn!("[Command Mode] Could not restore the clipboard: {}", e),
+    }
+    pasted?;
+    Ok(edited)
+}
+
+/// Apply the spoken `instruction` to the selection copied for `command` and paste the
+/// result over it (`auto_paste`), or just copy it. On failure the selection is left alone
+/// and the UI is told why.
+pub async fn apply(app_handle: &AppHandle, command: PendingCommand, instruction: &str, auto_paste: bool) -> Result<String, String> {
+    let result = run(app_handle, command, instruction, auto_paste).await;
+    if let Err(e) = &result {
+        error!("[Command Mode] {}", e);
+        let _ = app_handle.emit_all("fethr-command-failed", e.clone());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_actions::UserAction;
+
+    #[test]
+    fn command_hotkey_must_be_free_and_supported() {
+        let settings = CommandModeSettings { enabled: true, hotkey: "F8".to_string() };
+        let mut store = AiActionsStore::default();
+        assert!(check_hotkey(&settings, &store).is_ok());
+        assert!(check_hotkey(&CommandModeSettings { hotkey: "AltGr".to_string(), ..settings.clone() }, &store).is_err());
+
+        store.actions.push(UserAction {
+            id: "user_1".to_string(),
+            name: "Haiku".to_string(),
+            template: "${text}".to_string(),
+            output_mode: Default::default(),
+            model: None,
+            hotkey: Some("F8".to_string()),
+        });
+        assert!(check_hotkey(&settings, &store).unwrap_err().contains("Haiku"));
+        assert!(check_hotkey(&CommandModeSettings { enabled: false, ..settings }, &store).is_ok());
+    }
+}
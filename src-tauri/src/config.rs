@@ -32,6 +32,8 @@ pub struct AudioSettings {
     pub input_gain: f32,                       // Microphone gain (0.5-2.0)
     pub noise_suppression: bool,               // Enable noise reduction
     pub auto_gain_control: bool,               // Enable AGC
+    #[serde(default)]
+    pub input_channel: Option<u16>,            // 0-based channel on multi-input devices; None = downmix all
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -53,12 +55,452 @@ pub struct SmartFormattingSettings {
     pub list_detection: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioRetentionFormat {
+    Flac, // Lossless
+    Opus, // ~24 kbps, much smaller
+}
+
+/// Settings for keeping compressed source audio alongside history entries
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AudioRetentionSettings {
+    #[serde(default = "default_audio_retention_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_audio_retention_format")]
+    pub format: AudioRetentionFormat,
+    #[serde(default = "default_audio_retention_max_total_mb")]
+    pub max_total_mb: u64, // 0 = no size limit
+    #[serde(default = "default_audio_retention_max_age_days")]
+    pub max_age_days: u32, // 0 = keep forever
+}
+
+/// whisper.cpp decoding parameters. Defaults match whisper-cli's own.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WhisperDecodingSettings {
+    #[serde(default = "default_beam_size")]
+    pub beam_size: u32, // -bs; 1 = greedy
+    #[serde(default = "default_best_of")]
+    pub best_of: u32, // -bo; candidates when sampling with temperature > 0
+    #[serde(default)]
+    pub temperature: f32, // -tp
+    #[serde(default = "default_temperature_increment")]
+    pub temperature_increment: f32, // -tpi; step used when a decode fails the thresholds
+    #[serde(default)]
+    pub no_fallback: bool, // -nf; never retry at higher temperature
+    #[serde(default = "default_entropy_threshold")]
+    pub entropy_threshold: f32, // -et; above this the decode counts as repetitive
+    #[serde(default = "default_logprob_threshold")]
+    pub logprob_threshold: f32, // -lpt; below this the decode counts as failed
+    #[serde(default)]
+    pub threads: u32, // -t; 0 = whisper's default
+    #[serde(default = "default_no_speech_threshold")]
+    pub no_speech_threshold: f32, // -nth
+}
+
+pub const MAX_WHISPER_DECODERS: u32 = 8; // whisper.cpp's WHISPER_MAX_DECODERS
+pub const MAX_WHISPER_THREADS: u32 = 64;
+
+impl WhisperDecodingSettings {
+    /// Reject values whisper would refuse or that make no sense
+    pub fn validate(&self) -> Result<(), String> {
+        fn check_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), String> {
+            if !value.is_finite() || value < min || value > max {
+                return Err(format!("{} must be between {} and {} (got {})", name, min, max, value));
+            }
+            Ok(())
+        }
+
+        if self.beam_size == 0 || self.beam_size > MAX_WHISPER_DECODERS {
+            return Err(format!("Beam size must be between 1 and {} (got {})", MAX_WHISPER_DECODERS, self.beam_size));
+        }
+        if self.best_of == 0 || self.best_of > MAX_WHISPER_DECODERS {
+            return Err(format!("Best-of must be between 1 and {} (got {})", MAX_WHISPER_DECODERS, self.best_of));
+        }
+        check_range("Temperature", self.temperature, 0.0, 1.0)?;
+        check_range("Temperature increment", self.temperature_increment, 0.0, 1.0)?;
+        check_range("Entropy threshold", self.entropy_threshold, 0.0, 10.0)?;
+        check_range("Log probability threshold", self.logprob_threshold, -10.0, 0.0)?;
+        check_range("No-speech threshold", self.no_speech_threshold, 0.0, 1.0)?;
+        if self.threads > MAX_WHISPER_THREADS {
+            return Err(format!("Thread count must be at most {} (got {})", MAX_WHISPER_THREADS, self.threads));
+        }
+        Ok(())
+    }
+}
+
+/// What to do with whisper output the hallucination filter considers suspect
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HallucinationFilterMode {
+    Off,
+    Flag, // Keep the text, record it for review
+    Drop, // Remove it before correction and paste
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HallucinationFilterSettings {
+    #[serde(default = "default_hallucination_filter_mode")]
+    pub mode: HallucinationFilterMode,
+    #[serde(default = "default_compression_ratio_threshold")]
+    pub compression_ratio_threshold: f32, // zlib ratio above which a segment counts as looping
+    #[serde(default = "default_min_speech_seconds")]
+    pub min_speech_seconds: f32, // Below this much detected speech, all output is suspect
+}
+
+impl HallucinationFilterSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let ratio = self.compression_ratio_threshold;
+        if !ratio.is_finite() || !(1.0..=10.0).contains(&ratio) {
+            return Err(format!("Compression ratio threshold must be between 1 and 10 (got {})", ratio));
+        }
+        let speech = self.min_speech_seconds;
+        if !speech.is_finite() || !(0.0..=5.0).contains(&speech) {
+            return Err(format!("Minimum speech must be between 0 and 5 seconds (got {})", speech));
+        }
+        Ok(())
+    }
+}
+
+/// Per-word whisper confidence: correction gating and highlighting
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WordConfidenceSettings {
+    #[serde(default = "default_gate_corrections")]
+    pub gate_corrections: bool, // Dictionary correction only rewrites low-confidence words
+    #[serde(default = "default_low_confidence_threshold")]
+    pub low_confidence_threshold: f32, // 0-1; words below this are highlighted and correctable
+}
+
+impl WordConfidenceSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let threshold = self.low_confidence_threshold;
+        if !threshold.is_finite() || !(0.0..=1.0).contains(&threshold) {
+            return Err(format!("Low-confidence threshold must be between 0 and 1 (got {})", threshold));
+        }
+        Ok(())
+    }
+}
+
+/// How long whisper and ffmpeg may run before they're killed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProcessTimeoutSettings {
+    #[serde(default = "default_whisper_timeout_secs")]
+    pub whisper_timeout_secs: u64,
+    #[serde(default = "default_ffmpeg_timeout_secs")]
+    pub ffmpeg_timeout_secs: u64,
+}
+
+impl ProcessTimeoutSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(10..=3600).contains(&self.whisper_timeout_secs) {
+            return Err(format!("Whisper timeout must be between 10 and 3600 seconds (got {})", self.whisper_timeout_secs));
+        }
+        if !(5..=600).contains(&self.ffmpeg_timeout_secs) {
+            return Err(format!("ffmpeg timeout must be between 5 and 600 seconds (got {})", self.ffmpeg_timeout_secs));
+        }
+        Ok(())
+    }
+
+    pub fn whisper_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.whisper_timeout_secs)
+    }
+
+    pub fn ffmpeg_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.ffmpeg_timeout_secs)
+    }
+}
+
+/// Where audio is transcribed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackendKind {
+    LocalCli,  // Bundled whisper binary
+    InProcess, // whisper.cpp linked into the app (`in-process-whisper` feature)
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible, // Any /v1/audio/transcriptions API (OpenAI, Groq, faster-whisper-server, ...)
+    WhisperServer, // Self-hosted whisper.cpp server
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OpenAiCompatibleBackendSettings {
+    #[serde(default = "default_openai_base_url")]
+    pub base_url: String,
+    #[serde(default)]
+    pub api_key: String, // Empty = no Authorization header (local servers)
+    #[serde(default = "default_openai_transcription_model")]
+    pub model: String,
+    #[serde(default = "default_backend_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WhisperServerBackendSettings {
+    #[serde(default = "default_whisper_server_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_whisper_server_endpoint_path")]
+    pub endpoint_path: String, // "/inference" for whisper.cpp's server
+    #[serde(default = "default_backend_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionBackendSettings {
+    #[serde(default = "default_backend_order")]
+    pub order: Vec<TranscriptionBackendKind>, // Tried in turn until one succeeds
+    #[serde(default)]
+    pub openai_compatible: OpenAiCompatibleBackendSettings,
+    #[serde(default)]
+    pub whisper_server: WhisperServerBackendSettings,
+}
+
+pub const MAX_BACKEND_TIMEOUT_SECS: u64 = 600;
+
+fn check_url(name: &str, url: &str) -> Result<(), String> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(format!("{} URL must start with http:// or https:// (got '{}')", name, url));
+    }
+    Ok(())
+}
+
+fn check_prompt_tokens(name: &str, max_prompt_tokens: u32) -> Result<(), String> {
+    if !(100..=1_000_000).contains(&max_prompt_tokens) {
+        return Err(format!("{} prompt token limit must be between 100 and 1000000 (got {})", name, max_prompt_tokens));
+    }
+    Ok(())
+}
+
+fn check_timeout(name: &str, timeout_secs: u64) -> Result<(), String> {
+    if timeout_secs == 0 || timeout_secs > MAX_BACKEND_TIMEOUT_SECS {
+        return Err(format!("{} timeout must be between 1 and {} seconds (got {})", name, MAX_BACKEND_TIMEOUT_SECS, timeout_secs));
+    }
+    Ok(())
+}
+
+impl TranscriptionBackendSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.order.is_empty() {
+            return Err("At least one transcription backend must be enabled".to_string());
+        }
+        for (i, kind) in self.order.iter().enumerate() {
+            if self.order[..i].contains(kind) {
+                return Err(format!("Transcription backend {:?} is listed twice", kind));
+            }
+        }
+        if self.order.contains(&TranscriptionBackendKind::OpenAiCompatible) {
+            check_url("OpenAI-compatible", &self.openai_compatible.base_url)?;
+            check_timeout("OpenAI-compatible", self.openai_compatible.timeout_secs)?;
+            if self.openai_compatible.model.trim().is_empty() {
+                return Err("OpenAI-compatible backend needs a model name".to_string());
+            }
+        }
+        if self.order.contains(&TranscriptionBackendKind::WhisperServer) {
+            check_url("Whisper server", &self.whisper_server.base_url)?;
+            check_timeout("Whisper server", self.whisper_server.timeout_secs)?;
+            if !self.whisper_server.endpoint_path.starts_with('/') {
+                return Err(format!("Whisper server endpoint must start with '/' (got '{}')", self.whisper_server.endpoint_path));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which service AI actions are sent to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AiProviderKind {
+    Proxy, // Fethr's hosted relay
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible, // Any /v1/chat/completions API (OpenAI, Ollama, llama.cpp server, ...)
+    Anthropic,
+    Local, // llama.cpp on this machine; works offline
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProxyProviderSettings {
+    #[serde(default = "default_ai_proxy_url")]
+    pub url: String,
+    #[serde(default)]
+    pub model: String, // Empty = the relay's default
+    #[serde(default = "default_ai_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_proxy_max_prompt_tokens")]
+    pub max_prompt_tokens: u32, // Longer prompts are refused before sending
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OpenAiChatProviderSettings {
+    #[serde(default = "default_openai_base_url")]
+    pub base_url: String,
+    #[serde(default)]
+    pub api_key: String, // Empty = no Authorization header (local servers)
+    #[serde(default = "default_openai_chat_model")]
+    pub model: String,
+    #[serde(default = "default_ai_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_openai_max_prompt_tokens")]
+    pub max_prompt_tokens: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnthropicProviderSettings {
+    #[serde(default = "default_anthropic_base_url")]
+    pub base_url: String,
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default = "default_anthropic_model")]
+    pub model: String,
+    #[serde(default = "default_anthropic_max_tokens")]
+    pub max_tokens: u32,
+    #[serde(default = "default_ai_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_anthropic_max_prompt_tokens")]
+    pub max_prompt_tokens: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LocalProviderSettings {
+    #[serde(default = "default_local_model_id")]
+    pub model_id: String, // Entry of local_llm::LOCAL_MODEL_CATALOG
+    #[serde(default = "default_local_max_tokens")]
+    pub max_tokens: u32, // Longest answer generated
+    #[serde(default)]
+    pub threads: u32, // 0 = llama.cpp's default
+    #[serde(default = "default_local_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_local_max_prompt_tokens")]
+    pub max_prompt_tokens: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AiProviderSettings {
+    #[serde(default = "default_ai_provider")]
+    pub provider: AiProviderKind,
+    #[serde(default)]
+    pub proxy: ProxyProviderSettings,
+    #[serde(default)]
+    pub openai_compatible: OpenAiChatProviderSettings,
+    #[serde(default)]
+    pub anthropic: AnthropicProviderSettings,
+    #[serde(default)]
+    pub local: LocalProviderSettings,
+}
+
+impl AiProviderSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        // Only the selected provider has to be usable
+        match self.provider {
+            AiProviderKind::Proxy => {
+                check_url("AI proxy", &self.proxy.url)?;
+                check_timeout("AI proxy", self.proxy.timeout_secs)?;
+                check_prompt_tokens("AI proxy", self.proxy.max_prompt_tokens)?;
+            }
+            AiProviderKind::OpenAiCompatible => {
+                check_url("OpenAI-compatible chat", &self.openai_compatible.base_url)?;
+                check_timeout("OpenAI-compatible chat", self.openai_compatible.timeout_secs)?;
+                check_prompt_tokens("OpenAI-compatible chat", self.openai_compatible.max_prompt_tokens)?;
+                if self.openai_compatible.model.trim().is_empty() {
+                    return Err("OpenAI-compatible chat provider needs a model name".to_string());
+                }
+            }
+            AiProviderKind::Anthropic => {
+                check_url("Anthropic", &self.anthropic.base_url)?;
+                check_timeout("Anthropic", self.anthropic.timeout_secs)?;
+                check_prompt_tokens("Anthropic", self.anthropic.max_prompt_tokens)?;
+                if self.anthropic.model.trim().is_empty() {
+                    return Err("Anthropic provider needs a model name".to_string());
+                }
+                if self.anthropic.max_tokens == 0 {
+                    return Err("Anthropic max tokens must be at least 1".to_string());
+                }
+            }
+            AiProviderKind::Local => {
+                check_timeout("Local model", self.local.timeout_secs)?;
+                check_prompt_tokens("Local model", self.local.max_prompt_tokens)?;
+                if self.local.model_id.trim().is_empty() {
+                    return Err("The local AI provider needs a model".to_string());
+                }
+                if !(16..=4096).contains(&self.local.max_tokens) {
+                    return Err(format!("Local model max tokens must be between 16 and 4096 (got {})", self.local.max_tokens));
+                }
+                if self.local.threads > 256 {
+                    return Err(format!("Local model threads must be at most 256 (got {})", self.local.threads));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// AI actions run automatically on every dictation before it's pasted
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AiChainSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub actions: Vec<String>, // Action ids, applied in order (built-in or user-defined)
+    #[serde(default = "default_ai_chain_latency_budget_ms")]
+    pub latency_budget_ms: u64, // Past this, the raw transcription is pasted instead
+}
+
+pub const MAX_AI_CHAIN_STEPS: usize = 5;
+
+impl AiChainSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.actions.is_empty() {
+            return Err("The automatic AI chain needs at least one action".to_string());
+        }
+        if self.actions.len() > MAX_AI_CHAIN_STEPS {
+            return Err(format!("The automatic AI chain can have at most {} actions (got {})", MAX_AI_CHAIN_STEPS, self.actions.len()));
+        }
+        if self.actions.iter().any(|action| action.trim().is_empty()) {
+            return Err("AI chain action ids cannot be empty".to_string());
+        }
+        if !(500..=120_000).contains(&self.latency_budget_ms) {
+            return Err(format!("AI chain latency budget must be between 500 and 120000 ms (got {})", self.latency_budget_ms));
+        }
+        Ok(())
+    }
+}
+
+/// Voice commands on the selected text (see command_mode.rs)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommandModeSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_command_mode_hotkey")]
+    pub hotkey: String, // Key name as for action hotkeys, e.g. "F8"
+}
+
+impl CommandModeSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.hotkey.trim().is_empty() {
+            return Err("Command mode needs a hotkey".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Translation dictation (hotkey held with Shift)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TranslationSettings {
+    #[serde(default = "default_translation_enabled")]
+    pub enabled: bool, // Shift + hotkey starts a translated take
+    #[serde(default = "default_translation_target_language")]
+    pub target_language: String, // ISO code; "en" uses whisper's own translation
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AppSettings {
     #[serde(default = "default_model_name")]
     pub model_name: String,
+    #[serde(default)]
+    pub models_dir: Option<String>, // Where downloaded models go; None = app data dir
+    #[serde(default = "default_auto_model_latency_budget_ms")]
+    pub auto_model_latency_budget_ms: u64, // Target transcription time when model_name = "auto"
     #[serde(default = "default_language")]
     pub language: String,
+    #[serde(default)]
+    pub allowed_languages: Vec<String>, // Restricts "auto" detection; empty = any language
     #[serde(default = "default_auto_paste")]
     pub auto_paste: bool,
     #[serde(default = "default_pill_enabled")]
@@ -79,6 +521,28 @@ pub struct AppSettings {
     pub sounds: SoundSettings,
     #[serde(default = "default_smart_formatting")]
     pub smart_formatting: SmartFormattingSettings,
+    #[serde(default = "default_audio_retention")]
+    pub audio_retention: AudioRetentionSettings,
+    #[serde(default = "default_translation")]
+    pub translation: TranslationSettings,
+    #[serde(default = "default_whisper_decoding")]
+    pub whisper_decoding: WhisperDecodingSettings,
+    #[serde(default = "default_hallucination_filter")]
+    pub hallucination_filter: HallucinationFilterSettings,
+    #[serde(default = "default_word_confidence")]
+    pub word_confidence: WordConfidenceSettings,
+    #[serde(default)]
+    pub transcription_backends: TranscriptionBackendSettings,
+    #[serde(default)]
+    pub process_timeouts: ProcessTimeoutSettings,
+    #[serde(default)]
+    pub ai_provider: AiProviderSettings,
+    #[serde(default)]
+    pub ai_chain: AiChainSettings,
+    #[serde(default)]
+    pub command_mode: CommandModeSettings,
+    #[serde(default = "default_pause_hotkey")]
+    pub pause_hotkey: String, // Pauses/resumes a locked recording; key name as for action hotkeys
 }
 
 /// Settings for fuzzy dictionary correction
@@ -100,6 +564,10 @@ fn default_model_name() -> String {
     "ggml-tiny.en.bin".to_string()
 }
 
+fn default_auto_model_latency_budget_ms() -> u64 {
+    2500
+}
+
 fn default_language() -> String {
     "en".to_string()
 }
@@ -139,6 +607,7 @@ fn default_audio_settings() -> AudioSettings {
         input_gain: 1.0,             // Normal gain
         noise_suppression: false,     // Disabled by default
         auto_gain_control: false,     // Disabled by default
+        input_channel: None,          // Downmix all channels
     }
 }
 
@@ -187,6 +656,197 @@ fn default_list_detection() -> bool {
     false // Start conservative - disabled by default
 }
 
+fn default_audio_retention() -> AudioRetentionSettings {
+    AudioRetentionSettings::default()
+}
+
+fn default_audio_retention_enabled() -> bool {
+    false // Opt-in: recordings are deleted after transcription by default
+}
+
+fn default_audio_retention_format() -> AudioRetentionFormat {
+    AudioRetentionFormat::Opus
+}
+
+fn default_audio_retention_max_total_mb() -> u64 {
+    500
+}
+
+fn default_audio_retention_max_age_days() -> u32 {
+    30
+}
+
+fn default_whisper_decoding() -> WhisperDecodingSettings {
+    WhisperDecodingSettings::default()
+}
+
+fn default_hallucination_filter() -> HallucinationFilterSettings {
+    HallucinationFilterSettings::default()
+}
+
+fn default_hallucination_filter_mode() -> HallucinationFilterMode {
+    HallucinationFilterMode::Drop
+}
+
+fn default_compression_ratio_threshold() -> f32 {
+    2.4 // whisper's own threshold
+}
+
+fn default_min_speech_seconds() -> f32 {
+    0.3
+}
+
+fn default_word_confidence() -> WordConfidenceSettings {
+    WordConfidenceSettings::default()
+}
+
+fn default_gate_corrections() -> bool {
+    true
+}
+
+fn default_low_confidence_threshold() -> f32 {
+    0.5
+}
+
+fn default_whisper_timeout_secs() -> u64 {
+    300 // Large models on slow CPUs take minutes for long dictations
+}
+
+fn default_ffmpeg_timeout_secs() -> u64 {
+    60
+}
+
+fn default_backend_order() -> Vec<TranscriptionBackendKind> {
+    vec![TranscriptionBackendKind::LocalCli]
+}
+
+fn default_openai_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_openai_transcription_model() -> String {
+    "whisper-1".to_string()
+}
+
+fn default_whisper_server_base_url() -> String {
+    "http://127.0.0.1:8080".to_string()
+}
+
+fn default_whisper_server_endpoint_path() -> String {
+    "/inference".to_string()
+}
+
+fn default_backend_timeout_secs() -> u64 {
+    60
+}
+
+fn default_ai_provider() -> AiProviderKind {
+    AiProviderKind::Proxy
+}
+
+fn default_ai_proxy_url() -> String {
+    "https://fethr-ai-proxy.vercel.app/api/ai-proxy".to_string()
+}
+
+fn default_openai_chat_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_anthropic_base_url() -> String {
+    "https://api.anthropic.com".to_string()
+}
+
+fn default_anthropic_model() -> String {
+    "claude-3-5-haiku-latest".to_string()
+}
+
+fn default_anthropic_max_tokens() -> u32 {
+    4096
+}
+
+// Prompt budgets: the proxy is kept small, the APIs' context windows are much larger
+fn default_proxy_max_prompt_tokens() -> u32 {
+    4_000
+}
+
+fn default_openai_max_prompt_tokens() -> u32 {
+    16_000
+}
+
+fn default_anthropic_max_prompt_tokens() -> u32 {
+    32_000
+}
+
+// Context window of the catalog models is larger, but CPU prompt processing is slow
+fn default_local_max_prompt_tokens() -> u32 {
+    3_000
+}
+
+fn default_local_model_id() -> String {
+    "qwen2.5-1.5b-instruct-q4_k_m".to_string()
+}
+
+fn default_local_max_tokens() -> u32 {
+    512
+}
+
+// CPU generation is much slower than an API
+fn default_local_timeout_secs() -> u64 {
+    180
+}
+
+fn default_ai_timeout_secs() -> u64 {
+    60
+}
+
+fn default_ai_chain_latency_budget_ms() -> u64 {
+    8000
+}
+
+fn default_command_mode_hotkey() -> String {
+    "F8".to_string()
+}
+
+fn default_pause_hotkey() -> String {
+    "Pause".to_string()
+}
+
+fn default_beam_size() -> u32 {
+    5
+}
+
+fn default_best_of() -> u32 {
+    5
+}
+
+fn default_temperature_increment() -> f32 {
+    0.2
+}
+
+fn default_entropy_threshold() -> f32 {
+    2.4
+}
+
+fn default_logprob_threshold() -> f32 {
+    -1.0
+}
+
+fn default_no_speech_threshold() -> f32 {
+    0.6
+}
+
+fn default_translation() -> TranslationSettings {
+    TranslationSettings::default()
+}
+
+fn default_translation_enabled() -> bool {
+    true
+}
+
+fn default_translation_target_language() -> String {
+    "en".to_string()
+}
+
 impl Default for FuzzyCorrectionSettings {
     fn default() -> Self {
         Self {
@@ -209,11 +869,188 @@ impl Default for SmartFormattingSettings {
     }
 }
 
+impl Default for AudioRetentionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_audio_retention_enabled(),
+            format: default_audio_retention_format(),
+            max_total_mb: default_audio_retention_max_total_mb(),
+            max_age_days: default_audio_retention_max_age_days(),
+        }
+    }
+}
+
+impl Default for WhisperDecodingSettings {
+    fn default() -> Self {
+        Self {
+            beam_size: default_beam_size(),
+            best_of: default_best_of(),
+            temperature: 0.0,
+            temperature_increment: default_temperature_increment(),
+            no_fallback: false,
+            entropy_threshold: default_entropy_threshold(),
+            logprob_threshold: default_logprob_threshold(),
+            threads: 0,
+            no_speech_threshold: default_no_speech_threshold(),
+        }
+    }
+}
+
+impl Default for HallucinationFilterSettings {
+    fn default() -> Self {
+        Self {
+            mode: default_hallucination_filter_mode(),
+            compression_ratio_threshold: default_compression_ratio_threshold(),
+            min_speech_seconds: default_min_speech_seconds(),
+        }
+    }
+}
+
+impl Default for WordConfidenceSettings {
+    fn default() -> Self {
+        Self {
+            gate_corrections: default_gate_corrections(),
+            low_confidence_threshold: default_low_confidence_threshold(),
+        }
+    }
+}
+
+impl Default for ProcessTimeoutSettings {
+    fn default() -> Self {
+        Self {
+            whisper_timeout_secs: default_whisper_timeout_secs(),
+            ffmpeg_timeout_secs: default_ffmpeg_timeout_secs(),
+        }
+    }
+}
+
+impl Default for OpenAiCompatibleBackendSettings {
+    fn default() -> Self {
+        Self {
+            base_url: default_openai_base_url(),
+            api_key: String::new(),
+            model: default_openai_transcription_model(),
+            timeout_secs: default_backend_timeout_secs(),
+        }
+    }
+}
+
+impl Default for WhisperServerBackendSettings {
+    fn default() -> Self {
+        Self {
+            base_url: default_whisper_server_base_url(),
+            endpoint_path: default_whisper_server_endpoint_path(),
+            timeout_secs: default_backend_timeout_secs(),
+        }
+    }
+}
+
+impl Default for TranscriptionBackendSettings {
+    fn default() -> Self {
+        Self {
+            order: default_backend_order(),
+            openai_compatible: OpenAiCompatibleBackendSettings::default(),
+            whisper_server: WhisperServerBackendSettings::default(),
+        }
+    }
+}
+
+impl Default for ProxyProviderSettings {
+    fn default() -> Self {
+        Self {
+            url: default_ai_proxy_url(),
+            model: String::new(),
+            timeout_secs: default_ai_timeout_secs(),
+            max_prompt_tokens: default_proxy_max_prompt_tokens(),
+        }
+    }
+}
+
+impl Default for OpenAiChatProviderSettings {
+    fn default() -> Self {
+        Self {
+            base_url: default_openai_base_url(),
+            api_key: String::new(),
+            model: default_openai_chat_model(),
+            timeout_secs: default_ai_timeout_secs(),
+            max_prompt_tokens: default_openai_max_prompt_tokens(),
+        }
+    }
+}
+
+impl Default for AnthropicProviderSettings {
+    fn default() -> Self {
+        Self {
+            base_url: default_anthropic_base_url(),
+            api_key: String::new(),
+            model: default_anthropic_model(),
+            max_tokens: default_anthropic_max_tokens(),
+            timeout_secs: default_ai_timeout_secs(),
+            max_prompt_tokens: default_anthropic_max_prompt_tokens(),
+        }
+    }
+}
+
+impl Default for LocalProviderSettings {
+    fn default() -> Self {
+        Self {
+            model_id: default_local_model_id(),
+            max_tokens: default_local_max_tokens(),
+            threads: 0,
+            timeout_secs: default_local_timeout_secs(),
+            max_prompt_tokens: default_local_max_prompt_tokens(),
+        }
+    }
+}
+
+impl Default for AiProviderSettings {
+    fn default() -> Self {
+        Self {
+            provider: default_ai_provider(),
+            proxy: ProxyProviderSettings::default(),
+            openai_compatible: OpenAiChatProviderSettings::default(),
+            anthropic: AnthropicProviderSettings::default(),
+            local: LocalProviderSettings::default(),
+        }
+    }
+}
+
+impl Default for AiChainSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            actions: Vec::new(),
+            latency_budget_ms: default_ai_chain_latency_budget_ms(),
+        }
+    }
+}
+
+impl Default for CommandModeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hotkey: default_command_mode_hotkey(),
+        }
+    }
+}
+
+impl Default for TranslationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_translation_enabled(),
+            target_language: default_translation_target_language(),
+        }
+    }
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
             model_name: default_model_name(),
+            models_dir: None,
+            auto_model_latency_budget_ms: default_auto_model_latency_budget_ms(),
             language: default_language(),
+            allowed_languages: Vec::new(),
             auto_paste: default_auto_paste(),
             pill_enabled: default_pill_enabled(),
             supabase_url: default_supabase_url(),
@@ -224,6 +1061,17 @@ impl Default for AppSettings {
             audio: default_audio_settings(),
             sounds: default_sound_settings(),
             smart_formatting: default_smart_formatting(),
+            audio_retention: default_audio_retention(),
+            translation: default_translation(),
+            whisper_decoding: default_whisper_decoding(),
+            hallucination_filter: default_hallucination_filter(),
+            word_confidence: default_word_confidence(),
+            transcription_backends: TranscriptionBackendSettings::default(),
+            process_timeouts: ProcessTimeoutSettings::default(),
+            ai_provider: AiProviderSettings::default(),
+            ai_chain: AiChainSettings::default(),
+            command_mode: CommandModeSettings::default(),
+            pause_hotkey: default_pause_hotkey(),
         }
     }
 }
@@ -254,7 +1102,40 @@ fn load_settings() -> AppSettings {
         match fs::read_to_string(&config_path) {
             Ok(contents) => {
                 match toml::from_str::<AppSettings>(&contents) {
-                    Ok(settings) => {
+                    Ok(mut settings) => {
+                         // A hand-edited config shouldn't break every transcription
+                         if let Err(e) = settings.whisper_decoding.validate() {
+                             eprintln!("[Config ERROR] Invalid whisper decoding settings ({}). Using defaults.", e);
+                             settings.whisper_decoding = WhisperDecodingSettings::default();
+                         }
+                         if let Err(e) = settings.hallucination_filter.validate() {
+                             eprintln!("[Config ERROR] Invalid hallucination filter settings ({}). Using defaults.", e);
+                             settings.hallucination_filter = HallucinationFilterSettings::default();
+                         }
+                         if let Err(e) = settings.word_confidence.validate() {
+                             eprintln!("[Config ERROR] Invalid word confidence settings ({}). Using defaults.", e);
+                             settings.word_confidence = WordConfidenceSettings::default();
+                         }
+                         if let Err(e) = settings.transcription_backends.validate() {
+                             eprintln!("[Config ERROR] Invalid transcription backend settings ({}). Using the local backend.", e);
+                             settings.transcription_backends.order = default_backend_order();
+                         }
+                         if let Err(e) = settings.process_timeouts.validate() {
+                             eprintln!("[Config ERROR] Invalid process timeouts ({}). Using defaults.", e);
+                             settings.process_timeouts = ProcessTimeoutSettings::default();
+                         }
+                         if let Err(e) = settings.ai_provider.validate() {
+                             eprintln!("[Config ERROR] Invalid AI provider settings ({}). Using defaults.", e);
+                             settings.ai_provider = AiProviderSettings::default();
+                         }
+                         if let Err(e) = settings.ai_chain.validate() {
+                             eprintln!("[Config ERROR] Invalid AI chain settings ({}). Disabling the chain.", e);
+                             settings.ai_chain = AiChainSettings::default();
+                         }
+                         if let Err(e) = settings.command_mode.validate() {
+                             eprintln!("[Config ERROR] Invalid command mode settings ({}). Disabling command mode.", e);
+                             settings.command_mode = CommandModeSettings::default();
+                         }
                          println!("[Config] Settings loaded successfully: model='{}', lang='{}', paste={}, pill={}", 
                                   settings.model_name, settings.language, settings.auto_paste, settings.pill_enabled);
                          return settings;
@@ -318,6 +1199,22 @@ impl AppSettings {
         get_config_path().ok_or_else(|| "Could not determine config path".to_string())
     }
     
+    /// Check user-editable values before they are stored
+    pub fn validate(&self) -> Result<(), String> {
+        self.whisper_decoding.validate()?;
+        self.hallucination_filter.validate()?;
+        self.word_confidence.validate()?;
+        self.transcription_backends.validate()?;
+        self.process_timeouts.validate()?;
+        self.ai_provider.validate()?;
+        self.ai_chain.validate()?;
+        self.command_mode.validate()?;
+        if self.pause_hotkey.trim().is_empty() {
+            return Err("Pausing a recording needs a hotkey".to_string());
+        }
+        Ok(())
+    }
+
     pub fn save(&self) -> Result<(), String> {
         let config_path = Self::config_path()?;
         if let Some(dir) = config_path.parent() {
@@ -328,4 +1225,108 @@ impl AppSettings {
         fs::write(&config_path, config_content).map_err(|e| format!("Failed to write config: {}", e))?;
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_decoding_settings_are_valid() {
+        assert!(WhisperDecodingSettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_out_of_range_decoding_settings() {
+        let base = WhisperDecodingSettings::default();
+        let cases = vec![
+            WhisperDecodingSettings { beam_size: 0, ..base.clone() },
+            WhisperDecodingSettings { best_of: MAX_WHISPER_DECODERS + 1, ..base.clone() },
+            WhisperDecodingSettings { temperature: 1.5, ..base.clone() },
+            WhisperDecodingSettings { temperature_increment: -0.1, ..base.clone() },
+            WhisperDecodingSettings { logprob_threshold: 0.5, ..base.clone() },
+            WhisperDecodingSettings { no_speech_threshold: f32::NAN, ..base.clone() },
+            WhisperDecodingSettings { threads: MAX_WHISPER_THREADS + 1, ..base.clone() },
+        ];
+        for settings in cases {
+            assert!(settings.validate().is_err(), "{:?} should be rejected", settings);
+        }
+    }
+
+    #[test]
+    fn missing_decoding_fields_use_defaults() {
+        let parsed: WhisperDecodingSettings = toml::from_str("beam_size = 2").unwrap();
+        assert_eq!(parsed.beam_size, 2);
+        assert_eq!(parsed.best_of, default_best_of());
+        assert_eq!(parsed.no_speech_threshold, default_no_speech_threshold());
+    }
+
+    #[test]
+    fn validates_process_timeouts() {
+        assert!(ProcessTimeoutSettings::default().validate().is_ok());
+        assert!(ProcessTimeoutSettings { whisper_timeout_secs: 0, ..Default::default() }.validate().is_err());
+        assert!(ProcessTimeoutSettings { ffmpeg_timeout_secs: 601, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn validates_backend_settings() {
+        assert!(TranscriptionBackendSettings::default().validate().is_ok());
+
+        let mut settings = TranscriptionBackendSettings { order: vec![], ..Default::default() };
+        assert!(settings.validate().is_err());
+
+        settings.order = vec![TranscriptionBackendKind::LocalCli, TranscriptionBackendKind::LocalCli];
+        assert!(settings.validate().is_err());
+
+        // Settings of backends that aren't in the order don't matter
+        settings.order = vec![TranscriptionBackendKind::LocalCli];
+        settings.whisper_server.base_url = "localhost:8080".to_string();
+        assert!(settings.validate().is_ok());
+        settings.order.push(TranscriptionBackendKind::WhisperServer);
+        assert!(settings.validate().is_err());
+
+        let parsed: TranscriptionBackendSettings = toml::from_str(r#"order = ["openai_compatible", "local_cli"]"#).unwrap();
+        assert_eq!(parsed.order, vec![TranscriptionBackendKind::OpenAiCompatible, TranscriptionBackendKind::LocalCli]);
+        assert_eq!(parsed.openai_compatible.model, "whisper-1");
+    }
+
+    #[test]
+    fn validates_only_the_selected_ai_provider() {
+        assert!(AiProviderSettings::default().validate().is_ok());
+
+        let mut settings = AiProviderSettings::default();
+        settings.anthropic.model = String::new();
+        assert!(settings.validate().is_ok());
+        settings.provider = AiProviderKind::Anthropic;
+        assert!(settings.validate().is_err());
+
+        let parsed: AiProviderSettings = toml::from_str(r#"
+            provider = "openai_compatible"
+            [openai_compatible]
+            base_url = "http://localhost:11434/v1"
+            model = "llama3.2"
+        "#).unwrap();
+        assert!(parsed.validate().is_ok());
+        assert_eq!(parsed.openai_compatible.timeout_secs, 60);
+        assert_eq!(parsed.openai_compatible.max_prompt_tokens, 16_000);
+        assert!(AiProviderSettings { openai_compatible: OpenAiChatProviderSettings { max_prompt_tokens: 50, ..parsed.openai_compatible.clone() }, ..parsed.clone() }.validate().is_err());
+        assert!(AiProviderSettings { openai_compatible: OpenAiChatProviderSettings { timeout_secs: 0, ..parsed.openai_compatible.clone() }, ..parsed }.validate().is_err());
+
+        let local: AiProviderSettings = toml::from_str(r#"provider = "local""#).unwrap();
+        assert!(local.validate().is_ok());
+        assert_eq!(local.local.timeout_secs, 180);
+        assert!(AiProviderSettings { local: LocalProviderSettings { max_tokens: 0, ..local.local.clone() }, ..local }.validate().is_err());
+    }
+
+    #[test]
+    fn validates_ai_chain() {
+        assert!(AiChainSettings::default().validate().is_ok());
+        assert!(AiChainSettings { enabled: true, ..Default::default() }.validate().is_err());
+
+        let chain = AiChainSettings { enabled: true, actions: vec!["written_form".to_string(), "translate".to_string()], ..Default::default() };
+        assert!(chain.validate().is_ok());
+        assert!(AiChainSettings { latency_budget_ms: 100, ..chain.clone() }.validate().is_err());
+        assert!(AiChainSettings { actions: vec!["written_form".to_string(); MAX_AI_CHAIN_STEPS + 1], ..chain.clone() }.validate().is_err());
+        assert!(AiChainSettings { actions: vec![" ".to_string()], ..chain }.validate().is_err());
+    }
+}
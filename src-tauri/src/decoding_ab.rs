@@ -0,0 +1,342 @@
+// src-tauri/src/decoding_ab.rs
+//
+// A/B harness for whisper decoding parameters: transcribe one audio file with two
+// parameter sets, interleaving the runs so disk/page cache favours neither, and report
+// both texts, a word-level diff and the timings. Used to tune accuracy against latency.
+//
+// Runs whisper directly (no dictionary prompt, correction or formatting) so the diff
+// shows only what the decoding parameters changed. The test is a transcription queue job,
+// so it waits for dictation instead of competing with it, and cancelling it kills whisper.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use log::info;
+use serde::Serialize;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::config::{WhisperDecodingSettings, SETTINGS};
+use crate::process_runner;
+use crate::transcription;
+use crate::transcription_queue::{self, Job, JobControl};
+
+const MAX_REPEATS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Removed, // Only in A
+    Added,   // Only in B
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodingRun {
+    pub params: WhisperDecodingSettings,
+    pub text: String,
+    pub timings_ms: Vec<u64>,
+    pub mean_ms: u64,
+    pub real_time_factor: f64, // mean time / audio length; below 1 is faster than real time
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodingAbReport {
+    pub model_name: String,
+    pub audio_seconds: f64,
+    pub a: DecodingRun,
+    pub b: DecodingRun,
+    pub diff: Vec<DiffSegment>,
+    pub changed_words: usize,
+}
+
+/// Word-level diff of `a` against `b` (LCS), with adjacent words of the same kind merged
+pub fn diff_words(a: &str, b: &str) -> Vec<DiffSegment> {
+    let a_words: Vec<&str> = a.split_whitespace().collect();
+    let b_words: Vec<&str> = b.split_whitespace().collect();
+    let (n, m) = (a_words.len(), b_words.len());
+
+    // lcs[i][j] = LCS length of a_words[i..] and b_words[j..]
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a_words[i] == b_words[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    let mut push = |op: DiffOp, word: &str| {
+        match segments.last_mut() {
+            Some(last) if last.op == op => {
+                last.text.push(' ');
+                last.text.push_str(word);
+            }
+            _ => segments.push(DiffSegment { op, text: word.to_string() }),
+        }
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a_words[i] == b_words[j] {
+            push(DiffOp::Equal, a_words[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push(DiffOp::Removed, a_words[i]);
+            i += 1;
+        } else {
+            push(DiffOp::Added, b_words[j]);
+            j += 1;
+        }
+    }
+    for word in &a_words[i..] {
+        push(DiffOp::Removed, word);
+    }
+    for word in &b_words[j..] {
+        push(DiffOp::Added, word);
+    }
+    segments
+}
+
+/// Words that differ between the two sides
+pub fn changed_word_count(diff: &[DiffSegment]) -> usize {
+    diff.iter()
+        .filter(|segment| segment.op != DiffOp::Equal)
+        .map(|segment| segment.text.split_whitespace().count())
+        .sum()
+}
+
+fn normalize_whisper_text(stdout: &str) -> String {
+    stdout.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+struct WhisperSetup {
+    binary: PathBuf,
+    working_dir: PathBuf,
+    model_path: PathBuf,
+    language_arg: String,
+    timeout: Duration,
+    supported_flags: HashSet<String>,
+    control: Arc<JobControl>,
+}
+
+async fn run_once(setup: &WhisperSetup, params: &WhisperDecodingSettings, input: &Path) -> Result<(String, u64), String> {
+    let mut command = transcription::base_whisper_command(&setup.binary, &setup.working_dir, &setup.model_path, &setup.language_arg);
+    command.args(transcription::whisper_decoding_args(params, &setup.supported_flags)).arg(input);
+
+    let started = Instant::now();
+    let output = process_runner::run(command, setup.timeout, Some(setup.control.clone())).await.map_err(|e| format!("Whisper {}", e))?;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    Ok((normalize_whisper_text(&String::from_utf8_lossy(&output.stdout)), elapsed_ms))
+}
+
+fn finish_run(params: WhisperDecodingSettings, text: String, timings_ms: Vec<u64>, audio_seconds: f64) -> DecodingRun {
+    let mean_ms = timings_ms.iter().sum::<u64>() / timings_ms.len().max(1) as u64;
+    let real_time_factor = if audio_seconds > 0.0 { mean_ms as f64 / 1000.0 / audio_seconds } else { 0.0 };
+    DecodingRun { params, text, timings_ms, mean_ms, real_time_factor }
+}
+
+/// Transcribe `audio_path` with both parameter sets and compare.
+/// `model_name` defaults to the configured model; `repeats` (1-10) averages the timings.
+#[tauri::command]
+pub async fn run_decoding_ab_test(
+    app_handle: AppHandle,
+    audio_path: String,
+    params_a: WhisperDecodingSettings,
+    params_b: WhisperDecodingSettings,
+    model_name: Option<String>,
+    repeats: Option<u32>,
+) -> Result<DecodingAbReport, String> {
+    params_a.validate().map_err(|e| format!("Parameter set A: {}", e))?;
+    params_b.validate().map_err(|e| format!("Parameter set B: {}", e))?;
+    let repeats = repeats.unwrap_or(1).clamp(1, MAX_REPEATS);
+
+    let source = PathBuf::from(&audio_path);
+    if !source.exists() {
+        return Err(format!("Audio file not found: {}", audio_path));
+    }
+
+    // Queued with live dictation; whisper runs one job at a time
+    let mut job = transcription_queue::submit(&app_handle)?;
+    let result = ab_test_job(&mut job, &app_handle, &audio_path, &source, params_a, params_b, model_name, repeats).await;
+    job.finish(&result);
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn ab_test_job(
+    job: &mut Job,
+    app_handle: &AppHandle,
+    audio_path: &str,
+    source: &Path,
+    params_a: WhisperDecodingSettings,
+    params_b: WhisperDecodingSettings,
+    model_name: Option<String>,
+    repeats: u32,
+) -> Result<DecodingAbReport, String> {
+    job.wait_turn().await?;
+
+    // Same 16 kHz mono conversion dictation uses; the source file is left alone
+    let converted = crate::recordings::recordings_dir_or_temp(app_handle)
+        .join(format!("{}{}.wav", crate::recordings::CONVERTED_PREFIX, Uuid::new_v4()));
+    transcription::run_ffmpeg_conversion(source, &converted, app_handle, Some(job.control())).await.map_err(|e| e.to_string())?;
+    let converted_for_cleanup = converted.clone();
+    scopeguard::defer!({
+        let _ = std::fs::remove_file(&converted_for_cleanup);
+    });
+    let audio_seconds = crate::model_selector::wav_duration_seconds(&converted).unwrap_or(0.0);
+
+    let (configured_model, language_setting, allowed_languages, timeout) = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        (settings_guard.model_name.clone(), settings_guard.language.clone(), settings_guard.allowed_languages.clone(),
+         settings_guard.process_timeouts.whisper_timeout())
+    };
+    let model_name = match model_name.unwrap_or(configured_model) {
+        name if name == crate::model_selector::AUTO_MODEL => crate::model_selector::select_model_for(app_handle, audio_seconds),
+        name => name,
+    };
+    let model_path = transcription::resolve_model_path(app_handle, &model_name);
+    if !model_path.exists() {
+        return Err(format!("Whisper model '{}' is not installed", model_name));
+    }
+    let (binary, working_dir) = transcription::resolve_whisper_binary()?;
+    let language_mode = crate::language::resolve_language_mode(&language_setting, &allowed_languages);
+    let supported_flags = transcription::whisper_supported_flags_async(&binary).await;
+    let setup = WhisperSetup {
+        binary,
+        working_dir,
+        model_path,
+        language_arg: language_mode.whisper_arg().to_string(),
+        timeout,
+        supported_flags,
+        control: job.control(),
+    };
+
+    info!("[DecodingAB] {} x{} on {:.1}s of audio with model {}", audio_path, repeats, audio_seconds, model_name);
+
+    let (mut text_a, mut text_b) = (None, None);
+    let (mut timings_a, mut timings_b) = (Vec::new(), Vec::new());
+    for round in 0..repeats {
+        // Alternate which side goes first so warm caches favour neither
+        let a_first = round % 2 == 0;
+        for side_a in [a_first, !a_first] {
+            let params = if side_a { &params_a } else { &params_b };
+            let (text, elapsed_ms) = run_once(&setup, params, &converted).await?;
+            if side_a {
+                timings_a.push(elapsed_ms);
+                text_a.get_or_insert(text);
+            } else {
+                timings_b.push(elapsed_ms);
+                text_b.get_or_insert(text);
+            }
+        }
+    }
+
+    let text_a = text_a.unwrap_or_default();
+    let text_b = text_b.unwrap_or_default();
+    let diff = diff_words(&text_a, &text_b);
+    let changed_words = changed_word_count(&diff);
+    let a = finish_run(params_a, text_a, timings_a, audio_seconds);
+    let b = finish_run(params_b, text_b, timings_b, audio_seconds);
+    info!("[DecodingAB] A: {} ms mean, B: {} ms mean, {} words differ", a.mean_ms, b.mean_ms, changed_words);
+
+    Ok(DecodingAbReport { model_name, audio_seconds, a, b, diff, changed_words })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(op: DiffOp, text: &str) -> DiffSegment {
+        DiffSegment { op, text: text.to_string() }
+    }
+
+    #[test]
+    fn identical_texts_are_one_equal_segment() {
+        let diff = diff_words("the quick brown fox", "the  quick brown fox");
+        assert_eq!(diff, vec![seg(DiffOp::Equal, "the quick brown fox")]);
+        assert_eq!(changed_word_count(&diff), 0);
+    }
+
+    #[test]
+    fn substitution_shows_removed_then_added() {
+        let diff = diff_words("send it to super base today", "send it to Supabase today");
+        assert_eq!(diff, vec![
+            seg(DiffOp::Equal, "send it to"),
+            seg(DiffOp::Removed, "super base"),
+            seg(DiffOp::Added, "Supabase"),
+            seg(DiffOp::Equal, "today"),
+        ]);
+        assert_eq!(changed_word_count(&diff), 3);
+    }
+
+    #[test]
+    fn handles_empty_sides() {
+        assert_eq!(diff_words("", "hello there"), vec![seg(DiffOp::Added, "hello there")]);
+        assert_eq!(diff_words("hello", ""), vec![seg(DiffOp::Removed, "hello")]);
+        assert!(diff_words("", "").is_empty());
+    }
+
+    // Trimmed from whisper.cpp's --help
+    const HELP: &str = "usage: whisper [options] file0.wav file1.wav ...
+
+options:
+  -h,        --help              [default] show this help message and exit
+  -t N,      --threads N         [4      ] number of threads to use during computation
+  -bo N,     --best-of N         [5      ] number of best candidates to keep
+  -bs N,     --beam-size N       [5      ] beam size for beam search
+  -et N,     --entropy-thold N   [2.40   ] entropy threshold for decoder fail
+  -lpt N,    --logprob-thold N   [-1.00  ] log probability threshold for decoder fail
+  -tp,       --temperature N     [0.00   ] The sampling temperature, between 0 and 1
+  -tpi,      --temperature-inc N [0.20   ] The increment of temperature, between 0 and 1
+  -nf,       --no-fallback       [false  ] do not use temperature fallback while decoding
+  -nth N,    --no-speech-thold N [0.60   ] no speech threshold
+";
+
+    #[test]
+    fn parses_flags_from_help() {
+        let flags = transcription::parse_help_flags(HELP);
+        for flag in ["-h", "--help", "-t", "-bs", "--beam-size", "-lpt", "-tpi", "-nf", "-nth"] {
+            assert!(flags.contains(flag), "{} missing", flag);
+        }
+        assert!(!flags.contains("-1.00"));
+        assert!(!flags.contains("N"));
+    }
+
+    #[test]
+    fn decoding_args_leave_out_unsupported_flags() {
+        let tuned = WhisperDecodingSettings { no_speech_threshold: 0.4, ..Default::default() };
+        let old_help: String = HELP.lines().filter(|line| !line.contains("-nth")).collect::<Vec<_>>().join("\n");
+        let args = transcription::whisper_decoding_args(&tuned, &transcription::parse_help_flags(&old_help));
+        assert!(args.contains(&"-bs".to_string()));
+        assert!(!args.contains(&"-nth".to_string()));
+        assert!(transcription::whisper_decoding_args(&tuned, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn decoding_args_skip_defaulted_optional_flags() {
+        let supported = transcription::parse_help_flags(HELP);
+        let args = transcription::whisper_decoding_args(&WhisperDecodingSettings::default(), &supported);
+        assert!(args.contains(&"-bs".to_string()));
+        assert!(!args.contains(&"-t".to_string()));
+        assert!(!args.contains(&"-nth".to_string()));
+        assert!(!args.contains(&"-nf".to_string()));
+
+        let tuned = WhisperDecodingSettings { threads: 4, no_fallback: true, no_speech_threshold: 0.4, ..Default::default() };
+        let args = transcription::whisper_decoding_args(&tuned, &supported);
+        assert!(args.windows(2).any(|w| w[0] == "-t" && w[1] == "4"));
+        assert!(args.windows(2).any(|w| w[0] == "-nth" && w[1] == "0.4"));
+        assert!(args.contains(&"-nf".to_string()));
+    }
+}
@@ -10,16 +10,25 @@ use std::collections::HashMap;
 use std::time::Instant;
 use crate::common_words;
 use crate::whisper_variations;
+use crate::word_confidence::ConfidenceGate;
 
 /// Simple dictionary corrector with exact matching only
 pub struct DictionaryCorrector {
     /// Case-insensitive lookup map: lowercase_word -> original_cased_word
     word_map: HashMap<String, String>,
+    /// Apply the English-specific Whisper variation mappings ("sequel" -> "SQL", ...)
+    english: bool,
 }
 
 impl DictionaryCorrector {
     /// Create a new dictionary corrector from a list of dictionary words
     pub fn new(dictionary_words: &[String]) -> Self {
+        Self::for_language(dictionary_words, "en")
+    }
+
+    /// Create a corrector for text in `language` (ISO code).
+    /// Non-English text only gets exact dictionary matches.
+    pub fn for_language(dictionary_words: &[String], language: &str) -> Self {
         let mut word_map = HashMap::new();
         
         // Build case-insensitive lookup map
@@ -32,12 +41,17 @@ impl DictionaryCorrector {
             }
         }
         
-        Self { word_map }
+        Self { word_map, english: is_english(language) }
     }
     
     /// Correct text using simple exact matching with context awareness
     /// Returns the corrected text with preserved spacing and punctuation
     pub fn correct_text(&self, text: &str) -> String {
+        self.correct_text_gated(text, None)
+    }
+
+    /// Like `correct_text`, but words whisper was confident about are left alone
+    pub fn correct_text_gated(&self, text: &str, gate: Option<&ConfidenceGate>) -> String {
         if self.word_map.is_empty() || text.trim().is_empty() {
             return text.to_string();
         }
@@ -66,6 +80,7 @@ impl DictionaryCorrector {
         }
         
         // Second pass: correct words with context
+        let mut aligner = gate.map(|g| g.aligner());
         let mut result = String::with_capacity(text.len());
         for i in 0..tokens.len() {
             let (token, is_word) = &tokens[i];
@@ -76,6 +91,15 @@ impl DictionaryCorrector {
                 let next_word = self.find_next_word(&tokens, i);
                 
                 let corrected = self.correct_word_with_context(token, prev_word, next_word);
+                if let (Some(gate), Some(aligner)) = (gate, aligner.as_mut()) {
+                    let confidence = aligner.next(token);
+                    if corrected != *token && !gate.allows(confidence) {
+                        println!("[DictionaryCorrector] Kept high-confidence '{}' (p = {:.2}) instead of '{}'",
+                                 token, confidence.unwrap_or_default(), corrected);
+                        result.push_str(token);
+                        continue;
+                    }
+                }
                 result.push_str(&corrected);
             } else {
                 result.push_str(token);
@@ -115,6 +139,9 @@ impl DictionaryCorrector {
     /// Correct a single word using exact matching with context awareness
     fn correct_word_with_context(&self, word: &str, prev_word: Option<&str>, next_word: Option<&str>) -> String {
         // First try context-aware Whisper variations
+        if !self.english {
+            return self.correct_word(word);
+        }
         if let Some(corrected) = whisper_variations::get_correct_form_with_context(word, prev_word, next_word) {
             println!("[DictionaryCorrector] Applied context-aware Whisper correction: '{}' -> '{}'", word, corrected);
             return corrected;
@@ -157,7 +184,7 @@ impl DictionaryCorrector {
         
         // Check for known Whisper variations
         // Only check if it's safe to do so (not a common word, long enough, etc.)
-        if whisper_variations::should_check_variations(word, false) {
+        if self.english && whisper_variations::should_check_variations(word, false) {
             if let Some(correct_form) = whisper_variations::get_correct_form(word) {
                 let correct_lowercase = correct_form.to_lowercase();
                 if let Some(dictionary_word) = self.word_map.get(&correct_lowercase) {
@@ -341,18 +368,66 @@ pub struct DictionaryStats {
     pub average_word_length: f32,
 }
 
+fn is_english(language: &str) -> bool {
+    language.is_empty() || language.starts_with("en")
+}
+
 /// Public interface function for integration with existing transcription pipeline
 pub fn correct_text_with_dictionary(text: &str, dictionary_words: &[String]) -> String {
+    correct_text_with_dictionary_for_language(text, dictionary_words, "en")
+}
+
+/// Dictionary correction for text in `language`. The noise normalization
+/// (rn -> m, cl -> d, digit/letter swaps) and variation mappings are tuned on
+/// English and would rewrite valid words elsewhere, so they only run for English.
+pub fn correct_text_with_dictionary_for_language(text: &str, dictionary_words: &[String], language: &str) -> String {
+    correct_text_with_confidence(text, dictionary_words, language, None)
+}
+
+/// Dictionary correction that only rewrites words the gate allows (low or unknown confidence)
+pub fn correct_text_with_confidence(text: &str, dictionary_words: &[String], language: &str, gate: Option<&ConfidenceGate>) -> String {
     if dictionary_words.is_empty() {
         return text.to_string();
     }
     
     // Layer 1: Character normalization (preprocessing)
-    let normalized_text = normalize_transcription_noise(text);
+    let normalized_text = match gate {
+        _ if !is_english(language) => text.to_string(),
+        None => normalize_transcription_noise(text),
+        Some(gate) => normalize_low_confidence_words(text, gate),
+    };
     
     // Layer 2: Dictionary correction with exact matching only
-    let corrector = DictionaryCorrector::new(dictionary_words);
-    corrector.correct_text(&normalized_text)
+    let corrector = DictionaryCorrector::for_language(dictionary_words, language);
+    corrector.correct_text_gated(&normalized_text, gate)
+}
+
+/// Noise normalization word by word, skipping words whisper was confident about
+/// ("modern" must not become "modem")
+fn normalize_low_confidence_words(text: &str, gate: &ConfidenceGate) -> String {
+    let mut aligner = gate.aligner();
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    let mut flush = |word: &mut String, result: &mut String| {
+        if !word.is_empty() {
+            if gate.allows(aligner.next(word)) {
+                result.push_str(&normalize_transcription_noise(word));
+            } else {
+                result.push_str(word);
+            }
+            word.clear();
+        }
+    };
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            flush(&mut word, &mut result);
+            result.push(ch);
+        } else {
+            word.push(ch);
+        }
+    }
+    flush(&mut word, &mut result);
+    result
 }
 
 
@@ -695,6 +770,31 @@ mod tests {
                    "stop dicking around"); // Should NOT correct in this context
     }
     
+    #[test]
+    fn test_non_english_skips_english_heuristics() {
+        let dictionary = vec!["button".to_string(), "Supabase".to_string()];
+        
+        // "dick on" -> "click on" and rn -> m are English-only
+        assert_eq!(correct_text_with_dictionary_for_language("bitte dick on", &dictionary, "de"), "bitte dick on");
+        assert_eq!(correct_text_with_dictionary_for_language("gern", &dictionary, "de"), "gern");
+        
+        // Exact dictionary matches still apply
+        assert_eq!(correct_text_with_dictionary_for_language("mit supabase", &dictionary, "de"), "mit Supabase");
+    }
+    
+    #[test]
+    fn test_confidence_gate_protects_confident_words() {
+        use crate::word_confidence::WordConfidence;
+        let dictionary = vec!["Supabase".to_string(), "modem".to_string()];
+        let word = |word: &str, confidence: f32| WordConfidence { word: word.to_string(), confidence, start_ms: 0, end_ms: 0 };
+        let words = vec![word("a", 0.99), word("modern", 0.97), word("supabase", 0.30), word("supabase", 0.95)];
+        let gate = ConfidenceGate { words: &words, threshold: 0.5 };
+        
+        // Without the gate "modern" is normalized to "modem" and both supabases are corrected
+        assert_eq!(correct_text_with_dictionary_for_language("a modern supabase supabase", &dictionary, "en"), "a modem Supabase Supabase");
+        assert_eq!(correct_text_with_confidence("a modern supabase supabase", &dictionary, "en", Some(&gate)), "a modern Supabase supabase");
+    }
+    
     #[test]
     fn test_conservative_corrections() {
         // Test the specific errors from user's testing
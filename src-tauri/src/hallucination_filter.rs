@@ -0,0 +1,554 @@
+// src-tauri/src/hallucination_filter.rs
+//
+// Detection of whisper hallucinations in raw whisper output, before dictionary
+// correction and formatting. Short or silent clips make whisper emit text it learned
+// from subtitles ("Thank you for watching.", caption credits) or get stuck repeating
+// a phrase. Four checks:
+//   - known phrases: subtitle credits and outro lines no one dictates
+//   - repetition: the same n-gram several times in a row, and duplicate segments
+//   - compression ratio: whisper's own loop heuristic (zlib ratio above ~2.4)
+//   - speech cross-check: text on a clip where the energy VAD found (almost) no speech
+// Suspect segments are dropped or only flagged depending on settings, and every hit
+// is appended to a review log in the app data dir.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::config::{HallucinationFilterMode, HallucinationFilterSettings};
+
+const REVIEW_LOG_FILENAME: &str = "hallucination_review.jsonl";
+const REVIEW_LOG_MAX_ENTRIES: usize = 1000;
+
+const MAX_LOOP_NGRAM: usize = 8;
+// Speaking faster than this per second of detected speech means the text didn't come from the audio
+const MAX_WORDS_PER_SPEECH_SECOND: f32 = 6.0;
+// Weak phrases ("Thank you.") are only suspect as the whole output of a near-silent clip
+const WEAK_PHRASE_MAX_SPEECH_SECONDS: f32 = 1.0;
+
+const VAD_FRAME_SECONDS: f32 = 0.03;
+const VAD_MIN_SPEECH_DBFS: f32 = -50.0;
+const VAD_ABOVE_NOISE_DB: f32 = 10.0;
+
+// Never plausible in dictation: exact (normalized) sentence matches
+const KNOWN_PHRASES: &[&str] = &[
+    "thank you for watching",
+    "thanks for watching",
+    "thank you so much for watching",
+    "thank you for watching and see you next time",
+    "thanks for watching and see you next time",
+    "please subscribe",
+    "please like and subscribe",
+    "like and subscribe",
+    "don t forget to like and subscribe",
+    "please subscribe to my channel",
+    "subscribe to my channel",
+    "see you in the next video",
+    "see you next time",
+    "продолжение следует",
+    "vielen dank fürs zuschauen",
+    "merci d avoir regardé",
+    "gracias por ver",
+];
+
+// Caption credits: suspect wherever they appear in a sentence
+const KNOWN_MARKERS: &[&str] = &[
+    "amara org",
+    "untertitel im auftrag",
+    "untertitel der amara",
+    "sous titres réalisés",
+    "napisy stworzone przez",
+    "субтитры сделал",
+    "субтитры создавал",
+];
+
+// Credit lines: suspect when a sentence starts with them ("The subtitles by default..." is fine)
+const CREDIT_PREFIXES: &[&str] = &[
+    "subtitles by",
+    "subtitled by",
+    "captions by",
+    "captioning by",
+    "transcribed by",
+    "transcription by",
+];
+
+// Plausible dictation, but also what whisper says about silence
+const WEAK_PHRASES: &[&str] = &["thank you", "thanks", "thank you very much", "you", "bye", "bye bye", "okay", "so"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuspectReason {
+    KnownPhrase,
+    Annotation, // "[Music]", "(applause)", "♪"
+    Repetition,
+    CompressionRatio,
+    NoSpeech,
+    SpeechRateMismatch,
+}
+
+/// A piece of whisper output the filter considered suspect
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuspectSegment {
+    pub text: String,
+    pub reason: SuspectReason,
+    pub dropped: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterOutcome {
+    pub text: String,
+    pub suspects: Vec<SuspectSegment>,
+}
+
+/// Lowercase, drop punctuation, collapse whitespace
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c.to_lowercase().next().unwrap_or(c) } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_annotation(sentence: &str) -> bool {
+    let trimmed = sentence.trim();
+    if trimmed.is_empty() {
+        return false;
+    }
+    let bracketed = (trimmed.starts_with('[') && trimmed.ends_with(']'))
+        || (trimmed.starts_with('(') && trimmed.ends_with(')'))
+        || (trimmed.starts_with('*') && trimmed.ends_with('*') && trimmed.len() > 1);
+    bracketed || trimmed.chars().all(|c| c == '♪' || c == '♫' || c.is_whitespace())
+}
+
+fn is_known_phrase(normalized: &str) -> bool {
+    KNOWN_PHRASES.contains(&normalized)
+        || KNOWN_MARKERS.iter().any(|marker| normalized.contains(marker))
+        || CREDIT_PREFIXES.iter().any(|prefix| normalized.starts_with(prefix))
+}
+
+fn is_weak_phrase(normalized: &str) -> bool {
+    WEAK_PHRASES.contains(&normalized)
+}
+
+/// Split a line into sentences, keeping the terminating punctuation ("Amara.org" stays whole)
+fn split_sentences(line: &str) -> Vec<String> {
+    let mut sentences: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let at_boundary = !matches!(chars.peek(), Some(next) if !next.is_whitespace());
+        if matches!(c, '.' | '!' | '?' | '。' | '！' | '？') && (at_boundary || !c.is_ascii()) {
+            let is_bare_punctuation = !current.chars().any(char::is_alphanumeric);
+            match sentences.last_mut() {
+                // "Wait..." - trailing dots belong to the sentence before
+                Some(last) if is_bare_punctuation => last.push_str(current.trim()),
+                // Leading "..." stays with the sentence it introduces
+                None if is_bare_punctuation => continue,
+                _ if !current.trim().is_empty() => sentences.push(current.trim().to_string()),
+                _ => {}
+            }
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        sentences.push(current.trim().to_string());
+    }
+    sentences
+}
+
+fn word_key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+fn min_repeats(ngram: usize) -> usize {
+    // "no no no" is real speech; a single word four times in a row rarely is
+    if ngram == 1 { 4 } else { 3 }
+}
+
+/// Collapse runs of a repeated n-gram to one occurrence. Returns the kept words and the removed runs.
+pub fn collapse_loops<'a>(words: &[&'a str]) -> (Vec<&'a str>, Vec<String>) {
+    let keys: Vec<String> = words.iter().map(|w| word_key(w)).collect();
+    let mut kept = Vec::with_capacity(words.len());
+    let mut removed = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let mut collapsed = false;
+        for n in 1..=MAX_LOOP_NGRAM {
+            if i + n * min_repeats(n) > words.len() || keys[i..i + n].iter().any(|k| k.is_empty()) {
+                continue;
+            }
+            let mut repeats = 1;
+            while i + (repeats + 1) * n <= words.len() && keys[i + repeats * n..i + (repeats + 1) * n] == keys[i..i + n] {
+                repeats += 1;
+            }
+            if repeats >= min_repeats(n) {
+                kept.extend_from_slice(&words[i..i + n]);
+                removed.push(words[i + n..i + repeats * n].join(" "));
+                i += repeats * n;
+                collapsed = true;
+                break;
+            }
+        }
+        if !collapsed {
+            kept.push(words[i]);
+            i += 1;
+        }
+    }
+    (kept, removed)
+}
+
+/// zlib compression ratio, the measure whisper uses to spot looping decodes
+pub fn compression_ratio(text: &str) -> f32 {
+    if text.is_empty() {
+        return 0.0;
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    if encoder.write_all(text.as_bytes()).is_err() {
+        return 0.0;
+    }
+    match encoder.finish() {
+        Ok(compressed) if !compressed.is_empty() => text.len() as f32 / compressed.len() as f32,
+        _ => 0.0,
+    }
+}
+
+/// Seconds of speech in 16-bit mono audio, by frame energy against the clip's own noise floor
+pub fn speech_seconds(samples: &[i16], sample_rate: u32) -> f32 {
+    let frame_len = ((sample_rate as f32 * VAD_FRAME_SECONDS) as usize).max(1);
+    let frame_dbfs: Vec<f32> = samples.chunks(frame_len)
+        .filter(|frame| frame.len() == frame_len)
+        .map(|frame| {
+            let mean_square = frame.iter().map(|&s| (s as f64 / 32768.0).powi(2)).sum::<f64>() / frame.len() as f64;
+            (10.0 * mean_square.max(1e-12).log10()) as f32
+        })
+        .collect();
+    if frame_dbfs.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = frame_dbfs.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let noise_floor = sorted[sorted.len() / 10]; // 10th percentile
+    let threshold = (noise_floor + VAD_ABOVE_NOISE_DB).max(VAD_MIN_SPEECH_DBFS);
+
+    let speech_frames = frame_dbfs.iter().filter(|&&db| db > threshold).count();
+    speech_frames as f32 * frame_len as f32 / sample_rate as f32
+}
+
+/// Speech seconds in a WAV file; None if it can't be read as 16-bit PCM
+pub fn wav_speech_seconds(path: &Path) -> Option<f32> {
+    let mut reader = hound::WavReader::open(path).ok()?;
+    let spec = reader.spec();
+    if spec.bits_per_sample != 16 || spec.sample_format != hound::SampleFormat::Int {
+        return None;
+    }
+    let channels = spec.channels.max(1) as usize;
+    let samples: Vec<i16> = reader.samples::<i16>()
+        .filter_map(Result::ok)
+        .step_by(channels) // First channel is enough for a speech estimate
+        .collect();
+    Some(speech_seconds(&samples, spec.sample_rate))
+}
+
+/// Run all checks over raw whisper stdout (one segment per line).
+/// `speech_seconds` is the VAD estimate for the audio, if known.
+pub fn filter_output(raw: &str, speech_seconds: Option<f32>, settings: &HallucinationFilterSettings) -> FilterOutcome {
+    if settings.mode == HallucinationFilterMode::Off {
+        return FilterOutcome { text: raw.to_string(), suspects: Vec::new() };
+    }
+    let drop = settings.mode == HallucinationFilterMode::Drop;
+    let mut suspects = Vec::new();
+    let flag = |text: &str, reason: SuspectReason, suspects: &mut Vec<SuspectSegment>| {
+        suspects.push(SuspectSegment { text: text.trim().to_string(), reason, dropped: drop });
+    };
+
+    let segments: Vec<&str> = raw.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let all_text = segments.join(" ");
+    if all_text.trim().is_empty() {
+        return FilterOutcome { text: String::new(), suspects };
+    }
+
+    // No speech at all: everything whisper said is invented
+    if let Some(speech) = speech_seconds {
+        if speech < settings.min_speech_seconds {
+            flag(&all_text, SuspectReason::NoSpeech, &mut suspects);
+            let text = if drop { String::new() } else { raw.to_string() };
+            return FilterOutcome { text, suspects };
+        }
+    }
+
+    // A lone weak phrase on a near-silent clip
+    let whole = normalize(&all_text);
+    if is_weak_phrase(&whole) && speech_seconds.is_some_and(|s| s < WEAK_PHRASE_MAX_SPEECH_SECONDS) {
+        flag(&all_text, SuspectReason::KnownPhrase, &mut suspects);
+        let text = if drop { String::new() } else { raw.to_string() };
+        return FilterOutcome { text, suspects };
+    }
+
+    let mut kept_lines: Vec<String> = Vec::new();
+    let mut previous_segment: Option<String> = None;
+    for segment in segments {
+        // Whisper stuck on a segment prints it again and again
+        let segment_key = normalize(segment);
+        if previous_segment.as_deref() == Some(segment_key.as_str()) && !segment_key.is_empty() {
+            flag(segment, SuspectReason::Repetition, &mut suspects);
+            if drop {
+                continue;
+            }
+        }
+        previous_segment = Some(segment_key);
+
+        let mut kept_sentences: Vec<String> = Vec::new();
+        for sentence in split_sentences(segment) {
+            let normalized = normalize(&sentence);
+            let reason = if is_annotation(&sentence) {
+                Some(SuspectReason::Annotation)
+            } else if is_known_phrase(&normalized) {
+                Some(SuspectReason::KnownPhrase)
+            } else {
+                None
+            };
+            match reason {
+                Some(reason) => {
+                    flag(&sentence, reason, &mut suspects);
+                    if !drop {
+                        kept_sentences.push(sentence);
+                    }
+                }
+                None => kept_sentences.push(sentence),
+            }
+        }
+
+        let line = kept_sentences.join(" ");
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (collapsed, removed_runs) = collapse_loops(&words);
+        for run in &removed_runs {
+            flag(run, SuspectReason::Repetition, &mut suspects);
+        }
+        let line = if drop && !removed_runs.is_empty() { collapsed.join(" ") } else { line };
+
+        // Still looping after collapsing runs: the whole segment is unreliable
+        let ratio = compression_ratio(&line);
+        if ratio > settings.compression_ratio_threshold {
+            flag(&line, SuspectReason::CompressionRatio, &mut suspects);
+            if drop {
+                continue;
+            }
+        }
+
+        if !line.trim().is_empty() {
+            kept_lines.push(line);
+        }
+    }
+
+    let text = kept_lines.join("\n");
+
+    // More words than the detected speech could hold: flag only, the VAD can be wrong
+    if let Some(speech) = speech_seconds {
+        let words = text.split_whitespace().count() as f32;
+        if speech > 0.0 && words / speech > MAX_WORDS_PER_SPEECH_SECOND {
+            suspects.push(SuspectSegment { text: text.clone(), reason: SuspectReason::SpeechRateMismatch, dropped: false });
+        }
+    }
+
+    FilterOutcome { text, suspects }
+}
+
+// --- Review log ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub model_used: String,
+    pub speech_seconds: Option<f32>,
+    pub raw_output: String,
+    pub suspects: Vec<SuspectSegment>,
+}
+
+fn review_log_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_handle.path_resolver().app_data_dir()
+        .ok_or_else(|| "Failed to get app data directory".to_string())?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(data_dir.join(REVIEW_LOG_FILENAME))
+}
+
+fn read_review_log(path: &Path) -> Vec<ReviewLogEntry> {
+    fs::read_to_string(path)
+        .map(|content| content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Append a filter hit to the review log, keeping the newest entries only
+pub fn log_for_review(app_handle: &AppHandle, model_used: &str, speech_seconds: Option<f32>, raw_output: &str, suspects: &[SuspectSegment]) {
+    if suspects.is_empty() {
+        return;
+    }
+    for suspect in suspects {
+        warn!("[HallucinationFilter] {:?} ({}): \"{}\"", suspect.reason, if suspect.dropped { "dropped" } else { "flagged" }, suspect.text);
+    }
+
+    let path = match review_log_path(app_handle) {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("[HallucinationFilter] {}", e);
+            return;
+        }
+    };
+    let entry = ReviewLogEntry {
+        timestamp: Utc::now(),
+        model_used: model_used.to_string(),
+        speech_seconds,
+        raw_output: raw_output.trim().to_string(),
+        suspects: suspects.to_vec(),
+    };
+
+    let mut entries = read_review_log(&path);
+    if entries.len() >= REVIEW_LOG_MAX_ENTRIES {
+        entries.drain(0..=entries.len() - REVIEW_LOG_MAX_ENTRIES);
+        entries.push(entry);
+        let content: String = entries.iter()
+            .filter_map(|e| serde_json::to_string(e).ok())
+            .map(|line| line + "\n")
+            .collect();
+        if let Err(e) = fs::write(&path, content) {
+            warn!("[HallucinationFilter] Failed to rewrite review log: {}", e);
+        }
+        return;
+    }
+
+    let line = match serde_json::to_string(&entry) {
+        Ok(line) => line,
+        Err(e) => {
+            warn!("[HallucinationFilter] Failed to serialize review entry: {}", e);
+            return;
+        }
+    };
+    match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(mut file) => {
+            if let Err(e) = writeln!(file, "{}", line) {
+                warn!("[HallucinationFilter] Failed to append review log: {}", e);
+            }
+        }
+        Err(e) => warn!("[HallucinationFilter] Failed to open review log: {}", e),
+    }
+}
+
+#[tauri::command]
+pub fn get_hallucination_review_log(app_handle: AppHandle) -> Result<Vec<ReviewLogEntry>, String> {
+    let path = review_log_path(&app_handle)?;
+    let mut entries = read_review_log(&path);
+    entries.reverse(); // Newest first, like history
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn clear_hallucination_review_log(app_handle: AppHandle) -> Result<(), String> {
+    let path = review_log_path(&app_handle)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to clear review log: {}", e))?;
+    }
+    info!("[HallucinationFilter] Review log cleared");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One case per line: `raw output => expected text` (empty expected = dropped entirely).
+    // `\n` separates whisper segments. Lines starting with '#' are comments.
+    const CORPUS: &str = include_str!("../test-data/whisper_hallucinations.txt");
+
+    fn drop_settings() -> HallucinationFilterSettings {
+        HallucinationFilterSettings { mode: HallucinationFilterMode::Drop, ..Default::default() }
+    }
+
+    #[test]
+    fn corpus_of_known_bad_outputs() {
+        let settings = drop_settings();
+        let mut cases = 0;
+        for line in CORPUS.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#')) {
+            let (raw, expected) = line.split_once("=>").expect("corpus line needs '=>'");
+            let raw = raw.trim().replace("\\n", "\n");
+            let expected = expected.trim().replace("\\n", "\n");
+            // Plenty of speech, so only the text-based checks apply
+            let outcome = filter_output(&raw, Some(30.0), &settings);
+            assert_eq!(outcome.text, expected, "corpus case: {}", raw);
+            cases += 1;
+        }
+        assert!(cases >= 10, "corpus looks truncated");
+    }
+
+    #[test]
+    fn silent_clip_drops_everything() {
+        let outcome = filter_output(" Thank you.\n", Some(0.0), &drop_settings());
+        assert_eq!(outcome.text, "");
+        assert_eq!(outcome.suspects[0].reason, SuspectReason::NoSpeech);
+    }
+
+    #[test]
+    fn weak_phrase_survives_real_speech() {
+        let outcome = filter_output(" Thank you.\n", Some(1.5), &drop_settings());
+        assert_eq!(outcome.text, "Thank you.");
+        assert!(outcome.suspects.is_empty());
+    }
+
+    #[test]
+    fn flag_mode_keeps_text() {
+        let settings = HallucinationFilterSettings { mode: HallucinationFilterMode::Flag, ..Default::default() };
+        let outcome = filter_output("Send the report. Thanks for watching!", Some(5.0), &settings);
+        assert_eq!(outcome.text, "Send the report. Thanks for watching!");
+        assert_eq!(outcome.suspects.len(), 1);
+        assert!(!outcome.suspects[0].dropped);
+    }
+
+    #[test]
+    fn collapses_phrase_loops() {
+        let words: Vec<&str> = "I think I think I think I think we should go".split_whitespace().collect();
+        let (kept, removed) = collapse_loops(&words);
+        assert_eq!(kept.join(" "), "I think we should go");
+        assert_eq!(removed, vec!["I think I think I think".to_string()]);
+
+        // Short legitimate repeats are left alone
+        let words: Vec<&str> = "no no no that's wrong".split_whitespace().collect();
+        assert_eq!(collapse_loops(&words).0.join(" "), "no no no that's wrong");
+    }
+
+    #[test]
+    fn compression_ratio_spots_loops() {
+        let normal = "The meeting moved to Thursday because the client needs more time to review the budget.";
+        let looping = "and then we went to the store and then we went to the store ".repeat(8);
+        assert!(compression_ratio(normal) < 2.4);
+        assert!(compression_ratio(&looping) > 2.4);
+    }
+
+    #[test]
+    fn vad_separates_silence_from_tone() {
+        let rate = 16000;
+        let mut samples = vec![0i16; rate as usize]; // 1 s silence
+        for n in 0..rate as usize {
+            // 1 s of 200 Hz tone at about -12 dBFS
+            let t = n as f32 / rate as f32;
+            samples.push(((2.0 * std::f32::consts::PI * 200.0 * t).sin() * 8000.0) as i16);
+        }
+        let speech = speech_seconds(&samples, rate);
+        assert!((speech - 1.0).abs() < 0.1, "speech estimate {}", speech);
+        assert_eq!(speech_seconds(&vec![0i16; rate as usize], rate), 0.0);
+    }
+
+    #[test]
+    fn too_many_words_for_the_speech_is_flagged() {
+        let outcome = filter_output("one two three four five six seven eight nine ten eleven twelve", Some(1.0), &drop_settings());
+        assert_eq!(outcome.suspects.len(), 1);
+        assert_eq!(outcome.suspects[0].reason, SuspectReason::SpeechRateMismatch);
+        assert!(!outcome.text.is_empty());
+    }
+}
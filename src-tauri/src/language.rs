@@ -0,0 +1,222 @@
+// src-tauri/src/language.rs
+//
+// Language handling for transcription: deciding what to pass to whisper's `-l`,
+// restricting auto-detection to the user's allowed languages, reading the detected
+// language back out of whisper's log output, and switching between English-only
+// (`.en`) and multilingual models to match.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+pub const AUTO_LANGUAGE: &str = "auto";
+pub const ENGLISH: &str = "en";
+
+/// What we ask whisper to do for one transcription
+#[derive(Debug, Clone, PartialEq)]
+pub enum LanguageMode {
+    Fixed(String),
+    /// Let whisper detect; the result must be one of these (empty = any)
+    Detect { allowed: Vec<String> },
+}
+
+impl LanguageMode {
+    /// Value for whisper's `-l` flag
+    pub fn whisper_arg(&self) -> &str {
+        match self {
+            LanguageMode::Fixed(language) => language,
+            LanguageMode::Detect { .. } => AUTO_LANGUAGE,
+        }
+    }
+
+    pub fn needs_multilingual_model(&self) -> bool {
+        match self {
+            LanguageMode::Fixed(language) => language != ENGLISH,
+            LanguageMode::Detect { allowed } => !(allowed.len() == 1 && allowed[0] == ENGLISH),
+        }
+    }
+}
+
+fn normalize_code(code: &str) -> String {
+    code.trim().to_lowercase()
+}
+
+/// Combine the language setting with the allowed list.
+/// A single allowed language makes detection pointless, so it becomes fixed.
+pub fn resolve_language_mode(language_setting: &str, allowed_languages: &[String]) -> LanguageMode {
+    let setting = normalize_code(language_setting);
+    if setting != AUTO_LANGUAGE && !setting.is_empty() {
+        return LanguageMode::Fixed(setting);
+    }
+    let mut allowed: Vec<String> = allowed_languages.iter()
+        .map(|code| normalize_code(code))
+        .filter(|code| !code.is_empty() && code != AUTO_LANGUAGE)
+        .collect();
+    allowed.dedup();
+    if allowed.len() == 1 {
+        return LanguageMode::Fixed(allowed.remove(0));
+    }
+    LanguageMode::Detect { allowed }
+}
+
+static DETECTED_LANGUAGE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    // whisper.cpp: "whisper_full_with_state: auto-detected language: de (p = 0.912345)"
+    Regex::new(r"auto-detected language:\s*([a-z]{2,3})\s*\(p\s*=\s*([0-9.]+)\)").unwrap()
+});
+
+/// Detected language code and probability from whisper's stderr, if it ran detection
+pub fn parse_detected_language(whisper_log: &str) -> Option<(String, f32)> {
+    let captures = DETECTED_LANGUAGE_PATTERN.captures(whisper_log)?;
+    let code = captures.get(1)?.as_str().to_string();
+    let confidence = captures.get(2)?.as_str().parse::<f32>().ok()?;
+    Some((code, confidence))
+}
+
+/// Is `detected` acceptable under `mode`? Fixed mode accepts anything (whisper didn't detect).
+pub fn is_allowed(mode: &LanguageMode, detected: &str) -> bool {
+    match mode {
+        LanguageMode::Fixed(_) => true,
+        LanguageMode::Detect { allowed } => allowed.is_empty() || allowed.iter().any(|code| code == detected),
+    }
+}
+
+/// Language to force when detection landed outside the allowed list
+pub fn fallback_language(mode: &LanguageMode) -> Option<String> {
+    match mode {
+        LanguageMode::Detect { allowed } => allowed.first().cloned(),
+        LanguageMode::Fixed(_) => None,
+    }
+}
+
+// whisper's language table (code, name), as reported by HTTP backends' verbose_json
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("en", "english"), ("zh", "chinese"), ("de", "german"), ("es", "spanish"), ("ru", "russian"),
+    ("ko", "korean"), ("fr", "french"), ("ja", "japanese"), ("pt", "portuguese"), ("tr", "turkish"),
+    ("pl", "polish"), ("ca", "catalan"), ("nl", "dutch"), ("ar", "arabic"), ("sv", "swedish"),
+    ("it", "italian"), ("id", "indonesian"), ("hi", "hindi"), ("fi", "finnish"), ("vi", "vietnamese"),
+    ("he", "hebrew"), ("uk", "ukrainian"), ("el", "greek"), ("ms", "malay"), ("cs", "czech"),
+    ("ro", "romanian"), ("da", "danish"), ("hu", "hungarian"), ("ta", "tamil"), ("no", "norwegian"),
+    ("th", "thai"), ("ur", "urdu"), ("hr", "croatian"), ("bg", "bulgarian"), ("lt", "lithuanian"),
+    ("la", "latin"), ("mi", "maori"), ("ml", "malayalam"), ("cy", "welsh"), ("sk", "slovak"),
+    ("te", "telugu"), ("fa", "persian"), ("lv", "latvian"), ("bn", "bengali"), ("sr", "serbian"),
+    ("az", "azerbaijani"), ("sl", "slovenian"), ("kn", "kannada"), ("et", "estonian"), ("mk", "macedonian"),
+    ("br", "breton"), ("eu", "basque"), ("is", "icelandic"), ("hy", "armenian"), ("ne", "nepali"),
+    ("mn", "mongolian"), ("bs", "bosnian"), ("kk", "kazakh"), ("sq", "albanian"), ("sw", "swahili"),
+    ("gl", "galician"), ("mr", "marathi"), ("pa", "punjabi"), ("si", "sinhala"), ("km", "khmer"),
+    ("sn", "shona"), ("yo", "yoruba"), ("so", "somali"), ("af", "afrikaans"), ("oc", "occitan"),
+    ("ka", "georgian"), ("be", "belarusian"), ("tg", "tajik"), ("sd", "sindhi"), ("gu", "gujarati"),
+    ("am", "amharic"), ("yi", "yiddish"), ("lo", "lao"), ("uz", "uzbek"), ("fo", "faroese"),
+    ("ht", "haitian creole"), ("ps", "pashto"), ("tk", "turkmen"), ("nn", "nynorsk"), ("mt", "maltese"),
+    ("sa", "sanskrit"), ("lb", "luxembourgish"), ("my", "myanmar"), ("bo", "tibetan"), ("tl", "tagalog"),
+    ("mg", "malagasy"), ("as", "assamese"), ("tt", "tatar"), ("haw", "hawaiian"), ("ln", "lingala"),
+    ("ha", "hausa"), ("ba", "bashkir"), ("jw", "javanese"), ("su", "sundanese"), ("yue", "cantonese"),
+];
+
+/// ISO code for a language reported by name ("german") or code ("de"); None if unknown
+pub fn code_for_language(reported: &str) -> Option<String> {
+    let reported = normalize_code(reported);
+    LANGUAGE_NAMES.iter()
+        .find(|(code, name)| *code == reported || *name == reported)
+        .map(|(code, _)| code.to_string())
+}
+
+pub fn is_english_only_model(model_name: &str) -> bool {
+    model_name.contains(".en.") || model_name.contains(".en-")
+}
+
+/// ggml-base.en.bin <-> ggml-base.bin, ggml-tiny.en-q5_1.bin <-> ggml-tiny-q5_1.bin
+pub fn counterpart_model(model_name: &str) -> Option<String> {
+    if is_english_only_model(model_name) {
+        Some(model_name.replacen(".en.", ".", 1).replacen(".en-", "-", 1))
+    } else {
+        // The size name ("tiny", "base", ...) runs from after "ggml-" to the next '-' or '.'
+        let after_prefix = if model_name.starts_with("ggml-") { 5 } else { 0 };
+        let stem_end = model_name[after_prefix..].find(['-', '.'])? + after_prefix;
+        let size = &model_name[..stem_end];
+        // large-v3 and turbo have no English-only variant
+        if size.contains("large") {
+            return None;
+        }
+        Some(format!("{}.en{}", size, &model_name[stem_end..]))
+    }
+}
+
+/// Swap to the English-only or multilingual twin when the language calls for it and the twin is installed.
+/// English gets the `.en` model (more accurate); anything else needs a multilingual one.
+pub fn model_for_language(model_name: &str, mode: &LanguageMode, installed: &[String]) -> String {
+    let needs_multilingual = mode.needs_multilingual_model();
+    let english_only = is_english_only_model(model_name);
+    let wants_english_only = matches!(mode, LanguageMode::Fixed(language) if language == ENGLISH);
+
+    let should_switch = (needs_multilingual && english_only) || (wants_english_only && !english_only);
+    if should_switch {
+        if let Some(twin) = counterpart_model(model_name) {
+            if installed.contains(&twin) {
+                return twin;
+            }
+        }
+    }
+    model_name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn langs(codes: &[&str]) -> Vec<String> {
+        codes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn resolves_modes() {
+        assert_eq!(resolve_language_mode("de", &langs(&["en", "fr"])), LanguageMode::Fixed("de".into()));
+        assert_eq!(resolve_language_mode("auto", &langs(&["fr"])), LanguageMode::Fixed("fr".into()));
+        assert_eq!(resolve_language_mode("auto", &langs(&["en", "DE "])), LanguageMode::Detect { allowed: langs(&["en", "de"]) });
+        assert_eq!(resolve_language_mode("auto", &[]), LanguageMode::Detect { allowed: vec![] });
+    }
+
+    #[test]
+    fn parses_whisper_detection_line() {
+        let log = "whisper_init_state: compute buffer (decode) = 96.47 MB\n\
+                   whisper_full_with_state: auto-detected language: de (p = 0.912345)\n";
+        assert_eq!(parse_detected_language(log), Some(("de".to_string(), 0.912345)));
+        assert_eq!(parse_detected_language("no detection here"), None);
+    }
+
+    #[test]
+    fn maps_reported_language_names() {
+        assert_eq!(code_for_language("German").as_deref(), Some("de"));
+        assert_eq!(code_for_language("en").as_deref(), Some("en"));
+        assert_eq!(code_for_language("klingon"), None);
+    }
+
+    #[test]
+    fn allowed_list_restricts_detection() {
+        let mode = LanguageMode::Detect { allowed: langs(&["en", "de"]) };
+        assert!(is_allowed(&mode, "de"));
+        assert!(!is_allowed(&mode, "nl"));
+        assert_eq!(fallback_language(&mode).as_deref(), Some("en"));
+        assert!(is_allowed(&LanguageMode::Detect { allowed: vec![] }, "nl"));
+    }
+
+    #[test]
+    fn maps_model_counterparts() {
+        assert_eq!(counterpart_model("ggml-tiny.en.bin").as_deref(), Some("ggml-tiny.bin"));
+        assert_eq!(counterpart_model("ggml-tiny.bin").as_deref(), Some("ggml-tiny.en.bin"));
+        assert_eq!(counterpart_model("ggml-base.en-q5_1.bin").as_deref(), Some("ggml-base-q5_1.bin"));
+        assert_eq!(counterpart_model("ggml-base-q5_1.bin").as_deref(), Some("ggml-base.en-q5_1.bin"));
+        assert_eq!(counterpart_model("ggml-large-v3.bin"), None);
+    }
+
+    #[test]
+    fn switches_models_only_when_twin_installed() {
+        let installed = langs(&["ggml-tiny.en.bin", "ggml-tiny.bin", "ggml-base.bin"]);
+        let german = LanguageMode::Fixed("de".into());
+        let english = LanguageMode::Fixed("en".into());
+        let detect = LanguageMode::Detect { allowed: langs(&["en", "de"]) };
+        assert_eq!(model_for_language("ggml-tiny.en.bin", &german, &installed), "ggml-tiny.bin");
+        assert_eq!(model_for_language("ggml-tiny.en.bin", &detect, &installed), "ggml-tiny.bin");
+        assert_eq!(model_for_language("ggml-tiny.bin", &english, &installed), "ggml-tiny.en.bin");
+        // base.en isn't installed, so English keeps the multilingual base
+        assert_eq!(model_for_language("ggml-base.bin", &english, &installed), "ggml-base.bin");
+    }
+}
@@ -0,0 +1,368 @@
+// src-tauri/src/local_llm.rs
+//
+// Offline AI actions: a small instruction-tuned GGUF model run on the CPU by llama.cpp's
+// `llama-cli`, one process per request. llama-cli ships as a sidecar (externalBin) like
+// whisper: release builds run it from next to the app executable, debug builds from vendor/.
+// A cancelled request kills the process.
+//
+// Models are downloaded into <app data>/llm_models with the model manager's resumable
+// download and checked against the SHA256 pinned in the catalog; a model without a pin
+// can't be downloaded, so nothing unverified is ever loaded. They are meant for the
+// built-in written_form, summarize and email actions; the other built-in actions need
+// more than a small model can do and are refused with the local provider.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::config::LocalProviderSettings;
+use crate::model_manager::{download_and_verify, read_manifest, sha256_file, write_manifest, ModelDownloadProgress, PARTIAL_SUFFIX};
+use crate::process_runner::{self, ProcessError};
+use crate::transcription_queue::JobControl;
+
+const LOCAL_MODELS_DIR_NAME: &str = "llm_models";
+
+/// Built-in actions the local models handle well enough
+pub const LOCAL_ACTIONS: &[&str] = &["written_form", "summarize", "email"];
+
+/// How a model expects a chat turn to be laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatFormat {
+    ChatMl, // Qwen
+    Llama3,
+}
+
+/// One downloadable GGUF model
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalModelEntry {
+    pub id: &'static str,
+    pub file_name: &'static str,
+    pub url: &'static str,
+    pub approx_size_mb: u32,
+    pub chat_format: ChatFormat,
+    pub sha256: Option<&'static str>, // None = not pinned yet, download refused
+}
+
+/// Smallest first; all Q4_K_M quantizations that run acceptably on a laptop CPU
+pub static LOCAL_MODEL_CATALOG: &[LocalModelEntry] = &[
+    LocalModelEntry {
+        id: "qwen2.5-0.5b-instruct-q4_k_m",
+        file_name: "qwen2.5-0.5b-instruct-q4_k_m.gguf",
+        url: "https://huggingface.co/Qwen/Qwen2.5-0.5B-Instruct-GGUF/resolve/main/qwen2.5-0.5b-instruct-q4_k_m.gguf",
+        approx_size_mb: 400,
+        chat_format: ChatFormat::ChatMl,
+        sha256: None,
+    },
+    LocalModelEntry {
+        id: "llama-3.2-1b-instruct-q4_k_m",
+        file_name: "Llama-3.2-1B-Instruct-Q4_K_M.gguf",
+        url: "https://huggingface.co/bartowski/Llama-3.2-1B-Instruct-GGUF/resolve/main/Llama-3.2-1B-Instruct-Q4_K_M.gguf",
+        approx_size_mb: 810,
+        chat_format: ChatFormat::Llama3,
+        sha256: None,
+    },
+    LocalModelEntry {
+        id: "qwen2.5-1.5b-instruct-q4_k_m",
+        file_name: "qwen2.5-1.5b-instruct-q4_k_m.gguf",
+        url: "https://huggingface.co/Qwen/Qwen2.5-1.5B-Instruct-GGUF/resolve/main/qwen2.5-1.5b-instruct-q4_k_m.gguf",
+        approx_size_mb: 1120,
+        chat_format: ChatFormat::ChatMl,
+        sha256: None,
+    },
+];
+
+pub fn find_local_model(model_id: &str) -> Option<&'static LocalModelEntry> {
+    LOCAL_MODEL_CATALOG.iter().find(|m| m.id == model_id || m.file_name == model_id)
+}
+
+/// Catalog entry plus download state
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalModelStatus {
+    #[serde(flatten)]
+    pub entry: LocalModelEntry,
+    pub downloaded: bool,
+    pub downloading: bool,
+}
+
+// Set by init(); the provider is built from settings alone and has no AppHandle
+static MODELS_DIR: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+// Cancel flags for in-flight downloads, keyed by model id
+static ACTIVE_DOWNLOADS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Called at startup: resolve the local models directory
+pub fn init(app_handle: &AppHandle) {
+    match app_handle.path_resolver().app_data_dir() {
+        Some(dir) => *MODELS_DIR.lock().unwrap() = Some(dir.join(LOCAL_MODELS_DIR_NAME)),
+        None => warn!("[Local LLM] No app data directory; local models are unavailable"),
+    }
+}
+
+fn models_dir() -> Result<PathBuf, String> {
+    let dir = MODELS_DIR.lock().unwrap().clone()
+        .ok_or_else(|| "Local models directory is not available".to_string())?;
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    Ok(dir)
+}
+
+// --- Running the model ---
+
+/// Wrap `prompt` as a single user turn and open the assistant's turn
+pub fn format_prompt(format: ChatFormat, prompt: &str) -> String {
+    match format {
+        ChatFormat::ChatMl => format!(
+            "<|im_start|>system\nYou are a careful writing assistant.<|im_end|>\n<|im_start|>user\n{}<|im_end|>\n<|im_start|>assistant\n",
+            prompt
+        ),
+        // llama.cpp adds <|begin_of_text|> itself
+        ChatFormat::Llama3 => format!(
+            "<|start_header_id|>user<|end_header_id|>\n\n{}<|eot_id|><|start_header_id|>assistant<|end_header_id|>\n\n",
+            prompt
+        ),
+    }
+}
+
+/// The generated text without llama-cli's end marker or stray end-of-turn tokens
+pub fn clean_output(stdout: &str) -> String {
+    let mut text = stdout.trim();
+    for marker in ["[end of text]", "<|im_end|>", "<|eot_id|>"] {
+        text = text.strip_suffix(marker).unwrap_or(text).trim_end();
+    }
+    text.trim().to_string()
+}
+
+fn resolve_llama_binary() -> Result<PathBuf, String> {
+    let sidecar = if cfg!(debug_assertions) {
+        let binary_name = if cfg!(target_os = "windows") {
+            "llama-cli-x86_64-pc-windows-msvc.exe"
+        } else if cfg!(target_os = "macos") {
+            if cfg!(target_arch = "aarch64") { "llama-cli-aarch64-apple-darwin" } else { "llama-cli-x86_64-apple-darwin" }
+        } else {
+            "llama-cli-x86_64-unknown-linux-gnu"
+        };
+        Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("vendor").join(binary_name))
+    } else {
+        std::env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(if cfg!(target_os = "windows") { "llama-cli.exe" } else { "llama-cli" })))
+    };
+    match sidecar {
+        Some(path) if path.is_file() => Ok(path),
+        Some(path) => Err(format!("The bundled llama.cpp (llama-cli) was not found at {}", path.display())),
+        None => Err("Could not determine the executable directory to find llama-cli".to_string()),
+    }
+}
+
+fn build_command(binary: &Path, model_path: &Path, prompt_path: &Path, settings: &LocalProviderSettings) -> Command {
+    let mut command = Command::new(binary);
+    command
+        .arg("-m").arg(model_path)
+        .arg("-f").arg(prompt_path)
+        .arg("-n").arg(settings.max_tokens.to_string())
+        .arg("-c").arg((settings.max_prompt_tokens + settings.max_tokens).to_string())
+        .args(["--temp", "0.2", "-ngl", "0", "--no-display-prompt", "-no-cnv"]);
+    if settings.threads > 0 {
+        command.arg("-t").arg(settings.threads.to_string());
+    }
+    command
+}
+
+/// Run `prompt` through the configured local model (`model_id` overrides it).
+/// Cancelling `cancel` kills llama-cli.
+pub async fn complete(settings: &LocalProviderSettings, model_id: Option<&str>, prompt: &str, cancel: Option<Arc<JobControl>>) -> Result<String, String> {
+    let model_id = model_id.unwrap_or(&settings.model_id);
+    let entry = find_local_model(model_id)
+        .ok_or_else(|| format!("Unknown local model '{}'", model_id))?;
+    let model_path = models_dir()?.join(entry.file_name);
+    if !model_path.is_file() {
+        return Err(format!("The local model {} is not downloaded yet", entry.id));
+    }
+
+    // Passed as a file: prompts can be longer than a command line allows
+    let mut prompt_file = tempfile::NamedTempFile::new().map_err(|e| format!("Failed to create prompt file: {}", e))?;
+    prompt_file.write_all(format_prompt(entry.chat_format, prompt).as_bytes())
+        .map_err(|e| format!("Failed to write prompt file: {}", e))?;
+
+    let binary = resolve_llama_binary()?;
+    info!("[Local LLM] Running {} with {} via {}", entry.id, prompt_file.path().display(), binary.display());
+    let command = build_command(&binary, &model_path, prompt_file.path(), settings);
+    let output = process_runner::run(command, Duration::from_secs(settings.timeout_secs), cancel).await
+        .map_err(|e| match e {
+            ProcessError::Spawn(e) => format!("llama.cpp (llama-cli) could not start: {}", e),
+            other => format!("Local model {}", other),
+        })?;
+
+    let text = clean_output(&String::from_utf8_lossy(&output.stdout));
+    if text.is_empty() {
+        return Err("The local model returned no text".to_string());
+    }
+    Ok(text)
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub async fn list_local_models() -> Result<Vec<LocalModelStatus>, String> {
+    let dir = models_dir()?;
+    let active: Vec<String> = ACTIVE_DOWNLOADS.lock().unwrap().keys().cloned().collect();
+    Ok(LOCAL_MODEL_CATALOG.iter().filter_map(|entry| {
+        let downloaded = dir.join(entry.file_name).is_file();
+        // Not offered until its hash is pinned: download_local_model would refuse it
+        if entry.sha256.is_none() && !downloaded {
+            return None;
+        }
+        Some(LocalModelStatus {
+            entry: entry.clone(),
+            downloaded,
+            downloading: active.iter().any(|id| id == entry.id),
+        })
+    }).collect())
+}
+
+/// Download and verify a catalog model. Progress goes out as
+/// "fethr-local-model-download-progress" events.
+#[tauri::command]
+pub async fn download_local_model(app_handle: AppHandle, model_id: String) -> Result<String, String> {
+    let entry = find_local_model(&model_id)
+        .ok_or_else(|| format!("Unknown local model: {}", model_id))?;
+    let pinned = entry.sha256
+        .ok_or_else(|| format!("{} has no pinned checksum yet and can't be downloaded", entry.id))?;
+    let dir = models_dir()?;
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = ACTIVE_DOWNLOADS.lock().unwrap();
+        if active.contains_key(entry.id) {
+            return Err(format!("{} is already downloading", entry.id));
+        }
+        active.insert(entry.id.to_string(), cancel_flag.clone());
+    }
+    scopeguard::defer!({
+        ACTIVE_DOWNLOADS.lock().unwrap().remove(entry.id);
+    });
+
+    info!("[Local LLM] Downloading {} from {}", entry.id, entry.url);
+    let result = download_and_verify(entry.url, &dir, entry.file_name, Some(pinned), &cancel_flag, |downloaded, total| {
+        let payload = ModelDownloadProgress {
+            model_id: entry.id.to_string(),
+            downloaded_bytes: downloaded,
+            total_bytes: total,
+        };
+        let _ = app_handle.emit_all("fethr-local-model-download-progress", payload);
+    }).await;
+
+    match result {
+        Ok(hash) => {
+            let mut manifest = read_manifest(&dir);
+            manifest.insert(entry.file_name.to_string(), hash);
+            write_manifest(&dir, &manifest)?;
+            info!("[Local LLM] {} downloaded and verified", entry.id);
+            Ok(entry.id.to_string())
+        }
+        Err(e) => {
+            error!("[Local LLM] Download of {} failed: {}", entry.id, e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_local_model_download(model_id: String) -> Result<(), String> {
+    let active = ACTIVE_DOWNLOADS.lock().unwrap();
+    let flag = active.get(&model_id)
+        .ok_or_else(|| format!("{} is not downloading", model_id))?;
+    flag.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Re-hash a downloaded model against the hash recorded when it was verified
+#[tauri::command]
+pub async fn verify_local_model(model_id: String) -> Result<bool, String> {
+    let entry = find_local_model(&model_id)
+        .ok_or_else(|| format!("Unknown local model: {}", model_id))?;
+    let dir = models_dir()?;
+    let path = dir.join(entry.file_name);
+    if !path.is_file() {
+        return Err(format!("{} is not downloaded", entry.id));
+    }
+    let expected = entry.sha256.map(|h| h.to_string())
+        .or_else(|| read_manifest(&dir).get(entry.file_name).cloned())
+        .ok_or_else(|| format!("No recorded checksum for {}", entry.id))?;
+    let actual = tokio::task::spawn_blocking(move || sha256_file(&path))
+        .await
+        .map_err(|e| format!("Checksum task failed: {}", e))??;
+    if actual != expected {
+        warn!("[Local LLM] {} failed verification: expected {}, got {}", entry.id, expected, actual);
+    }
+    Ok(actual == expected)
+}
+
+#[tauri::command]
+pub async fn delete_local_model(model_id: String) -> Result<(), String> {
+    let entry = find_local_model(&model_id)
+        .ok_or_else(|| format!("Unknown local model: {}", model_id))?;
+    let dir = models_dir()?;
+    let path = dir.join(entry.file_name);
+    if path.is_file() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+    }
+    let _ = fs::remove_file(dir.join(format!("{}{}", entry.file_name, PARTIAL_SUFFIX)));
+    let mut manifest = read_manifest(&dir);
+    if manifest.remove(entry.file_name).is_some() {
+        write_manifest(&dir, &manifest)?;
+    }
+    info!("[Local LLM] Deleted {}", entry.id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_prompts_per_model_family() {
+        let chatml = format_prompt(ChatFormat::ChatMl, "Summarize: ship it");
+        assert!(chatml.contains("<|im_start|>user\nSummarize: ship it<|im_end|>"));
+        assert!(chatml.ends_with("<|im_start|>assistant\n"));
+        let llama = format_prompt(ChatFormat::Llama3, "Summarize: ship it");
+        assert!(llama.ends_with("<|start_header_id|>assistant<|end_header_id|>\n\n"));
+        assert!(!llama.contains("<|begin_of_text|>"));
+    }
+
+    #[test]
+    fn strips_end_markers() {
+        assert_eq!(clean_output("\nShip it today. [end of text]\n"), "Ship it today.");
+        assert_eq!(clean_output("Ship it.<|im_end|>"), "Ship it.");
+        assert_eq!(clean_output("  [end of text]"), "");
+    }
+
+    #[test]
+    fn builds_a_cpu_only_command() {
+        let settings = LocalProviderSettings { threads: 4, ..Default::default() };
+        let command = build_command(Path::new("llama-cli"), Path::new("m.gguf"), Path::new("p.txt"), &settings);
+        let args: Vec<String> = command.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(&args[..4], ["-m", "m.gguf", "-f", "p.txt"]);
+        assert!(args.windows(2).any(|w| w == ["-ngl", "0"]));
+        assert!(args.windows(2).any(|w| w == ["-c", "3512"]));
+        assert!(args.windows(2).any(|w| w == ["-t", "4"]));
+        assert!(find_local_model(&LocalProviderSettings::default().model_id).is_some());
+    }
+
+    #[test]
+    fn pinned_hashes_are_sha256_hex() {
+        for hash in LOCAL_MODEL_CATALOG.iter().filter_map(|entry| entry.sha256) {
+            assert_eq!(hash.len(), 64);
+            assert!(hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')));
+        }
+    }
+}
@@ -41,11 +41,35 @@ mod word_usage_tracker; // <<< ADDED: Track dictionary word usage
 mod whisper_variations; // <<< ADDED: Handle common Whisper transcription variations
 mod user_statistics; // User statistics tracking for Supabase
 mod audio_devices; // Audio device management
+mod audio_convert; // Capture buffer conversion (formats, channel downmix)
 mod auth_manager; // Authentication and token management
 mod stats_queue; // Statistics queue for batching updates
 mod sound_player; // Sound effects player
 mod sound_commands; // Sound-related commands
 mod smart_formatter; // Smart text formatting - removes filler words
+mod recordings; // Recording storage, crash recovery and cleanup
+mod audio_retention; // Optional compressed source audio for history entries
+mod model_manager; // Whisper model catalog, downloads and verification
+mod model_selector; // "auto" model mode: benchmark and per-recording choice
+mod language; // Language detection, allowed languages and .en/multilingual model switching
+mod translation; // Translation dictation (Shift + hotkey)
+mod decoding_ab; // A/B comparison of whisper decoding parameters
+mod hallucination_filter; // Drops whisper hallucinations and repetition loops
+mod word_confidence; // Per-word whisper confidence: correction gating and highlighting
+mod transcription_backend; // Local, in-process and cloud speech-to-text backends with fallback
+mod transcription_queue; // Ordered transcription job queue with status events and cancellation
+mod process_runner; // whisper/ffmpeg child processes with timeouts and cancellation
+mod ai_actions; // User-defined AI actions, prompt overrides and action hotkeys
+mod ai_provider; // Fethr relay, OpenAI-compatible chat and Anthropic clients for AI actions
+mod ai_chain; // Automatic AI actions between transcription and paste
+mod prompt_template; // Template language of AI action prompts: variables, conditionals, escapes
+mod prompt_context; // Values for prompt template variables (clipboard, active app, history, ...)
+mod command_mode; // Spoken instructions applied to the text selected in another app
+mod local_llm; // Offline AI actions with a small GGUF model run by llama.cpp
+mod ai_cache; // Stored AI action results, reused instead of repeating a request
+mod style_profile; // The user's writing style, added to rewrite action prompts
+#[cfg(test)]
+mod mock_http; // Single-request HTTP server for client tests
 
 // Export modules for cross-file references
 pub use config::SETTINGS; // Export SETTINGS for use by other modules
@@ -61,6 +85,9 @@ use crate::transcription::TranscriptionState; // Make sure TranscriptionState is
 pub struct HistoryEntry {
     timestamp: String,
     text: String,
+    // Keep fields owned by transcription.rs (corrections, audio_file, revisions) intact on rewrite
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 // --- END HistoryEntry Struct ---
 
@@ -129,6 +156,7 @@ pub enum FrontendRecordingState {
     Idle,
     Recording,
     LockedRecording,
+    Paused, // Locked recording on hold; samples are not written
     Transcribing,
     Error,
     Pasting, // Add any other states defined in TypeScript
@@ -155,6 +183,7 @@ struct StateUpdatePayload {
 pub enum RecordingLifecycle {
     Idle,
     Recording(Arc<AtomicBool>), // Store the session's active flag
+    Paused(Arc<AtomicBool>),    // Session kept alive, capture thread discards samples
     Stopping,                   // Intermediate state during cleanup
 }
 
@@ -165,6 +194,7 @@ impl PartialEq for RecordingLifecycle {
             (RecordingLifecycle::Idle, RecordingLifecycle::Idle) => true,
             (RecordingLifecycle::Stopping, RecordingLifecycle::Stopping) => true,
             (RecordingLifecycle::Recording(_), RecordingLifecycle::Recording(_)) => true,
+            (RecordingLifecycle::Paused(_), RecordingLifecycle::Paused(_)) => true,
             _ => false,
         }
     }
@@ -184,6 +214,7 @@ pub enum AppRecordingState { // Use simpler enum
     Idle,
     Recording,
     LockedRecording,
+    Paused,
     Transcribing,
 }
 
@@ -192,6 +223,8 @@ pub enum AppRecordingState { // Use simpler enum
 enum HotkeyEvent {
     Press(Instant),
     Release(Instant),
+    TogglePause, // Pause hotkey or toggle_pause_recording command
+    Cancel,      // Escape while recording - discard the take
 }
 
 // Shared application state
@@ -220,6 +253,8 @@ lazy_static! {
     static ref EVENT_CHANNEL: (Sender<HotkeyEvent>, Receiver<HotkeyEvent>) = unbounded();
     static ref EVENT_SENDER: Sender<HotkeyEvent> = EVENT_CHANNEL.0.clone();
     static ref EVENT_RECEIVER: Receiver<HotkeyEvent> = EVENT_CHANNEL.1.clone();
+    // Registered pause hotkey (settings.pause_hotkey)
+    static ref PAUSE_KEY: Mutex<RdevKey> = Mutex::new(RdevKey::Pause);
 }
 
 #[derive(Debug, Clone)]
@@ -229,6 +264,7 @@ struct AuthState {
 }
 
 const TAP_MAX_DURATION_MS: u128 = 300;
+const CANCEL_HOTKEY: RdevKey = RdevKey::Escape;
 
 #[derive(Default)]
 pub struct AudioRecordingState {
@@ -237,6 +273,9 @@ pub struct AudioRecordingState {
     pub temp_wav_path: Option<PathBuf>,
     pub writer: Option<Arc<Mutex<Option<hound::WavWriter<BufWriter<File>>>>>>,
     pub recording_start_time: Option<std::time::Instant>,
+    pub capture_paused: Arc<AtomicBool>, // Checked by the data callback before writing samples
+    pub paused_since: Option<std::time::Instant>,
+    pub paused_duration: Duration, // Accumulated pause time, excluded from stats duration
 }
 pub type SharedRecordingState = Arc<Mutex<AudioRecordingState>>;
 
@@ -408,6 +447,9 @@ enum PostEventAction {
     StartRecordingAndEmitUi,
     StopAndTranscribeAndEmitUi,
     UpdateUiOnly, // For entering LockedRecording
+    PauseAndEmitUi,
+    ResumeAndEmitUi,
+    CancelAndEmitUi,
     AuthRequired, // For when auth is needed
 }
 
@@ -434,6 +476,10 @@ fn process_hotkey_event(event: HotkeyEvent, app_handle: &AppHandle) {
                             
                             if is_authenticated {
                                 // Starting recording
+                                audio_manager_rs::clear_cancel_before_start();
+                                translation::arm_for_new_take();
+                                ai_actions::arm_for_new_take();
+                                command_mode::arm_for_new_take();
                                 state.recording_state = AppRecordingState::Recording;
                                 action_to_take = PostEventAction::StartRecordingAndEmitUi;
                             } else {
@@ -441,7 +487,7 @@ fn process_hotkey_event(event: HotkeyEvent, app_handle: &AppHandle) {
                                 action_to_take = PostEventAction::AuthRequired;
                             }
                         }
-                        AppRecordingState::LockedRecording => {
+                        AppRecordingState::LockedRecording | AppRecordingState::Paused => {
                             // Stopping recording
                             state.recording_state = AppRecordingState::Transcribing;
                             action_to_take = PostEventAction::StopAndTranscribeAndEmitUi;
@@ -474,6 +520,34 @@ fn process_hotkey_event(event: HotkeyEvent, app_handle: &AppHandle) {
                     } else { println!("[State Processor (Simplified V2) WARN] Release without matching press_start_time! (State: {:?})", current_state); }
                  } else { println!("[State Processor (Simplified V2) WARN] Ignoring spurious Release event."); }
             }
+            HotkeyEvent::TogglePause => {
+                // Only locked recordings can be paused; hold-to-talk ends on release anyway
+                match current_state {
+                    AppRecordingState::LockedRecording => {
+                        state.recording_state = AppRecordingState::Paused;
+                        action_to_take = PostEventAction::PauseAndEmitUi;
+                    }
+                    AppRecordingState::Paused => {
+                        state.recording_state = AppRecordingState::LockedRecording;
+                        action_to_take = PostEventAction::ResumeAndEmitUi;
+                    }
+                    _ => println!("[State Processor (Simplified V2)] Ignoring TogglePause in state: {:?}", current_state),
+                }
+            }
+            HotkeyEvent::Cancel => {
+                match current_state {
+                    AppRecordingState::Recording | AppRecordingState::LockedRecording | AppRecordingState::Paused => {
+                        // Keep hotkey_down_physically so a pending AltGr release is still consumed
+                        state.recording_state = AppRecordingState::Idle;
+                        state.press_start_time = None;
+                        translation::disarm();
+                        ai_actions::disarm();
+                        command_mode::disarm();
+                        action_to_take = PostEventAction::CancelAndEmitUi;
+                    }
+                    _ => {} // Escape outside a recording is none of our business
+                }
+            }
         }
         // State updated
     } // State lock released
@@ -485,6 +559,10 @@ fn process_hotkey_event(event: HotkeyEvent, app_handle: &AppHandle) {
              let payload = StateUpdatePayload { state: FrontendRecordingState::Recording, ..Default::default() };
              emit_state_update(app_handle, payload);
              emit_start_recording(app_handle);
+             // Lets the pill show that this take will be translated
+             let _ = app_handle.emit_all("fethr-translation-mode", translation::take_is_armed());
+             let _ = app_handle.emit_all("fethr-ai-action-mode", ai_actions::take_is_armed());
+             let _ = app_handle.emit_all("fethr-command-mode", command_mode::take_is_armed());
              
              // Play start sound
              if let Ok(player_guard) = sound_player::SOUND_PLAYER.lock() {
@@ -509,6 +587,31 @@ fn process_hotkey_event(event: HotkeyEvent, app_handle: &AppHandle) {
              let payload = StateUpdatePayload { state: FrontendRecordingState::LockedRecording, ..Default::default() };
              emit_state_update(app_handle, payload);
          }
+         PostEventAction::PauseAndEmitUi | PostEventAction::ResumeAndEmitUi => {
+             let pause = action_to_take == PostEventAction::PauseAndEmitUi;
+             match audio_manager_rs::set_capture_paused(app_handle, pause) {
+                 Ok(_) => {
+                     let ui_state = if pause { FrontendRecordingState::Paused } else { FrontendRecordingState::LockedRecording };
+                     let payload = StateUpdatePayload { state: ui_state, ..Default::default() };
+                     emit_state_update(app_handle, payload);
+                 }
+                 Err(e) => {
+                     // Capture side refused (e.g. stop already in flight) - undo the hotkey transition
+                     println!("[State Processor (Simplified V2)] Failed to {} capture: {}", if pause { "pause" } else { "resume" }, e);
+                     let mut state = HOTKEY_STATE.lock().unwrap();
+                     let expected = if pause { AppRecordingState::Paused } else { AppRecordingState::LockedRecording };
+                     if state.recording_state == expected {
+                         state.recording_state = if pause { AppRecordingState::LockedRecording } else { AppRecordingState::Paused };
+                     }
+                 }
+             }
+         }
+         PostEventAction::CancelAndEmitUi => {
+             // Tears down the capture thread, deletes the WAV and emits IDLE
+             if let Err(e) = audio_manager_rs::cancel_recording_internal(app_handle, true) {
+                 println!("[State Processor (Simplified V2)] Cancel failed: {}", e);
+             }
+         }
          PostEventAction::AuthRequired => {
              println!("[State Processor (Simplified V2)] Emitting auth-required event");
              app_handle.emit_all("fethr-auth-required", ()).unwrap_or_else(|e| {
@@ -560,6 +663,19 @@ fn signal_reset_complete(app_handle: AppHandle) { // Add AppHandle back
     // --- End Moved Reset Logic ---
 }
 
+// Called once a stopped take has been submitted to the transcription queue. Until then
+// HOTKEY_STATE sits in Transcribing and ignores presses; the queue keeps the jobs in order,
+// so the next dictation doesn't have to wait for this one to finish.
+pub fn hotkey_ready_for_next_take() {
+    let mut state = HOTKEY_STATE.lock().unwrap();
+    if state.recording_state == AppRecordingState::Transcribing {
+        state.recording_state = AppRecordingState::Idle;
+        state.press_start_time = None;
+        // hotkey_down_physically is kept: the release of a locked-stop press is still coming
+        println!("[State Processor (Simplified V2)] Take queued, hotkey ready for the next dictation.");
+    }
+}
+
 #[tauri::command]
 fn update_auth_state(is_authenticated: bool, user_id: Option<String>) -> Result<(), String> {
     let mut auth = AUTH_STATE.lock().unwrap();
@@ -574,6 +690,36 @@ fn update_auth_state(is_authenticated: bool, user_id: Option<String>) -> Result<
     Ok(())
 }
 
+// Pill button / frontend binding for the pause hotkey. Routed through the state thread
+// so HOTKEY_STATE and RECORDING_LIFECYCLE stay in step.
+#[tauri::command]
+fn toggle_pause_recording() -> Result<(), String> {
+    println!("[RUST CMD] toggle_pause_recording received.");
+    EVENT_SENDER.send(HotkeyEvent::TogglePause)
+        .map_err(|e| format!("Failed to send pause toggle: {}", e))
+}
+
+/// A take is recording, locked or paused as far as the hotkey is concerned
+pub fn hotkey_take_in_progress() -> bool {
+    let state = HOTKEY_STATE.lock().unwrap();
+    matches!(state.recording_state, AppRecordingState::Recording | AppRecordingState::LockedRecording | AppRecordingState::Paused)
+}
+
+/// Back to Idle after a cancel. Unlike force_reset_to_idle, hotkey_down_physically is kept:
+/// the key may still be held, and its release (or auto-repeat) must not start a new take.
+pub fn reset_after_cancel(app_handle: &AppHandle) {
+    {
+        let mut state = HOTKEY_STATE.lock().unwrap();
+        state.recording_state = AppRecordingState::Idle;
+        state.press_start_time = None;
+    }
+    translation::disarm();
+    ai_actions::disarm();
+    command_mode::disarm();
+    let payload = StateUpdatePayload { state: FrontendRecordingState::Idle, ..Default::default() };
+    emit_state_update(app_handle, payload);
+}
+
 #[tauri::command]
 fn force_reset_to_idle(app_handle: AppHandle) -> Result<(), String> {
     println!("[RUST CMD] force_reset_to_idle - FORCING all states to IDLE regardless of current state");
@@ -593,6 +739,9 @@ fn force_reset_to_idle(app_handle: AppHandle) -> Result<(), String> {
         state.hotkey_down_physically = false;
         println!("[RUST CMD] Hotkey state FORCED to IDLE");
     }
+    translation::disarm();
+    ai_actions::disarm();
+    command_mode::disarm();
     
     // Emit IDLE state to frontend
     let final_payload = StateUpdatePayload {
@@ -671,11 +820,25 @@ fn main() {
             // Manage audio recording state
             app.manage(Arc::new(Mutex::new(AudioRecordingState::default())));
 
+            // --- Recover recordings orphaned by a crash ---
+            // Runs before the hotkey listener starts so no live recording can be touched
+            println!("[RUST SETUP] Scanning for orphaned recordings...");
+            let recovered = recordings::recover_orphaned_recordings(&app.handle());
+            println!("[RUST SETUP] Orphan scan complete. {} recording(s) recovered.", recovered.len());
+            // --- End Recovery ---
+
             // --- Initialize Dictionary Manager ---
             println!("[RUST SETUP] Initializing DictionaryManager...");
             dictionary_manager::init_dictionary_manager(&app.handle());
             println!("[RUST SETUP] DictionaryManager initialized.");
             // --- End Dictionary Manager Init ---
+
+            // Migrates custom_prompts.json and registers action hotkeys before the listener starts
+            ai_actions::init(&app.handle());
+            command_mode::refresh_hotkey();
+            refresh_pause_hotkey();
+            local_llm::init(&app.handle());
+            model_selector::init(&app.handle());
             
             // --- Initialize Word Usage Tracker ---
             println!("[RUST SETUP] Initializing Word Usage Tracker...");
@@ -1000,8 +1163,28 @@ fn main() {
             // Core Commands:
             audio_manager_rs::start_backend_recording,
             audio_manager_rs::stop_backend_recording,
+            audio_manager_rs::cancel_backend_recording,
             transcription::transcribe_audio_file,
             transcription::get_history, // History command
+            transcription::retranscribe_history_entry,
+            transcription::list_derived_versions,
+            transcription::restore_derived_version,
+            transcription::get_low_confidence_spans,
+            transcription::correct_low_confidence_word,
+            transcription_queue::get_transcription_jobs,
+            transcription_queue::cancel_transcription_job,
+            model_manager::list_model_catalog,
+            model_manager::download_model,
+            model_manager::cancel_model_download,
+            model_manager::verify_model,
+            model_manager::delete_model,
+            model_manager::get_models_directory,
+            model_manager::set_models_directory,
+            model_selector::benchmark_models,
+            model_selector::get_model_benchmarks,
+            decoding_ab::run_decoding_ab_test,
+            hallucination_filter::get_hallucination_review_log,
+            hallucination_filter::clear_hallucination_review_log,
             update_history_entry,
             get_dashboard_stats,
             get_dashboard_stats_with_auth,
@@ -1012,15 +1195,31 @@ fn main() {
             edit_latest_transcription,
             toggle_recording_pill_visibility,
             ai_actions_manager::perform_ai_action, // <<< ADD NEW ONE
+            ai_actions_manager::cancel_ai_action,
             get_default_prompt_for_action,
             custom_prompts::save_custom_prompt,
             custom_prompts::get_custom_prompt,
             custom_prompts::delete_custom_prompt,
+            ai_actions::get_ai_actions,
+            ai_actions::save_ai_action,
+            ai_actions::delete_ai_action,
+            ai_actions::get_ai_action_migration_issues,
+            ai_actions::dismiss_ai_action_migration_issues,
+            local_llm::list_local_models,
+            local_llm::download_local_model,
+            local_llm::cancel_local_model_download,
+            local_llm::verify_local_model,
+            local_llm::delete_local_model,
+            ai_cache::clear_ai_cache,
+            style_profile::get_style_profile,
+            style_profile::save_style_profile,
+            style_profile::build_style_profile,
             // Utility Commands:
             write_to_clipboard_command,
             paste_text_to_cursor,
             signal_reset_complete,
             force_reset_to_idle,
+            toggle_pause_recording,
             update_auth_state,
             delete_file,
             // Settings Commands:
@@ -1057,7 +1256,11 @@ fn main() {
             // User statistics
             user_statistics::get_user_statistics,
             // Sound commands
-            sound_commands::get_sound_info
+            sound_commands::get_sound_info,
+            // Crash recovery
+            recordings::get_recovered_recordings,
+            recordings::transcribe_recovered_recording,
+            recordings::discard_recovered_recording
         ])
         .run(context)
         .expect("Error while running Fethr application");
@@ -1083,6 +1286,52 @@ fn callback(event: Event, _app_handle: &AppHandle) { // app_handle not needed he
                  println!("[RDEV Callback ERROR] Failed to send Release event: {}", e);
              }
         }
+        EventType::KeyPress(RdevKey::ShiftLeft) | EventType::KeyPress(RdevKey::ShiftRight) => {
+             translation::set_modifier_down(true);
+        }
+        EventType::KeyRelease(RdevKey::ShiftLeft) | EventType::KeyRelease(RdevKey::ShiftRight) => {
+             translation::set_modifier_down(false);
+        }
+        EventType::KeyPress(key) if key == CANCEL_HOTKEY => {
+             if let Err(e) = EVENT_SENDER.send(HotkeyEvent::Cancel) {
+                 println!("[RDEV Callback ERROR] Failed to send Cancel event: {}", e);
+             }
+        }
+        EventType::KeyPress(key) if key == *PAUSE_KEY.lock().unwrap() => {
+             if let Err(e) = EVENT_SENDER.send(HotkeyEvent::TogglePause) {
+                 println!("[RDEV Callback ERROR] Failed to send TogglePause event: {}", e);
+             }
+        }
+        // The command hotkey and action hotkeys dictate like AltGr; the take is then used
+        // as an instruction for the selected text, or run through the action
+        EventType::KeyPress(key) if command_mode::is_command_key(key) => {
+             command_mode::set_key_down(true);
+             if let Err(e) = EVENT_SENDER.send(HotkeyEvent::Press(event_time)) {
+                 println!("[RDEV Callback ERROR] Failed to send command Press event: {}", e);
+             }
+        }
+        EventType::KeyRelease(key) if command_mode::is_command_key(key) => {
+             command_mode::set_key_down(false);
+             if let Err(e) = EVENT_SENDER.send(HotkeyEvent::Release(event_time)) {
+                 println!("[RDEV Callback ERROR] Failed to send command Release event: {}", e);
+             }
+        }
+        EventType::KeyPress(key) => {
+             if let Some(action_id) = ai_actions::action_for_key(key) {
+                 ai_actions::set_pressed_action(Some(action_id));
+                 if let Err(e) = EVENT_SENDER.send(HotkeyEvent::Press(event_time)) {
+                     println!("[RDEV Callback ERROR] Failed to send action Press event: {}", e);
+                 }
+             }
+        }
+        EventType::KeyRelease(key) => {
+             if ai_actions::action_for_key(key).is_some() {
+                 ai_actions::set_pressed_action(None);
+                 if let Err(e) = EVENT_SENDER.send(HotkeyEvent::Release(event_time)) {
+                     println!("[RDEV Callback ERROR] Failed to send action Release event: {}", e);
+                 }
+             }
+        }
         _ => {} // Ignore other events
     }
 }
@@ -1133,11 +1382,41 @@ async fn get_settings(_app_handle: AppHandle) -> Result<AppSettings, String> {
     Ok(cloned_settings)
 }
 
+/// The pause hotkey must be a supported key that no other hotkey uses
+fn check_pause_hotkey(settings: &AppSettings, actions: &ai_actions::AiActionsStore) -> Result<(), String> {
+    let hotkey = settings.pause_hotkey.trim();
+    if ai_actions::parse_hotkey(hotkey).is_none() {
+        return Err(format!("Unsupported pause hotkey '{}'", hotkey));
+    }
+    if settings.command_mode.enabled && settings.command_mode.hotkey.trim() == hotkey {
+        return Err(format!("{} is already the command mode hotkey", hotkey));
+    }
+    if let Some(action) = actions.actions.iter().find(|action| action.hotkey.as_deref() == Some(hotkey)) {
+        return Err(format!("{} is already the hotkey of the AI action '{}'", hotkey, action.name));
+    }
+    Ok(())
+}
+
+/// Register the configured pause hotkey; called at startup and after the settings are saved
+fn refresh_pause_hotkey() {
+    let name = SETTINGS.lock().unwrap().pause_hotkey.clone();
+    let key = ai_actions::parse_hotkey(&name).unwrap_or_else(|| {
+        println!("[Hotkeys WARN] Unsupported pause hotkey '{}'; using Pause", name);
+        RdevKey::Pause
+    });
+    *PAUSE_KEY.lock().unwrap() = key;
+}
+
 #[tauri::command]
 async fn save_settings(settings: AppSettings, _app_handle: AppHandle) -> Result<(), String> {
     info!("[Settings] Saving new settings: model_name={}, language={}, auto_paste={}", 
           settings.model_name, settings.language, settings.auto_paste);
     
+    settings.validate()?;
+    let actions = ai_actions::load_store(&_app_handle)?;
+    command_mode::check_hotkey(&settings.command_mode, &actions)?;
+    check_pause_hotkey(&settings, &actions)?;
+
     // Access settings through the mutex
     let mut settings_guard = SETTINGS.lock()
         .map_err(|_| "Failed to lock settings mutex".to_string())?;
@@ -1148,6 +1427,9 @@ async fn save_settings(settings: AppSettings, _app_handle: AppHandle) -> Result<
     // Persist settings to file
     settings_guard.save()
         .map_err(|e| format!("Failed to save settings to file: {}", e))?;
+    drop(settings_guard); // refresh_hotkey reads SETTINGS
+    command_mode::refresh_hotkey();
+    refresh_pause_hotkey();
     
     info!("[Settings] Settings saved successfully");
     Ok(())
@@ -1157,40 +1439,8 @@ async fn save_settings(settings: AppSettings, _app_handle: AppHandle) -> Result<
 async fn get_available_models(_app_handle: AppHandle) -> Result<Vec<String>, String> {
     info!("[Settings] Getting available Whisper models");
     
-    // For debug builds, check vendor/models directory in the project
-    let model_path = if cfg!(debug_assertions) {
-        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        let vendor_dir = manifest_dir.join("vendor").join("models");
-        info!("[Settings] Debug mode - looking for models in: {:?}", vendor_dir);
-        vendor_dir
-    } else {
-        // For release builds, use Tauri's resource resolver
-        let resource_path = _app_handle.path_resolver()
-            .resolve_resource("vendor/models")
-            .ok_or_else(|| "Could not resolve resource path vendor/models".to_string())?;
-        info!("[Settings] Release mode - looking for models in: {:?}", resource_path);
-        resource_path
-    };
-    
-    // Read the directory contents
-    let entries = fs::read_dir(&model_path)
-        .map_err(|e| format!("Failed to read models directory {:?}: {}", model_path, e))?;
-    
-    // Collect model filenames
-    let mut model_files = Vec::new();
-    for entry_result in entries {
-        let entry = entry_result.map_err(|e| format!("Error reading directory entry: {}", e))?;
-        let path = entry.path();
-        if path.is_file() {
-            if let Some(filename_os) = path.file_name() {
-                if let Some(filename_str) = filename_os.to_str() {
-                    if filename_str.ends_with(".bin") {
-                        model_files.push(filename_str.to_string());
-                    }
-                }
-            }
-        }
-    }
+    // Downloaded models (model manager dir) plus the ones bundled in vendor/models
+    let model_files = model_manager::list_installed_model_files(&_app_handle);
     
     info!("[Settings] Found models: {:?}", model_files);
     Ok(model_files)
@@ -1202,6 +1452,7 @@ async fn update_history_entry(app_handle: AppHandle, timestamp: String, new_text
     println!("Backend: Received update request for timestamp: {}", timestamp); // Add logging
 
     let history_path = get_history_path(&app_handle)?;
+    let _history_guard = transcription::lock_history();
 
     // Read the existing history
     let history_json = fs::read_to_string(&history_path)
@@ -1219,6 +1470,8 @@ async fn update_history_entry(app_handle: AppHandle, timestamp: String, new_text
         if entry.timestamp == timestamp {
             println!("Backend: Found entry, updating text."); // Add logging
             entry.text = new_text.clone(); // Update the text
+            // Marks hand edits for the style profile; machine rewrites don't go through here
+            entry.extra.insert("edited".to_string(), serde_json::Value::Bool(true));
             found = true;
             break;
         }
@@ -0,0 +1,83 @@
+// src-tauri/src/mock_http.rs
+//
+// Test-only HTTP server for the transcription backend and AI provider clients.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+
+/// Serve one request with `status` and a JSON `body`; the received request comes back on the channel
+pub fn mock_server(status: &'static str, body: &'static str) -> (String, mpsc::Receiver<String>) {
+    mock_server_with(status, "application/json", body)
+}
+
+pub fn mock_server_with(status: &'static str, content_type: &'static str, body: &'static str) -> (String, mpsc::Receiver<String>) {
+    mock_sequence(vec![MockResponse { status, headers: "", content_type, body }])
+}
+
+pub struct MockResponse {
+    pub status: &'static str,
+    pub headers: &'static str, // Extra header lines, each ending in \r\n
+    pub content_type: &'static str,
+    pub body: &'static str,
+}
+
+/// Serve one request per response, in order; each received request comes back on the channel
+pub fn mock_sequence(responses: Vec<MockResponse>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for response in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let request = serve(stream, &response);
+            let _ = sender.send(request); // The test may not be listening
+        }
+    });
+    (url, receiver)
+}
+
+/// Accepts connections and never answers
+pub fn mock_silent_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let mut open = Vec::new();
+        while let Ok((stream, _)) = listener.accept() {
+            open.push(stream);
+        }
+    });
+    url
+}
+
+fn serve(mut stream: TcpStream, response: &MockResponse) -> String {
+    let mut received = Vec::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let n = stream.read(&mut buffer).unwrap();
+        received.extend_from_slice(&buffer[..n]);
+        let text = String::from_utf8_lossy(&received);
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            if text[..header_end].to_ascii_lowercase().contains("transfer-encoding: chunked") {
+                if text.ends_with("0\r\n\r\n") {
+                    break;
+                }
+                continue;
+            }
+            let content_length = text[..header_end].lines()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                .unwrap_or(0);
+            if received.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+        if n == 0 {
+            break;
+        }
+    }
+    let MockResponse { status, headers, content_type, body } = response;
+    let response = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                           status, content_type, headers, body.len(), body);
+    stream.write_all(response.as_bytes()).unwrap();
+    String::from_utf8_lossy(&received).to_string()
+}
@@ -0,0 +1,678 @@
+// src-tauri/src/model_manager.rs
+//
+// Whisper model manager: catalog of ggml models, resumable downloads into a
+// user-writable models directory, SHA256 verification, deletion and relocation.
+//
+// Bundled models (vendor/models) stay where they are and are read-only. Downloaded
+// models live in `settings.models_dir` or <app data>/models. A small manifest next to
+// the models records the verified hash of each download so `verify_model` works offline.
+//
+// Checksums: catalog entries pin the SHA256 of the published file and only that hash is
+// trusted for them; an entry without a pin can't be downloaded and is only listed once
+// it's installed (bundled or copied in by hand). The download host's
+// `x-linked-etag` header (Hugging Face serves the LFS SHA256 there) is only consulted for
+// files that aren't in a catalog. A download is never accepted without an expected hash.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use futures_util::StreamExt;
+use log::{info, warn, error};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+
+use crate::config::SETTINGS;
+
+pub const DEFAULT_DOWNLOAD_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+pub(crate) const PARTIAL_SUFFIX: &str = ".part";
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// One downloadable ggml model
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCatalogEntry {
+    pub id: &'static str,
+    pub file_name: &'static str,
+    pub approx_size_mb: u32,
+    pub multilingual: bool,
+    pub quantization: Option<&'static str>,
+    pub sha256: Option<&'static str>, // None = not pinned yet, download refused
+}
+
+const fn model(
+    id: &'static str,
+    file_name: &'static str,
+    approx_size_mb: u32,
+    multilingual: bool,
+    quantization: Option<&'static str>,
+    sha256: Option<&'static str>,
+) -> ModelCatalogEntry {
+    ModelCatalogEntry { id, file_name, approx_size_mb, multilingual, quantization, sha256 }
+}
+
+/// Ordered smallest/fastest to largest/most accurate
+pub static MODEL_CATALOG: &[ModelCatalogEntry] = &[
+    model("tiny.en-q5_1", "ggml-tiny.en-q5_1.bin", 31, false, Some("q5_1"), None),
+    model("tiny.en", "ggml-tiny.en.bin", 75, false, None, Some("921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f")),
+    model("tiny-q5_1", "ggml-tiny-q5_1.bin", 31, true, Some("q5_1"), None),
+    model("tiny", "ggml-tiny.bin", 75, true, None, Some("be07e048e1e599ad46341c8d2a135645097a538221678b7acdd1b1919c6e1b21")),
+    model("base.en-q5_1", "ggml-base.en-q5_1.bin", 57, false, Some("q5_1"), None),
+    model("base.en", "ggml-base.en.bin", 142, false, None, Some("a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002")),
+    model("base-q5_1", "ggml-base-q5_1.bin", 57, true, Some("q5_1"), None),
+    model("base", "ggml-base.bin", 142, true, None, Some("60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe")),
+    model("small.en-q5_1", "ggml-small.en-q5_1.bin", 181, false, Some("q5_1"), None),
+    model("small.en", "ggml-small.en.bin", 466, false, None, Some("c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d")),
+    model("small-q5_1", "ggml-small-q5_1.bin", 181, true, Some("q5_1"), None),
+    model("small", "ggml-small.bin", 466, true, None, Some("1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1fffea987b")),
+    model("medium.en-q5_0", "ggml-medium.en-q5_0.bin", 514, false, Some("q5_0"), None),
+    model("medium.en", "ggml-medium.en.bin", 1500, false, None, Some("cc37e93478338ec7700281a7ac30a10128929eb8f427dda2e865faa8f6da4356")),
+    model("medium-q5_0", "ggml-medium-q5_0.bin", 514, true, Some("q5_0"), None),
+    model("medium", "ggml-medium.bin", 1500, true, None, Some("6c14d5adee5f86394037b4e4e8b59f1673b6cee10e3cf0b11bbdbee79c156208")),
+    model("large-v3-turbo-q5_0", "ggml-large-v3-turbo-q5_0.bin", 547, true, Some("q5_0"), None),
+    model("large-v3-turbo", "ggml-large-v3-turbo.bin", 1500, true, None, Some("1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69")),
+    model("large-v3-q5_0", "ggml-large-v3-q5_0.bin", 1080, true, Some("q5_0"), None),
+    model("large-v3", "ggml-large-v3.bin", 2900, true, None, Some("64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2")),
+];
+
+pub fn find_catalog_entry(model_id: &str) -> Option<&'static ModelCatalogEntry> {
+    MODEL_CATALOG.iter().find(|m| m.id == model_id || m.file_name == model_id)
+}
+
+/// Catalog entry plus where (if anywhere) it is installed
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelStatus {
+    #[serde(flatten)]
+    pub entry: ModelCatalogEntry,
+    pub downloaded: bool,
+    pub bundled: bool,
+    pub downloading: bool,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDownloadProgress {
+    pub model_id: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+}
+
+// Cancel flags for in-flight downloads, keyed by model id
+static ACTIVE_DOWNLOADS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// --- Directories ---
+
+/// Where downloaded models live (created if missing)
+pub fn models_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let configured = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        settings_guard.models_dir.clone()
+    };
+    let dir = match configured {
+        Some(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+        _ => app_handle.path_resolver().app_data_dir()
+            .ok_or_else(|| "Failed to get app data directory".to_string())?
+            .join("models"),
+    };
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create models directory {}: {}", dir.display(), e))?;
+    }
+    Ok(dir)
+}
+
+/// Read-only models shipped with the app (Debug: source vendor dir, Release: resources)
+pub fn bundled_models_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    if cfg!(debug_assertions) {
+        Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("vendor").join("models"))
+    } else {
+        app_handle.path_resolver().resolve_resource("vendor/models")
+    }
+}
+
+/// A downloaded copy of `model_name` (file name), if one exists. Preferred over the bundled copy.
+pub fn downloaded_model_path(app_handle: &AppHandle, model_name: &str) -> Option<PathBuf> {
+    let path = models_dir(app_handle).ok()?.join(model_name);
+    if path.is_file() { Some(path) } else { None }
+}
+
+fn list_bin_files(dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    if name.ends_with(".bin") {
+                        files.push(name.to_string());
+                    }
+                }
+            }
+        }
+    }
+    files
+}
+
+/// File names of all usable models, downloaded and bundled, deduplicated
+pub fn list_installed_model_files(app_handle: &AppHandle) -> Vec<String> {
+    let mut files = Vec::new();
+    if let Ok(dir) = models_dir(app_handle) {
+        files.extend(list_bin_files(&dir));
+    }
+    if let Some(dir) = bundled_models_dir(app_handle) {
+        files.extend(list_bin_files(&dir));
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+// --- Manifest of verified hashes ---
+
+pub(crate) fn read_manifest(dir: &Path) -> HashMap<String, String> {
+    fs::read_to_string(dir.join(MANIFEST_FILE_NAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn write_manifest(dir: &Path, manifest: &HashMap<String, String>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize model manifest: {}", e))?;
+    fs::write(dir.join(MANIFEST_FILE_NAME), json)
+        .map_err(|e| format!("Failed to write model manifest: {}", e))
+}
+
+// --- Hashing ---
+
+/// SHA256 of a file as lowercase hex
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    use std::io::Read;
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Accept `"<64 hex>"`, `W/"<64 hex>"` or a bare hash; anything else isn't a SHA256
+pub fn parse_sha256_etag(value: &str) -> Option<String> {
+    let trimmed = value.trim().trim_start_matches("W/").trim_matches('"').to_lowercase();
+    if trimmed.len() == 64 && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(trimmed)
+    } else {
+        None
+    }
+}
+
+/// Ask the host for the LFS hash without following the redirect to the CDN
+async fn fetch_expected_sha256(url: &str) -> Result<Option<String>, String> {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let response = client.head(url).send().await
+        .map_err(|e| format!("Failed to query model checksum: {}", e))?;
+    let headers = response.headers();
+    let hash = ["x-linked-etag", "etag"].iter()
+        .filter_map(|name| headers.get(*name))
+        .filter_map(|value| value.to_str().ok())
+        .find_map(parse_sha256_etag);
+    Ok(hash)
+}
+
+// --- Download ---
+
+/// Download `url` to `dest_dir/file_name`, resuming a previous `.part` file if present.
+/// The hash covers the whole file (resumed bytes included); on mismatch the file is deleted.
+/// `expected_sha256` is the pinned hash; pass None only for files outside the catalogs,
+/// which fall back to the host's etag. Returns the verified SHA256. No Tauri dependency so it can run against a local stand-in server.
+pub async fn download_and_verify<F>(
+    url: &str,
+    dest_dir: &Path,
+    file_name: &str,
+    expected_sha256: Option<&str>,
+    cancel_flag: &AtomicBool,
+    mut on_progress: F,
+) -> Result<String, String>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let expected = match expected_sha256 {
+        Some(hash) => hash.to_lowercase(),
+        None => fetch_expected_sha256(url).await?
+            .ok_or_else(|| format!("No checksum available for {}; refusing unverified download", file_name))?,
+    };
+
+    let final_path = dest_dir.join(file_name);
+    let partial_path = dest_dir.join(format!("{}{}", file_name, PARTIAL_SUFFIX));
+    let already_have = fs::metadata(&partial_path).map(|m| m.len()).unwrap_or(0);
+
+    let client = reqwest::Client::new();
+    let mut request = client.get(url);
+    if already_have > 0 {
+        info!("[ModelManager] Resuming {} from byte {}", file_name, already_have);
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", already_have));
+    }
+    let response = request.send().await
+        .map_err(|e| format!("Failed to start download: {}", e))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && already_have > 0 {
+        // The .part already holds every byte (an earlier attempt stopped before the rename).
+        // It is only promoted if it hashes right; otherwise it is discarded.
+        return promote_partial(&partial_path, &final_path, file_name, &expected).await;
+    }
+    let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    if !status.is_success() {
+        return Err(format!("Download failed with HTTP {}", status));
+    }
+    // A 200 to a range request means the server ignored it: start over
+    let mut downloaded = if resumed { already_have } else { 0 };
+    let total = response.content_length().map(|len| len + downloaded);
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&partial_path)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", partial_path.display(), e))?;
+
+    on_progress(downloaded, total);
+    let mut last_emit = Instant::now();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        if cancel_flag.load(Ordering::SeqCst) {
+            // Keep the .part file so the next attempt resumes
+            return Err("Download cancelled".to_string());
+        }
+        let chunk = chunk.map_err(|e| format!("Download interrupted: {}", e))?;
+        file.write_all(&chunk).await
+            .map_err(|e| format!("Failed to write model file: {}", e))?;
+        downloaded += chunk.len() as u64;
+        if last_emit.elapsed() >= PROGRESS_EMIT_INTERVAL {
+            on_progress(downloaded, total);
+            last_emit = Instant::now();
+        }
+    }
+    file.flush().await.map_err(|e| format!("Failed to flush model file: {}", e))?;
+    drop(file);
+    on_progress(downloaded, total);
+
+    promote_partial(&partial_path, &final_path, file_name, &expected).await
+}
+
+/// Hash the finished `.part` file and move it into place, or delete it on mismatch
+async fn promote_partial(partial_path: &Path, final_path: &Path, file_name: &str, expected: &str) -> Result<String, String> {
+    let partial_for_hash = partial_path.to_path_buf();
+    let actual = tokio::task::spawn_blocking(move || sha256_file(&partial_for_hash))
+        .await
+        .map_err(|e| format!("Checksum task failed: {}", e))??;
+    if actual != expected {
+        let _ = fs::remove_file(partial_path);
+        return Err(format!("Checksum mismatch for {}: expected {}, got {}", file_name, expected, actual));
+    }
+
+    fs::rename(partial_path, final_path)
+        .map_err(|e| format!("Failed to move model into place: {}", e))?;
+    Ok(actual)
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub async fn list_model_catalog(app_handle: AppHandle) -> Result<Vec<ModelStatus>, String> {
+    let downloaded_dir = models_dir(&app_handle)?;
+    let bundled_dir = bundled_models_dir(&app_handle);
+    let active: Vec<String> = ACTIVE_DOWNLOADS.lock().unwrap().keys().cloned().collect();
+
+    Ok(MODEL_CATALOG.iter().filter_map(|entry| {
+        let downloaded_path = downloaded_dir.join(entry.file_name);
+        let bundled_path = bundled_dir.as_ref().map(|dir| dir.join(entry.file_name));
+        let downloaded = downloaded_path.is_file();
+        let bundled = bundled_path.as_ref().map_or(false, |p| p.is_file());
+        // Not offered until its hash is pinned: download_model would refuse it
+        if entry.sha256.is_none() && !downloaded && !bundled {
+            return None;
+        }
+        let path = if downloaded {
+            Some(downloaded_path.display().to_string())
+        } else if bundled {
+            bundled_path.map(|p| p.display().to_string())
+        } else {
+            None
+        };
+        Some(ModelStatus {
+            entry: entry.clone(),
+            downloaded,
+            bundled,
+            downloading: active.iter().any(|id| id == entry.id),
+            path,
+        })
+    }).collect())
+}
+
+#[tauri::command]
+pub async fn download_model(app_handle: AppHandle, model_id: String) -> Result<String, String> {
+    let entry = find_catalog_entry(&model_id)
+        .ok_or_else(|| format!("Unknown model: {}", model_id))?;
+    let pinned = entry.sha256
+        .ok_or_else(|| format!("{} has no pinned checksum yet and can't be downloaded", entry.id))?;
+    let dir = models_dir(&app_handle)?;
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = ACTIVE_DOWNLOADS.lock().unwrap();
+        if active.contains_key(entry.id) {
+            return Err(format!("{} is already downloading", entry.id));
+        }
+        active.insert(entry.id.to_string(), cancel_flag.clone());
+    }
+    scopeguard::defer!({
+        ACTIVE_DOWNLOADS.lock().unwrap().remove(entry.id);
+    });
+
+    let url = format!("{}/{}", DEFAULT_DOWNLOAD_BASE_URL, entry.file_name);
+    info!("[ModelManager] Downloading {} from {}", entry.id, url);
+
+    let progress_handle = app_handle.clone();
+    let result = download_and_verify(&url, &dir, entry.file_name, Some(pinned), &cancel_flag, |downloaded, total| {
+        let payload = ModelDownloadProgress {
+            model_id: entry.id.to_string(),
+            downloaded_bytes: downloaded,
+            total_bytes: total,
+        };
+        let _ = progress_handle.emit_all("fethr-model-download-progress", payload);
+    }).await;
+
+    match result {
+        Ok(hash) => {
+            let mut manifest = read_manifest(&dir);
+            manifest.insert(entry.file_name.to_string(), hash);
+            write_manifest(&dir, &manifest)?;
+            info!("[ModelManager] {} downloaded and verified", entry.id);
+            let _ = app_handle.emit_all("fethr-model-download-complete", entry.id);
+            Ok(entry.file_name.to_string())
+        }
+        Err(e) => {
+            error!("[ModelManager] Download of {} failed: {}", entry.id, e);
+            let payload = serde_json::json!({ "model_id": entry.id, "error": e });
+            let _ = app_handle.emit_all("fethr-model-download-failed", payload);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_model_download(model_id: String) -> Result<(), String> {
+    let active = ACTIVE_DOWNLOADS.lock().unwrap();
+    let flag = active.get(&model_id)
+        .ok_or_else(|| format!("{} is not downloading", model_id))?;
+    flag.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Re-hash a downloaded model against the catalog hash or the hash recorded at download time
+#[tauri::command]
+pub async fn verify_model(app_handle: AppHandle, model_id: String) -> Result<bool, String> {
+    let entry = find_catalog_entry(&model_id)
+        .ok_or_else(|| format!("Unknown model: {}", model_id))?;
+    let dir = models_dir(&app_handle)?;
+    let path = dir.join(entry.file_name);
+    if !path.is_file() {
+        return Err(format!("{} is not downloaded", entry.id));
+    }
+    let expected = entry.sha256.map(|h| h.to_string())
+        .or_else(|| read_manifest(&dir).get(entry.file_name).cloned())
+        .ok_or_else(|| format!("No recorded checksum for {}", entry.id))?;
+    let actual = tokio::task::spawn_blocking(move || sha256_file(&path))
+        .await
+        .map_err(|e| format!("Checksum task failed: {}", e))??;
+    let ok = actual == expected;
+    if !ok {
+        warn!("[ModelManager] {} failed verification: expected {}, got {}", entry.id, expected, actual);
+    }
+    Ok(ok)
+}
+
+#[tauri::command]
+pub async fn delete_model(app_handle: AppHandle, model_id: String) -> Result<(), String> {
+    let entry = find_catalog_entry(&model_id)
+        .ok_or_else(|| format!("Unknown model: {}", model_id))?;
+    let current_model = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        settings_guard.model_name.clone()
+    };
+    let dir = models_dir(&app_handle)?;
+    let path = dir.join(entry.file_name);
+    let bundled = bundled_models_dir(&app_handle)
+        .map_or(false, |d| d.join(entry.file_name).is_file());
+    if current_model == entry.file_name && !bundled {
+        return Err("Cannot delete the model currently in use. Switch models first.".to_string());
+    }
+
+    if path.is_file() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+    }
+    let _ = fs::remove_file(dir.join(format!("{}{}", entry.file_name, PARTIAL_SUFFIX)));
+    let mut manifest = read_manifest(&dir);
+    if manifest.remove(entry.file_name).is_some() {
+        write_manifest(&dir, &manifest)?;
+    }
+    info!("[ModelManager] Deleted {}", entry.id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_models_directory(app_handle: AppHandle) -> Result<String, String> {
+    Ok(models_dir(&app_handle)?.display().to_string())
+}
+
+/// Point downloads at a new directory. `move_existing` carries over downloaded models and the manifest.
+#[tauri::command]
+pub async fn set_models_directory(app_handle: AppHandle, path: String, move_existing: bool) -> Result<(), String> {
+    if !ACTIVE_DOWNLOADS.lock().unwrap().is_empty() {
+        return Err("Cannot change the models directory while a download is running".to_string());
+    }
+    let new_dir = PathBuf::from(&path);
+    fs::create_dir_all(&new_dir)
+        .map_err(|e| format!("Failed to create {}: {}", new_dir.display(), e))?;
+    // Make sure we can actually write there before committing to it
+    let probe = new_dir.join(".fethr_write_test");
+    fs::write(&probe, b"ok").map_err(|e| format!("Directory {} is not writable: {}", new_dir.display(), e))?;
+    let _ = fs::remove_file(&probe);
+
+    let old_dir = models_dir(&app_handle)?;
+    if move_existing && old_dir != new_dir {
+        let mut moved = list_bin_files(&old_dir);
+        moved.push(MANIFEST_FILE_NAME.to_string());
+        for name in moved {
+            let from = old_dir.join(&name);
+            if !from.is_file() {
+                continue;
+            }
+            let to = new_dir.join(&name);
+            // rename fails across drives; fall back to copy + delete
+            if fs::rename(&from, &to).is_err() {
+                fs::copy(&from, &to).map_err(|e| format!("Failed to move {}: {}", name, e))?;
+                let _ = fs::remove_file(&from);
+            }
+            info!("[ModelManager] Moved {} to {}", name, new_dir.display());
+        }
+    }
+
+    let mut settings_guard = SETTINGS.lock().map_err(|_| "Failed to lock settings mutex".to_string())?;
+    settings_guard.models_dir = Some(path);
+    settings_guard.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // Minimal HTTP stand-in: serves `body`, honours `Range: bytes=N-`, and answers HEAD with an etag
+    async fn serve(body: Vec<u8>, etag: Option<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await { Ok(s) => s, Err(_) => return };
+                let body = body.clone();
+                let etag = etag.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let start = request.lines()
+                        .find_map(|l| l.to_lowercase().strip_prefix("range: bytes=").map(|r| r.trim_end_matches('-').to_string()))
+                        .and_then(|s| s.parse::<usize>().ok());
+                    let etag_header = etag.map(|e| format!("x-linked-etag: \"{}\"\r\n", e)).unwrap_or_default();
+                    if request.starts_with("HEAD") {
+                        let head = format!("HTTP/1.1 200 OK\r\n{}content-length: {}\r\nconnection: close\r\n\r\n", etag_header, body.len());
+                        let _ = socket.write_all(head.as_bytes()).await;
+                        return;
+                    }
+                    let (status, slice) = match start {
+                        Some(s) if s < body.len() => ("206 Partial Content", &body[s..]),
+                        Some(_) => ("416 Range Not Satisfiable", &body[..0]),
+                        None => ("200 OK", &body[..]),
+                    };
+                    let head = format!("HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n", status, slice.len());
+                    let _ = socket.write_all(head.as_bytes()).await;
+                    let _ = socket.write_all(slice).await;
+                });
+            }
+        });
+        format!("http://{}/ggml-test.bin", addr)
+    }
+
+    fn sha256_hex(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    fn test_body() -> Vec<u8> {
+        (0..200_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn downloads_and_verifies_catalog_hash() {
+        let body = test_body();
+        let url = serve(body.clone(), None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let flag = AtomicBool::new(false);
+        let mut last = 0;
+        let hash = download_and_verify(&url, dir.path(), "ggml-test.bin", Some(&sha256_hex(&body)), &flag, |d, _| last = d)
+            .await.unwrap();
+        assert_eq!(hash, sha256_hex(&body));
+        assert_eq!(last, body.len() as u64);
+        assert_eq!(fs::read(dir.path().join("ggml-test.bin")).unwrap(), body);
+        assert!(!dir.path().join("ggml-test.bin.part").exists());
+    }
+
+    #[tokio::test]
+    async fn resumes_from_partial_file() {
+        let body = test_body();
+        let url = serve(body.clone(), None).await;
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("ggml-test.bin.part"), &body[..50_000]).unwrap();
+        let flag = AtomicBool::new(false);
+        let mut first = None;
+        download_and_verify(&url, dir.path(), "ggml-test.bin", Some(&sha256_hex(&body)), &flag, |d, t| {
+            if first.is_none() { first = Some((d, t)); }
+        }).await.unwrap();
+        assert_eq!(first, Some((50_000, Some(body.len() as u64))));
+        assert_eq!(fs::read(dir.path().join("ggml-test.bin")).unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn checksum_mismatch_deletes_download() {
+        let body = test_body();
+        let url = serve(body, None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let flag = AtomicBool::new(false);
+        let wrong = "0".repeat(64);
+        let err = download_and_verify(&url, dir.path(), "ggml-test.bin", Some(&wrong), &flag, |_, _| {})
+            .await.unwrap_err();
+        assert!(err.contains("Checksum mismatch"));
+        assert!(!dir.path().join("ggml-test.bin").exists());
+        assert!(!dir.path().join("ggml-test.bin.part").exists());
+    }
+
+    #[tokio::test]
+    async fn complete_partial_is_verified_before_promotion() {
+        let body = test_body();
+        let url = serve(body.clone(), None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let flag = AtomicBool::new(false);
+        fs::write(dir.path().join("ggml-test.bin.part"), &body).unwrap();
+        let hash = download_and_verify(&url, dir.path(), "ggml-test.bin", Some(&sha256_hex(&body)), &flag, |_, _| {})
+            .await.unwrap();
+        assert_eq!(hash, sha256_hex(&body));
+        assert_eq!(fs::read(dir.path().join("ggml-test.bin")).unwrap(), body);
+
+        // Same length, wrong bytes: the server answers 416 and the .part must not be promoted
+        let corrupt = vec![0u8; body.len()];
+        fs::write(dir.path().join("other.bin.part"), &corrupt).unwrap();
+        let err = download_and_verify(&url, dir.path(), "other.bin", Some(&sha256_hex(&body)), &flag, |_, _| {})
+            .await.unwrap_err();
+        assert!(err.contains("Checksum mismatch"));
+        assert!(!dir.path().join("other.bin").exists());
+        assert!(!dir.path().join("other.bin.part").exists());
+    }
+
+    #[tokio::test]
+    async fn uses_host_etag_for_files_outside_the_catalog() {
+        let body = test_body();
+        let url = serve(body.clone(), Some(sha256_hex(&body))).await;
+        let dir = tempfile::tempdir().unwrap();
+        let flag = AtomicBool::new(false);
+        let hash = download_and_verify(&url, dir.path(), "ggml-test.bin", None, &flag, |_, _| {}).await.unwrap();
+        assert_eq!(hash, sha256_hex(&body));
+    }
+
+    #[tokio::test]
+    async fn refuses_download_without_any_checksum() {
+        let url = serve(test_body(), None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let flag = AtomicBool::new(false);
+        assert!(download_and_verify(&url, dir.path(), "ggml-test.bin", None, &flag, |_, _| {}).await.is_err());
+    }
+
+    #[test]
+    fn parses_etag_forms() {
+        let hash = "a".repeat(64);
+        assert_eq!(parse_sha256_etag(&format!("\"{}\"", hash)), Some(hash.clone()));
+        assert_eq!(parse_sha256_etag(&format!("W/\"{}\"", hash.to_uppercase())), Some(hash));
+        assert_eq!(parse_sha256_etag("\"abc123\""), None);
+    }
+
+    #[test]
+    fn catalog_ids_are_unique_and_resolvable() {
+        for entry in MODEL_CATALOG {
+            assert_eq!(find_catalog_entry(entry.id).unwrap().file_name, entry.file_name);
+            assert_eq!(find_catalog_entry(entry.file_name).unwrap().id, entry.id);
+        }
+    }
+
+    #[test]
+    fn pinned_hashes_are_sha256_hex() {
+        for hash in MODEL_CATALOG.iter().filter_map(|entry| entry.sha256) {
+            assert_eq!(parse_sha256_etag(hash).as_deref(), Some(hash));
+        }
+    }
+}
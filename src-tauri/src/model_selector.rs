@@ -0,0 +1,388 @@
+// src-tauri/src/model_selector.rs
+//
+// "auto" model mode: pick a whisper model per recording.
+//
+// Every installed model is benchmarked once on a bundled speech sample. whisper.cpp pads
+// audio to 30 s windows, so the cost of one window predicts latency well:
+// latency ≈ seconds_per_window × number of windows. Per recording we take the largest
+// model whose predicted latency fits the configured budget. Until a benchmark exists
+// (or after the core count changes) a core-count heuristic is used.
+//
+// Benchmarks run as transcription queue jobs, so they never compete with a dictation for
+// the CPU: either on request (`benchmark_models`) or, in auto mode, once the app has been
+// idle for a while. An idle run gives way as soon as a recording starts or a dictation is
+// queued behind it; models it didn't get to are benchmarked the next time the app is idle.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use log::{info, warn, error};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::config::SETTINGS;
+use crate::language::is_english_only_model;
+use crate::model_manager;
+use crate::transcription;
+use crate::transcription_queue;
+
+pub const AUTO_MODEL: &str = "auto";
+const BENCHMARK_FILE_NAME: &str = "model_benchmarks.json";
+// ~10 s of clear English speech, 16 kHz mono: shorter than one window
+const BENCHMARK_SAMPLE: &str = "resources/benchmark/speech_sample.wav";
+const WHISPER_WINDOW_SECONDS: f64 = 30.0;
+const FALLBACK_MODEL: &str = "ggml-tiny.en.bin";
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const YIELD_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+static BENCHMARK_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelBenchmark {
+    pub model_name: String,
+    pub seconds_per_window: f64, // Wall time to transcribe one 30 s window
+    pub real_time_factor: f64,   // seconds_per_window / 30
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BenchmarkResults {
+    pub cpu_cores: usize,
+    pub benchmarked_at: Option<DateTime<Utc>>,
+    pub models: Vec<ModelBenchmark>,
+}
+
+pub fn cpu_cores() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Relative model capability: catalog size, unknown models rank lowest
+fn model_rank(model_name: &str) -> u32 {
+    model_manager::find_catalog_entry(model_name).map_or(0, |entry| entry.approx_size_mb)
+}
+
+pub fn predicted_latency_seconds(seconds_per_window: f64, audio_seconds: f64) -> f64 {
+    let windows = (audio_seconds / WHISPER_WINDOW_SECONDS).ceil().max(1.0);
+    seconds_per_window * windows
+}
+
+/// Largest benchmarked model that fits the budget, else the fastest one.
+/// English-only models are skipped unless `english_only_ok`.
+pub fn choose_model(
+    benchmarks: &[ModelBenchmark],
+    audio_seconds: f64,
+    latency_budget_seconds: f64,
+    english_only_ok: bool,
+) -> Option<String> {
+    let candidates: Vec<&ModelBenchmark> = benchmarks.iter()
+        .filter(|b| english_only_ok || !is_english_only_model(&b.model_name))
+        .collect();
+
+    let fitting = candidates.iter()
+        .filter(|b| predicted_latency_seconds(b.seconds_per_window, audio_seconds) <= latency_budget_seconds)
+        .max_by(|a, b| model_rank(&a.model_name).cmp(&model_rank(&b.model_name))
+            .then(b.seconds_per_window.total_cmp(&a.seconds_per_window))); // Same size: faster wins
+    if let Some(best) = fitting {
+        return Some(best.model_name.clone());
+    }
+
+    candidates.iter()
+        .min_by(|a, b| a.seconds_per_window.total_cmp(&b.seconds_per_window))
+        .map(|b| b.model_name.clone())
+}
+
+/// Pre-benchmark guess: short clips get the smallest model, long ones scale with cores
+pub fn heuristic_model(installed: &[String], cores: usize, audio_seconds: f64, english_only_ok: bool) -> Option<String> {
+    let size_limit_mb = if audio_seconds < 15.0 {
+        80
+    } else {
+        match cores {
+            0..=4 => 80,
+            5..=8 => 150,
+            _ => 500,
+        }
+    };
+    let candidates: Vec<&String> = installed.iter()
+        .filter(|name| english_only_ok || !is_english_only_model(name))
+        .collect();
+
+    candidates.iter()
+        .filter(|name| model_rank(name) > 0 && model_rank(name) <= size_limit_mb)
+        .max_by_key(|name| model_rank(name))
+        .or_else(|| candidates.iter().min_by_key(|name| model_rank(name)))
+        .map(|name| name.to_string())
+}
+
+// --- Persistence ---
+
+fn benchmark_path(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let config_dir = app_handle.path_resolver().app_config_dir()
+        .ok_or_else(|| "Failed to get app config directory".to_string())?;
+    std::fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    Ok(config_dir.join(BENCHMARK_FILE_NAME))
+}
+
+fn load_benchmarks(app_handle: &AppHandle) -> Option<BenchmarkResults> {
+    let content = std::fs::read_to_string(benchmark_path(app_handle).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_benchmarks(app_handle: &AppHandle, results: &BenchmarkResults) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(results)
+        .map_err(|e| format!("Failed to serialize benchmarks: {}", e))?;
+    std::fs::write(benchmark_path(app_handle)?, json)
+        .map_err(|e| format!("Failed to write benchmarks: {}", e))
+}
+
+// --- Selection ---
+
+/// Duration of a WAV from its header
+pub fn wav_duration_seconds(path: &Path) -> Option<f64> {
+    let reader = hound::WavReader::open(path).ok()?;
+    let spec = reader.spec();
+    if spec.sample_rate == 0 {
+        return None;
+    }
+    Some(reader.duration() as f64 / spec.sample_rate as f64)
+}
+
+/// Resolve "auto" to a concrete model file for a recording of `audio_seconds`
+pub fn select_model_for(app_handle: &AppHandle, audio_seconds: f64) -> String {
+    let (language, budget_ms) = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        (settings_guard.language.clone(), settings_guard.auto_model_latency_budget_ms)
+    };
+    let english_only_ok = language == "en";
+    let installed = model_manager::list_installed_model_files(app_handle);
+    let cores = cpu_cores();
+
+    let benchmarks = load_benchmarks(app_handle).filter(|b| b.cpu_cores == cores);
+    let benchmarked: Vec<ModelBenchmark> = benchmarks.map(|b| b.models).unwrap_or_default()
+        .into_iter()
+        .filter(|b| installed.contains(&b.model_name))
+        .collect();
+    let chosen = if benchmarked.is_empty() {
+        heuristic_model(&installed, cores, audio_seconds, english_only_ok)
+    } else {
+        choose_model(&benchmarked, audio_seconds, budget_ms as f64 / 1000.0, english_only_ok)
+    };
+    let chosen = chosen.unwrap_or_else(|| FALLBACK_MODEL.to_string());
+    info!("[ModelSelector] Auto picked {} for {:.1}s of audio ({} cores, budget {}ms, benchmarked: {})",
+          chosen, audio_seconds, cores, budget_ms, !benchmarked.is_empty());
+    chosen
+}
+
+// --- Benchmark ---
+
+/// The bundled speech sample (Debug: source resources dir, Release: app resources)
+fn benchmark_sample_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let path = if cfg!(debug_assertions) {
+        Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(BENCHMARK_SAMPLE))
+    } else {
+        app_handle.path_resolver().resolve_resource(BENCHMARK_SAMPLE)
+    };
+    path.filter(|p| p.is_file())
+        .ok_or_else(|| format!("Benchmark sample {} is missing from the app resources", BENCHMARK_SAMPLE))
+}
+
+async fn benchmark_one(app_handle: &AppHandle, model_name: &str, sample_path: &Path, job: &transcription_queue::Job) -> Result<ModelBenchmark, String> {
+    let (binary, cwd) = transcription::resolve_whisper_binary()?;
+    let model_path = transcription::resolve_model_path(app_handle, model_name);
+    let (decoding, timeout) = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        (settings_guard.whisper_decoding.clone(), settings_guard.process_timeouts.whisper_timeout())
+    };
+    // Same decoding flags as dictation - beam size alone can change the cost several-fold
+    let mut command = transcription::base_whisper_command(&binary, &cwd, &model_path, crate::language::ENGLISH);
+    let supported = transcription::whisper_supported_flags_async(&binary).await;
+    command.args(transcription::whisper_decoding_args(&decoding, &supported)).arg(sample_path);
+
+    let started = Instant::now();
+    crate::process_runner::run(command, timeout, Some(job.control())).await.map_err(|e| format!("Whisper {}", e))?;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    // The sample is shorter than one window, so the whole run is one window's cost
+    Ok(ModelBenchmark {
+        model_name: model_name.to_string(),
+        seconds_per_window: elapsed,
+        real_time_factor: elapsed / WHISPER_WINDOW_SECONDS,
+    })
+}
+
+// Someone wants the CPU: a recording is running or a dictation waits behind the benchmark
+fn dictation_waiting() -> bool {
+    *crate::RECORDING_LIFECYCLE.lock().unwrap() != crate::RecordingLifecycle::Idle
+        || transcription_queue::job_count() > 1
+}
+
+/// Benchmark installed models as a transcription queue job and persist the results.
+/// `idle_run` only measures models without a result and stops early when a dictation starts.
+pub async fn run_benchmark(app_handle: AppHandle, idle_run: bool) -> Result<BenchmarkResults, String> {
+    if BENCHMARK_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("A model benchmark is already running".to_string());
+    }
+    scopeguard::defer!({
+        BENCHMARK_RUNNING.store(false, Ordering::SeqCst);
+    });
+    let sample_path = benchmark_sample_path(&app_handle)?;
+
+    let mut job = transcription_queue::submit(&app_handle)?;
+    if let Err(e) = job.wait_turn().await {
+        info!("[ModelSelector] Benchmark did not run: {}", e);
+        return Err(e);
+    }
+
+    let cores = cpu_cores();
+    let previous = load_benchmarks(&app_handle).filter(|b| b.cpu_cores == cores && idle_run);
+    let mut results = BenchmarkResults {
+        cpu_cores: cores,
+        benchmarked_at: Some(Utc::now()),
+        models: previous.map(|b| b.models).unwrap_or_default(),
+    };
+    let to_measure: Vec<String> = model_manager::list_installed_model_files(&app_handle).into_iter()
+        .filter(|name| !results.models.iter().any(|b| &b.model_name == name))
+        .collect();
+
+    // An idle run gives way at once, killing whisper mid-model: a dictation shouldn't wait
+    // for a large model's benchmark to finish
+    let watcher = idle_run.then(|| {
+        let control = job.control();
+        tauri::async_runtime::spawn(async move {
+            while !control.is_cancelled() {
+                if dictation_waiting() {
+                    control.cancel();
+                    break;
+                }
+                tokio::time::sleep(YIELD_CHECK_INTERVAL).await;
+            }
+        })
+    });
+
+    for (index, model_name) in to_measure.iter().enumerate() {
+        if job.is_cancelled() {
+            info!("[ModelSelector] Benchmark stopped after {} of {} models", index, to_measure.len());
+            break;
+        }
+        let _ = app_handle.emit_all("fethr-model-benchmark-progress", serde_json::json!({
+            "model_name": model_name, "index": index, "total": to_measure.len()
+        }));
+        match benchmark_one(&app_handle, model_name, &sample_path, &job).await {
+            Ok(benchmark) => {
+                info!("[ModelSelector] {}: {:.2}s per window (RTF {:.3})", model_name, benchmark.seconds_per_window, benchmark.real_time_factor);
+                results.models.push(benchmark);
+            }
+            Err(_) if job.is_cancelled() => {}
+            Err(e) => warn!("[ModelSelector] Benchmark of {} failed: {}", model_name, e),
+        }
+    }
+    if let Some(watcher) = watcher {
+        watcher.abort();
+    }
+
+    let result = save_benchmarks(&app_handle, &results).map(|_| results);
+    job.finish(&result);
+    if let Ok(results) = &result {
+        let _ = app_handle.emit_all("fethr-model-benchmark-complete", results.clone());
+    }
+    result
+}
+
+// Auto mode and some installed model has no result for this machine
+fn benchmark_needed(app_handle: &AppHandle) -> bool {
+    let auto = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        settings_guard.model_name == AUTO_MODEL
+    };
+    if !auto {
+        return false;
+    }
+    let cores = cpu_cores();
+    let benchmarked = load_benchmarks(app_handle).filter(|b| b.cpu_cores == cores)
+        .map(|b| b.models).unwrap_or_default();
+    model_manager::list_installed_model_files(app_handle).iter()
+        .any(|name| !benchmarked.iter().any(|b| &b.model_name == name))
+}
+
+/// Called at startup: benchmark missing models whenever the app is idle in auto mode.
+/// Gives up for the session if the bundled speech sample is missing.
+pub fn init(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            let idle = transcription_queue::job_count() == 0 && !dictation_waiting();
+            if !idle || BENCHMARK_RUNNING.load(Ordering::SeqCst) || !benchmark_needed(&app_handle) {
+                continue;
+            }
+            if let Err(e) = benchmark_sample_path(&app_handle) {
+                warn!("[ModelSelector] {}; idle benchmarking disabled until restart", e);
+                return;
+            }
+            info!("[ModelSelector] App is idle, benchmarking models for auto mode");
+            if let Err(e) = run_benchmark(app_handle.clone(), true).await {
+                error!("[ModelSelector] Idle benchmark failed: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn benchmark_models(app_handle: AppHandle) -> Result<BenchmarkResults, String> {
+    run_benchmark(app_handle, false).await
+}
+
+#[tauri::command]
+pub async fn get_model_benchmarks(app_handle: AppHandle) -> Result<Option<BenchmarkResults>, String> {
+    Ok(load_benchmarks(&app_handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bench(name: &str, seconds_per_window: f64) -> ModelBenchmark {
+        ModelBenchmark { model_name: name.to_string(), seconds_per_window, real_time_factor: seconds_per_window / 30.0 }
+    }
+
+    fn benchmarks() -> Vec<ModelBenchmark> {
+        vec![bench("ggml-tiny.en.bin", 0.4), bench("ggml-base.en.bin", 0.9), bench("ggml-small.en.bin", 2.5), bench("ggml-base.bin", 1.0)]
+    }
+
+    #[test]
+    fn latency_scales_with_windows() {
+        assert_eq!(predicted_latency_seconds(1.0, 5.0), 1.0);
+        assert_eq!(predicted_latency_seconds(1.0, 30.0), 1.0);
+        assert_eq!(predicted_latency_seconds(1.0, 61.0), 3.0);
+    }
+
+    #[test]
+    fn short_clip_gets_largest_model_within_budget() {
+        assert_eq!(choose_model(&benchmarks(), 5.0, 3.0, true).as_deref(), Some("ggml-small.en.bin"));
+        assert_eq!(choose_model(&benchmarks(), 5.0, 1.0, true).as_deref(), Some("ggml-base.en.bin"));
+    }
+
+    #[test]
+    fn long_recording_steps_down() {
+        // 90 s = 3 windows: small needs 7.5 s, base.en 2.7 s
+        assert_eq!(choose_model(&benchmarks(), 90.0, 3.0, true).as_deref(), Some("ggml-base.en.bin"));
+    }
+
+    #[test]
+    fn nothing_fits_falls_back_to_fastest() {
+        assert_eq!(choose_model(&benchmarks(), 600.0, 0.5, true).as_deref(), Some("ggml-tiny.en.bin"));
+    }
+
+    #[test]
+    fn non_english_skips_english_only_models() {
+        assert_eq!(choose_model(&benchmarks(), 5.0, 3.0, false).as_deref(), Some("ggml-base.bin"));
+    }
+
+    #[test]
+    fn heuristic_scales_with_cores_and_length() {
+        let installed = vec!["ggml-tiny.en.bin".to_string(), "ggml-base.en.bin".to_string(), "ggml-small.en.bin".to_string()];
+        assert_eq!(heuristic_model(&installed, 16, 5.0, true).as_deref(), Some("ggml-tiny.en.bin"));
+        assert_eq!(heuristic_model(&installed, 8, 60.0, true).as_deref(), Some("ggml-base.en.bin"));
+        assert_eq!(heuristic_model(&installed, 16, 60.0, true).as_deref(), Some("ggml-small.en.bin"));
+    }
+}
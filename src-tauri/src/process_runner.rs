@@ -0,0 +1,154 @@
+// src-tauri/src/process_runner.rs
+//
+// Runs whisper and ffmpeg with a time limit. The child is killed once the limit passes
+// or its transcription job is cancelled, so a hung process can't leave the app stuck in
+// Transcribing. `run_blocking` is for code already on a blocking thread (transcription
+// backends); async code uses `run`, which moves the wait onto the blocking pool.
+
+use std::fmt;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::transcription_queue::JobControl;
+
+// How often the child is checked for exit, timeout and cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProcessError {
+    Spawn(String),
+    TimedOut(Duration),
+    Cancelled,
+    Exited { status: String, stdout: String, stderr: String },
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::Spawn(e) => write!(f, "failed to start: {}", e),
+            ProcessError::TimedOut(timeout) => write!(f, "killed after {} seconds without finishing", timeout.as_secs()),
+            ProcessError::Cancelled => write!(f, "cancelled"),
+            ProcessError::Exited { status, stdout, stderr } => {
+                write!(f, "failed with status: {}. Stderr: {}. Stdout: {}", status, stderr.trim(), stdout.trim())
+            }
+        }
+    }
+}
+
+// Read a pipe to the end on its own thread so a chatty child can't block on a full pipe
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// Run `command` to completion, capturing stdout and stderr. A non-zero exit is an error.
+pub fn run_blocking(mut command: Command, timeout: Duration, cancel: Option<&JobControl>) -> Result<Output, ProcessError> {
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = command.spawn().map_err(|e| ProcessError::Spawn(e.to_string()))?;
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        let stop = match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if cancel.is_some_and(JobControl::is_cancelled) => ProcessError::Cancelled,
+            Ok(None) if Instant::now() >= deadline => ProcessError::TimedOut(timeout),
+            Ok(None) => {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            Err(e) => ProcessError::Spawn(e.to_string()),
+        };
+        let _ = child.kill();
+        let _ = child.wait(); // Reap it; the drain threads end when the pipes close
+        return Err(stop);
+    };
+
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    if !output.status.success() {
+        return Err(ProcessError::Exited {
+            status: output.status.to_string(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(output)
+}
+
+/// `run_blocking` on the blocking thread pool
+pub async fn run(command: Command, timeout: Duration, cancel: Option<Arc<JobControl>>) -> Result<Output, ProcessError> {
+    tokio::task::spawn_blocking(move || run_blocking(command, timeout, cancel.as_deref()))
+        .await
+        .unwrap_or_else(|e| Err(ProcessError::Spawn(format!("process task failed: {}", e))))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[test]
+    fn captures_output_of_a_finished_process() {
+        let output = run_blocking(shell("echo transcript; echo progress >&2"), Duration::from_secs(5), None).unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "transcript\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "progress\n");
+    }
+
+    #[test]
+    fn reports_a_failed_exit() {
+        match run_blocking(shell("echo bad model >&2; exit 3"), Duration::from_secs(5), None) {
+            Err(ProcessError::Exited { status, stderr, .. }) => {
+                assert!(status.contains('3'));
+                assert_eq!(stderr.trim(), "bad model");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            run_blocking(Command::new("/nonexistent/whisper"), Duration::from_secs(5), None),
+            Err(ProcessError::Spawn(_))
+        ));
+    }
+
+    #[test]
+    fn kills_a_hung_process_at_the_timeout() {
+        let started = Instant::now();
+        let result = run_blocking(shell("sleep 30"), Duration::from_millis(200), None);
+        assert_eq!(result.unwrap_err(), ProcessError::TimedOut(Duration::from_millis(200)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn kills_the_process_on_cancel() {
+        let control = Arc::new(JobControl::default());
+        let canceller = {
+            let control = control.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(100));
+                control.cancel();
+            })
+        };
+        let started = Instant::now();
+        let result = run_blocking(shell("sleep 30"), Duration::from_secs(60), Some(&control));
+        canceller.join().unwrap();
+        assert_eq!(result.unwrap_err(), ProcessError::Cancelled);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
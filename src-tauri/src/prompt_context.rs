@@ -0,0 +1,124 @@
+// src-tauri/src/prompt_context.rs
+//
+// Values for the variables of an AI action template. Only the variables a template
+// actually uses are looked up, so the clipboard isn't read and no process is started
+// for templates that don't need them.
+
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Duration;
+use log::{debug, warn};
+use tauri::AppHandle;
+
+use crate::config::SETTINGS;
+use crate::prompt_template::Template;
+
+const APP_NAME_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What the caller knows; everything else is looked up
+#[derive(Debug, Default, Clone)]
+pub struct PromptInputs<'a> {
+    pub text: &'a str,
+    pub target_language: Option<&'a str>, // Defaults to the translation setting
+    pub selected_text: Option<&'a str>, // Only the command hotkey has a selection
+}
+
+/// Stands in for the text in RenderedPrompt::fingerprint
+pub const TEXT_MARKER: &str = "\u{1}text\u{1}";
+
+pub struct RenderedPrompt {
+    pub prompt: String,
+    // The same prompt with TEXT_MARKER in place of ${text}: it identifies the template and
+    // its context values independently of the text (cache keys)
+    pub fingerprint: String,
+    pub uses_style_guide: bool, // The template places ${style_guide} itself
+}
+
+/// Parse `source` and fill it in
+pub async fn render_prompt(app_handle: &AppHandle, source: &str, inputs: &PromptInputs<'_>) -> Result<RenderedPrompt, String> {
+    let template = Template::parse(source).map_err(|e| format!("Invalid prompt template: {}", e))?;
+    let mut values = HashMap::new();
+    for variable in template.variables() {
+        values.insert(variable, value_for(app_handle, variable, inputs).await);
+    }
+    let prompt = template.render(&values);
+    values.insert("text", TEXT_MARKER.to_string());
+    Ok(RenderedPrompt { prompt, fingerprint: template.render(&values), uses_style_guide: template.uses("style_guide") })
+}
+
+pub async fn render(app_handle: &AppHandle, source: &str, inputs: &PromptInputs<'_>) -> Result<String, String> {
+    Ok(render_prompt(app_handle, source, inputs).await?.prompt)
+}
+
+async fn value_for(app_handle: &AppHandle, variable: &str, inputs: &PromptInputs<'_>) -> String {
+    match variable {
+        "text" => inputs.text.to_string(),
+        "target_language" => match inputs.target_language {
+            Some(target) => target.to_string(),
+            None => SETTINGS.lock().unwrap().translation.target_language.clone(),
+        },
+        "clipboard" => arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .unwrap_or_default(),
+        "selected_text" => inputs.selected_text.unwrap_or_default().to_string(),
+        "app_name" => frontmost_app_name().await.unwrap_or_default(),
+        "date" => chrono::Local::now().format("%Y-%m-%d").to_string(),
+        "language" => dictation_language(app_handle),
+        "previous_transcription" => crate::transcription::previous_transcription(app_handle, inputs.text).unwrap_or_default(),
+        "dictionary_terms" => crate::dictionary_manager::get_dictionary(app_handle.clone())
+            .map(|words| words.join(", "))
+            .unwrap_or_default(),
+        "style_guide" => crate::style_profile::guide(app_handle).unwrap_or_default(),
+        _ => String::new(), // parse() only lets known variables through
+    }
+}
+
+// The configured language, or the one whisper detected for the latest dictation
+fn dictation_language(app_handle: &AppHandle) -> String {
+    let configured = SETTINGS.lock().unwrap().language.clone();
+    if configured != crate::language::AUTO_LANGUAGE {
+        return configured;
+    }
+    crate::transcription::latest_language(app_handle).unwrap_or_default()
+}
+
+fn app_name_command() -> Command {
+    #[cfg(target_os = "macos")]
+    {
+        let mut command = Command::new("osascript");
+        command.args(["-e", "tell application \"System Events\" to get name of first application process whose frontmost is true"]);
+        command
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let mut command = Command::new("powershell");
+        command.args([
+            "-NoProfile",
+            "-Command",
+            "Add-Type 'using System;using System.Runtime.InteropServices;public class W{[DllImport(\"user32.dll\")]public static extern IntPtr GetForegroundWindow();[DllImport(\"user32.dll\")]public static extern int GetWindowThreadProcessId(IntPtr h,out int p);}';\
+             $p=0;[void][W]::GetWindowThreadProcessId([W]::GetForegroundWindow(),[ref]$p);(Get-Process -Id $p).ProcessName",
+        ]);
+        command
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let mut command = Command::new("xdotool");
+        command.args(["getactivewindow", "getwindowclassname"]);
+        command
+    }
+}
+
+/// Name of the application in the foreground, if the platform tells us
+pub async fn frontmost_app_name() -> Option<String> {
+    match crate::process_runner::run(app_name_command(), APP_NAME_TIMEOUT, None).await {
+        Ok(output) => {
+            let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            debug!("[Prompt Context] Active app: '{}'", name);
+            Some(name).filter(|name| !name.is_empty())
+        }
+        Err(e) => {
+            warn!("[Prompt Context] Could not determine the active app: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// src-tauri/src/prompt_template.rs
+//
+// The template language of AI action prompts:
+//
+//   ${text}                             a variable (see VARIABLES)
+//   ${if clipboard}...${end}            kept only when the variable isn't blank
+//   ${if !app_name}...${else}...${end}  negated, with an else branch
+//   $${text}                            a literal "${text}"
+//
+// Templates are checked when they're saved, so a typo in a variable name is reported
+// then instead of silently reaching the model.
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Variables a template can use
+pub const VARIABLES: &[&str] = &[
+    "text",                   // The text the action runs on
+    "target_language",        // Translation target (ISO code)
+    "clipboard",              // Current clipboard text
+    "selected_text",          // Text selected in the active app (command mode only)
+    "app_name",               // Name of the active app
+    "date",                   // Today, YYYY-MM-DD
+    "language",               // Dictation language (ISO code)
+    "previous_transcription", // The dictation before this one
+    "dictionary_terms",       // Custom dictionary words, comma-separated
+    "style_guide",            // The user's writing style profile (style_profile.rs)
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(String),
+    If { variable: String, negate: bool, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+// A conditional still waiting for its ${end}
+struct OpenIf {
+    variable: String,
+    negate: bool,
+    line: usize,
+    outer: Vec<Node>, // Nodes before the ${if}
+    then: Option<Vec<Node>>, // Set once ${else} is reached
+}
+
+fn check_variable(name: &str, line: usize) -> Result<(), String> {
+    if VARIABLES.contains(&name) {
+        return Ok(());
+    }
+    Err(format!(
+        "Unknown variable ${{{}}} on line {}. Available variables: {}",
+        name,
+        line,
+        VARIABLES.iter().map(|v| format!("${{{}}}", v)).collect::<Vec<_>>().join(", ")
+    ))
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, String> {
+        let mut stack: Vec<OpenIf> = Vec::new();
+        let mut current: Vec<Node> = Vec::new();
+        let mut literal = String::new();
+        let mut rest = source;
+        let mut line = 1;
+
+        while let Some(start) = rest.find('$') {
+            literal.push_str(&rest[..start]);
+            line += rest[..start].matches('\n').count();
+            let after = &rest[start..];
+
+            if let Some(escaped) = after.strip_prefix("$${") {
+                literal.push_str("${");
+                rest = escaped;
+                continue;
+            }
+            let Some(tag_source) = after.strip_prefix("${") else {
+                literal.push('$'); // A lone dollar sign is just text
+                rest = &after[1..];
+                continue;
+            };
+            let end = tag_source.find('}')
+                .filter(|&end| !tag_source[..end].contains('\n'))
+                .ok_or_else(|| format!("Unclosed ${{ on line {}; write $${{ for a literal ${{", line))?;
+            let tag = tag_source[..end].trim();
+            rest = &tag_source[end + 1..];
+
+            if !literal.is_empty() {
+                current.push(Node::Text(std::mem::take(&mut literal)));
+            }
+            if let Some(condition) = tag.strip_prefix("if ") {
+                let condition = condition.trim();
+                let (negate, variable) = match condition.strip_prefix('!') {
+                    Some(variable) => (true, variable.trim()),
+                    None => (false, condition),
+                };
+                check_variable(variable, line)?;
+                let outer = std::mem::take(&mut current);
+                stack.push(OpenIf { variable: variable.to_string(), negate, line, outer, then: None });
+            } else if tag == "else" {
+                let open = stack.last_mut()
+                    .filter(|open| open.then.is_none())
+                    .ok_or_else(|| format!("${{else}} on line {} without a matching ${{if ...}}", line))?;
+                open.then = Some(std::mem::take(&mut current));
+            } else if tag == "end" {
+                let open = stack.pop()
+                    .ok_or_else(|| format!("${{end}} on line {} without a matching ${{if ...}}", line))?;
+                let branch = std::mem::replace(&mut current, open.outer);
+                let (then, otherwise) = match open.then {
+                    Some(then) => (then, branch),
+                    None => (branch, Vec::new()),
+                };
+                current.push(Node::If { variable: open.variable, negate: open.negate, then, otherwise });
+            } else if tag.is_empty() {
+                return Err(format!("Empty ${{}} on line {}", line));
+            } else {
+                check_variable(tag, line)?;
+                current.push(Node::Variable(tag.to_string()));
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            current.push(Node::Text(literal));
+        }
+        if let Some(open) = stack.last() {
+            return Err(format!("${{if {}}} on line {} is missing its ${{end}}", open.variable, open.line));
+        }
+        Ok(Template { nodes: current })
+    }
+
+    /// Every variable the template refers to, including in conditions
+    pub fn variables(&self) -> BTreeSet<&str> {
+        fn collect<'a>(nodes: &'a [Node], found: &mut BTreeSet<&'a str>) {
+            for node in nodes {
+                match node {
+                    Node::Text(_) => {}
+                    Node::Variable(name) => {
+                        found.insert(name);
+                    }
+                    Node::If { variable, then, otherwise, .. } => {
+                        found.insert(variable);
+                        collect(then, found);
+                        collect(otherwise, found);
+                    }
+                }
+            }
+        }
+        let mut found = BTreeSet::new();
+        collect(&self.nodes, &mut found);
+        found
+    }
+
+    pub fn uses(&self, variable: &str) -> bool {
+        self.variables().contains(variable)
+    }
+
+    /// Fill in `values`; a variable without a value renders as nothing
+    pub fn render(&self, values: &HashMap<&str, String>) -> String {
+        fn render_nodes(nodes: &[Node], values: &HashMap<&str, String>, out: &mut String) {
+            for node in nodes {
+                match node {
+                    Node::Text(text) => out.push_str(text),
+                    Node::Variable(name) => out.push_str(values.get(name.as_str()).map_or("", String::as_str)),
+                    Node::If { variable, negate, then, otherwise } => {
+                        let present = values.get(variable.as_str()).is_some_and(|value| !value.trim().is_empty());
+                        render_nodes(if present != *negate { then } else { otherwise }, values, out);
+                    }
+                }
+            }
+        }
+        let mut out = String::new();
+        render_nodes(&self.nodes, values, &mut out);
+        out
+    }
+}
+
+/// Check a template before it's saved. Prompts have to say where the text goes.
+pub fn validate(source: &str) -> Result<Template, String> {
+    let template = Template::parse(source)?;
+    if !template.uses("text") {
+        return Err("The prompt must include ${text} where the text to process goes".to_string());
+    }
+    Ok(template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&'static str, &str)]) -> HashMap<&'static str, String> {
+        pairs.iter().map(|(name, value)| (*name, value.to_string())).collect()
+    }
+
+    #[test]
+    fn substitutes_variables_and_keeps_escapes() {
+        let template = Template::parse("Fix for ${app_name}: ${ text }. Costs $5, write $${text} literally.").unwrap();
+        assert_eq!(
+            template.render(&values(&[("app_name", "Slack"), ("text", "ship it")])),
+            "Fix for Slack: ship it. Costs $5, write ${text} literally."
+        );
+        assert_eq!(template.variables().into_iter().collect::<Vec<_>>(), vec!["app_name", "text"]);
+    }
+
+    #[test]
+    fn renders_conditionals() {
+        let template = Template::parse(
+            "${if selected_text}Apply to: ${selected_text}${else}No selection${end}.${if !clipboard} Clipboard empty.${end}"
+        ).unwrap();
+        assert_eq!(template.render(&values(&[("selected_text", "Hi")])), "Apply to: Hi. Clipboard empty.");
+        assert_eq!(template.render(&values(&[("selected_text", "  "), ("clipboard", "x")])), "No selection.");
+        assert!(template.uses("clipboard"));
+
+        let nested = Template::parse("${if text}A${if date}B${else}C${end}D${end}").unwrap();
+        assert_eq!(nested.render(&values(&[("text", "t")])), "ACD");
+        assert_eq!(nested.render(&values(&[("text", "t"), ("date", "2026-01-01")])), "ABD");
+    }
+
+    #[test]
+    fn reports_unknown_variables_with_their_line() {
+        let error = Template::parse("Line one\nUse ${clipbaord} here").unwrap_err();
+        assert!(error.starts_with("Unknown variable ${clipbaord} on line 2."), "{}", error);
+        assert!(error.contains("${clipboard}"));
+        assert!(Template::parse("${if nope}x${end}").unwrap_err().contains("${nope}"));
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        assert!(Template::parse("${text").unwrap_err().contains("Unclosed"));
+        assert!(Template::parse("${if text}open").unwrap_err().contains("missing its ${end}"));
+        assert!(Template::parse("${end}").is_err());
+        assert!(Template::parse("${if text}a${else}b${else}c${end}").is_err());
+        assert!(Template::parse("${}").is_err());
+
+        assert!(validate("Summarize ${text}").is_ok());
+        assert!(validate("Summarize this").unwrap_err().contains("${text}"));
+    }
+}
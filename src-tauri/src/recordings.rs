@@ -0,0 +1,400 @@
+// src-tauri/src/recordings.rs
+//
+// Recording file management: app-owned storage for capture/conversion WAVs,
+// startup recovery of recordings orphaned by a crash, and garbage collection.
+//
+// With panic = "abort" in release, a crash mid-recording never runs hound's
+// finalize, so the WAV header keeps its placeholder sizes. The audio itself is
+// intact - we patch the RIFF/data sizes from the file length and offer the
+// recording for transcription.
+
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Utc};
+use log::{info, warn, error};
+use tauri::{AppHandle, Manager};
+
+use crate::transcription::{self, TranscriptionState};
+
+pub const RECORDING_PREFIX: &str = "fethr_rec_";
+pub const CONVERTED_PREFIX: &str = "fethr_converted_";
+const RECOVERED_DIR_NAME: &str = "recovered";
+
+/// Recovered recordings nobody acted on are deleted after this many days
+const RECOVERED_RETENTION_DAYS: u64 = 7;
+/// Anything shorter than this is a mis-tap, not worth offering
+const MIN_RECOVERABLE_SECONDS: f64 = 0.5;
+/// How much of the file we read to locate the data chunk
+const HEADER_SCAN_BYTES: usize = 4096;
+
+/// A recording found after a crash, repaired and waiting for the user's decision
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveredRecording {
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub duration_seconds: f64,
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+/// Result of inspecting a WAV header against the actual file length
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavHeaderFix {
+    pub riff_size: u32,
+    pub data_size_offset: u64,
+    pub data_size: u32,
+    pub needs_repair: bool,
+    pub sample_rate: u32,
+    pub block_align: u16,
+}
+
+impl WavHeaderFix {
+    pub fn duration_seconds(&self) -> f64 {
+        let bytes_per_second = self.sample_rate as f64 * self.block_align as f64;
+        if bytes_per_second <= 0.0 { 0.0 } else { self.data_size as f64 / bytes_per_second }
+    }
+}
+
+/// Directory for in-flight recordings and conversion outputs.
+/// Lives under the app data dir so crash leftovers can be found again on next launch.
+pub fn recordings_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_handle.path_resolver().app_data_dir()
+        .ok_or_else(|| "Failed to get app data directory".to_string())?;
+    let dir = data_dir.join("recordings");
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+    }
+    Ok(dir)
+}
+
+/// Recordings dir, falling back to the OS temp dir if the app dir is unavailable
+pub fn recordings_dir_or_temp(app_handle: &AppHandle) -> PathBuf {
+    recordings_dir(app_handle).unwrap_or_else(|e| {
+        warn!("[Recordings] {}. Falling back to temp dir.", e);
+        std::env::temp_dir()
+    })
+}
+
+fn recovered_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = recordings_dir(app_handle)?.join(RECOVERED_DIR_NAME);
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create recovered recordings directory: {}", e))?;
+    }
+    Ok(dir)
+}
+
+/// Work out the correct RIFF and data chunk sizes for a WAV whose header may be stale.
+/// `header` is the beginning of the file (at least up to the data chunk header).
+pub fn compute_wav_header_fix(header: &[u8], file_len: u64) -> Result<WavHeaderFix, String> {
+    if header.len() < 12 || &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err("Not a RIFF/WAVE file".to_string());
+    }
+
+    let mut pos = 12usize;
+    let mut sample_rate = 0u32;
+    let mut block_align = 0u16;
+    while pos + 8 <= header.len() {
+        let chunk_id = &header[pos..pos + 4];
+        let chunk_size = u32::from_le_bytes([header[pos + 4], header[pos + 5], header[pos + 6], header[pos + 7]]);
+        if chunk_id == b"fmt " {
+            if pos + 8 + 14 > header.len() {
+                return Err("Truncated fmt chunk".to_string());
+            }
+            let fmt = &header[pos + 8..];
+            sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+            block_align = u16::from_le_bytes([fmt[12], fmt[13]]);
+        } else if chunk_id == b"data" {
+            if block_align == 0 {
+                return Err("data chunk found before a valid fmt chunk".to_string());
+            }
+            let data_start = (pos + 8) as u64;
+            let available = file_len.saturating_sub(data_start);
+            // Drop a partially written trailing frame and stay inside the u32 RIFF limit
+            let max_data = (u32::MAX as u64).saturating_sub(data_start);
+            let data_size = (available.min(max_data) / block_align as u64 * block_align as u64) as u32;
+            let riff_size = (data_start - 8 + data_size as u64) as u32;
+            let current_riff = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+            return Ok(WavHeaderFix {
+                riff_size,
+                data_size_offset: (pos + 4) as u64,
+                data_size,
+                needs_repair: chunk_size != data_size || current_riff != riff_size,
+                sample_rate,
+                block_align,
+            });
+        }
+        // Chunks are word aligned
+        pos += 8 + chunk_size as usize + (chunk_size as usize & 1);
+    }
+    Err("No data chunk found in header".to_string())
+}
+
+/// Patch the size fields of a WAV file in place. Returns the fix that was applied.
+pub fn repair_wav_file(path: &Path) -> Result<WavHeaderFix, String> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let file_len = file.metadata().map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?.len();
+
+    let mut header = vec![0u8; HEADER_SCAN_BYTES.min(file_len as usize)];
+    file.read_exact(&mut header).map_err(|e| format!("Failed to read header: {}", e))?;
+    let fix = compute_wav_header_fix(&header, file_len)?;
+
+    if fix.needs_repair {
+        file.seek(SeekFrom::Start(4)).map_err(|e| e.to_string())?;
+        file.write_all(&fix.riff_size.to_le_bytes()).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(fix.data_size_offset)).map_err(|e| e.to_string())?;
+        file.write_all(&fix.data_size.to_le_bytes()).map_err(|e| e.to_string())?;
+        file.flush().map_err(|e| e.to_string())?;
+        info!("[Recordings] Repaired WAV header of {} ({} data bytes)", path.display(), fix.data_size);
+    }
+    Ok(fix)
+}
+
+fn file_name_str(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|n| n.to_str())
+}
+
+fn remove_quietly(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        warn!("[Recordings] Failed to delete {}: {}", path.display(), e);
+    } else {
+        info!("[Recordings] Deleted {}", path.display());
+    }
+}
+
+fn is_older_than(path: &Path, age: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map_or(false, |elapsed| elapsed > age)
+}
+
+/// Startup scan for leftovers from a crashed session. Must run before any recording starts.
+/// Conversion outputs are always deleted; capture files are repaired and moved to the
+/// recovered dir, or deleted if they hold no usable audio.
+pub fn recover_orphaned_recordings(app_handle: &AppHandle) -> Vec<RecoveredRecording> {
+    let recovered = match recovered_dir(app_handle) {
+        Ok(dir) => dir,
+        Err(e) => {
+            error!("[Recordings] Recovery skipped: {}", e);
+            return Vec::new();
+        }
+    };
+
+    // Older builds wrote straight to the temp dir, so scan it too
+    let mut scan_dirs = vec![std::env::temp_dir()];
+    if let Ok(dir) = recordings_dir(app_handle) {
+        scan_dirs.push(dir);
+    }
+
+    for dir in scan_dirs {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("[Recordings] Could not scan {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+            let name = match file_name_str(&path) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            if name.starts_with(CONVERTED_PREFIX) {
+                remove_quietly(&path);
+            } else if name.starts_with(RECORDING_PREFIX) && name.ends_with(".wav") {
+                match repair_wav_file(&path) {
+                    Ok(fix) if fix.duration_seconds() >= MIN_RECOVERABLE_SECONDS => {
+                        let target = recovered.join(&name);
+                        // rename fails across volumes (temp dir -> app dir), so fall back to copy
+                        let moved = fs::rename(&path, &target)
+                            .or_else(|_| fs::copy(&path, &target).and_then(|_| fs::remove_file(&path)));
+                        match moved {
+                            Ok(_) => info!("[Recordings] Recovered {} ({:.1}s)", name, fix.duration_seconds()),
+                            Err(e) => error!("[Recordings] Failed to move {} to recovered dir: {}", name, e),
+                        }
+                    }
+                    Ok(_) => remove_quietly(&path), // Too short to bother
+                    Err(e) => {
+                        warn!("[Recordings] Unrecoverable recording {}: {}", name, e);
+                        remove_quietly(&path);
+                    }
+                }
+            }
+        }
+    }
+
+    // Garbage-collect recovered recordings the user never dealt with
+    let retention = Duration::from_secs(RECOVERED_RETENTION_DAYS * 24 * 60 * 60);
+    if let Ok(entries) = fs::read_dir(&recovered) {
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+            if is_older_than(&path, retention) {
+                remove_quietly(&path);
+            }
+        }
+    }
+
+    let list = list_recovered(&recovered);
+    if !list.is_empty() {
+        info!("[Recordings] {} recovered recording(s) awaiting user action", list.len());
+        if let Err(e) = app_handle.emit_all("fethr-recovered-recordings", list.clone()) {
+            error!("[Recordings] Failed to emit fethr-recovered-recordings: {}", e);
+        }
+    }
+    list
+}
+
+fn list_recovered(recovered: &Path) -> Vec<RecoveredRecording> {
+    let mut list = Vec::new();
+    if let Ok(entries) = fs::read_dir(recovered) {
+        for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+            let name = match file_name_str(&path) {
+                Some(name) if name.starts_with(RECORDING_PREFIX) => name.to_string(),
+                _ => continue,
+            };
+            let metadata = fs::metadata(&path).ok();
+            let duration_seconds = fs::File::open(&path).ok()
+                .and_then(|mut f| {
+                    let mut header = vec![0u8; HEADER_SCAN_BYTES];
+                    let read = f.read(&mut header).ok()?;
+                    header.truncate(read);
+                    compute_wav_header_fix(&header, metadata.as_ref()?.len()).ok()
+                })
+                .map(|fix| fix.duration_seconds())
+                .unwrap_or(0.0);
+            list.push(RecoveredRecording {
+                file_name: name,
+                size_bytes: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                duration_seconds,
+                recorded_at: metadata.and_then(|m| m.modified().ok()).map(DateTime::<Utc>::from),
+            });
+        }
+    }
+    list.sort_by(|a, b| a.recorded_at.cmp(&b.recorded_at));
+    list
+}
+
+// Only bare file names from list_recovered are accepted - never paths from the frontend
+fn resolve_recovered_path(app_handle: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    if !file_name.starts_with(RECORDING_PREFIX) || file_name.contains(['/', '\\']) || file_name.contains("..") {
+        return Err(format!("Invalid recovered recording name: {}", file_name));
+    }
+    let path = recovered_dir(app_handle)?.join(file_name);
+    if !path.is_file() {
+        return Err(format!("Recovered recording not found: {}", file_name));
+    }
+    Ok(path)
+}
+
+#[tauri::command]
+pub fn get_recovered_recordings(app_handle: AppHandle) -> Result<Vec<RecoveredRecording>, String> {
+    Ok(list_recovered(&recovered_dir(&app_handle)?))
+}
+
+/// Run a recovered recording through the normal pipeline so it lands in history.
+/// No clipboard/paste: the user is looking at the recovery prompt, not a text field.
+#[tauri::command]
+pub async fn transcribe_recovered_recording(
+    app_handle: AppHandle,
+    transcription_state: tauri::State<'_, TranscriptionState>,
+    file_name: String,
+) -> Result<String, String> {
+    let path = resolve_recovered_path(&app_handle, &file_name)?;
+    info!("[Recordings] Transcribing recovered recording {}", path.display());
+    // The pipeline deletes its input once whisper has run
+    transcription::transcribe_audio_file(
+        app_handle,
+        transcription_state,
+        path.to_string_lossy().into_owned(),
+        false,
+        None,
+        None,
+        None,
+        None,
+    ).await
+}
+
+#[tauri::command]
+pub fn discard_recovered_recording(app_handle: AppHandle, file_name: String) -> Result<(), String> {
+    let path = resolve_recovered_path(&app_handle, &file_name)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete recovered recording: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 44-byte canonical PCM header as hound leaves it before finalize()
+    fn unfinalized_header(sample_rate: u32, channels: u16) -> Vec<u8> {
+        let block_align = channels * 2;
+        let mut h = Vec::new();
+        h.extend_from_slice(b"RIFF");
+        h.extend_from_slice(&0u32.to_le_bytes());
+        h.extend_from_slice(b"WAVE");
+        h.extend_from_slice(b"fmt ");
+        h.extend_from_slice(&16u32.to_le_bytes());
+        h.extend_from_slice(&1u16.to_le_bytes());
+        h.extend_from_slice(&channels.to_le_bytes());
+        h.extend_from_slice(&sample_rate.to_le_bytes());
+        h.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        h.extend_from_slice(&block_align.to_le_bytes());
+        h.extend_from_slice(&16u16.to_le_bytes());
+        h.extend_from_slice(b"data");
+        h.extend_from_slice(&0u32.to_le_bytes());
+        h
+    }
+
+    #[test]
+    fn test_fix_for_unfinalized_header() {
+        let header = unfinalized_header(16000, 1);
+        let fix = compute_wav_header_fix(&header, 44 + 32000).unwrap();
+        assert!(fix.needs_repair);
+        assert_eq!(fix.data_size, 32000);
+        assert_eq!(fix.riff_size, 36 + 32000);
+        assert_eq!(fix.data_size_offset, 40);
+        assert!((fix.duration_seconds() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_partial_trailing_frame_is_dropped() {
+        let header = unfinalized_header(48000, 2);
+        // 4-byte frames, 3 stray bytes at the end
+        let fix = compute_wav_header_fix(&header, 44 + 400 + 3).unwrap();
+        assert_eq!(fix.data_size, 400);
+    }
+
+    #[test]
+    fn test_valid_header_needs_no_repair() {
+        let mut header = unfinalized_header(16000, 1);
+        header[4..8].copy_from_slice(&(36u32 + 100).to_le_bytes());
+        header[40..44].copy_from_slice(&100u32.to_le_bytes());
+        let fix = compute_wav_header_fix(&header, 144).unwrap();
+        assert!(!fix.needs_repair);
+    }
+
+    #[test]
+    fn test_rejects_non_wav() {
+        assert!(compute_wav_header_fix(b"ID3\x03 not a wav at all", 100).is_err());
+        let mut header = unfinalized_header(16000, 1);
+        header.truncate(36); // No data chunk
+        assert!(compute_wav_header_fix(&header, 36).is_err());
+    }
+
+    #[test]
+    fn test_repair_wav_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fethr_rec_test.wav");
+        let mut bytes = unfinalized_header(16000, 1);
+        bytes.extend(vec![0u8; 16000]);
+        fs::write(&path, &bytes).unwrap();
+
+        repair_wav_file(&path).unwrap();
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.len(), 8000); // 16-bit mono samples
+    }
+}
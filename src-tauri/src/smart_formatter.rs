@@ -87,8 +87,18 @@ impl SmartFormatter {
         }
     }
 
+    /// Formatter for a transcription in `language` (ISO code).
+    /// The filler rules are English ("um", "like", "so") and would mangle other
+    /// languages - German "um" is a preposition - so they only run for English.
+    pub fn for_language(language: &str) -> Self {
+        if language.is_empty() || language.starts_with("en") {
+            Self::new()
+        } else {
+            Self::with_settings(false, false, false)
+        }
+    }
+
     /// Create formatter with custom settings
-    #[allow(dead_code)]
     pub fn with_settings(filler_removal: bool, remove_phrases: bool, remove_starters: bool) -> Self {
         Self {
             enabled: true,
@@ -700,6 +710,17 @@ mod tests {
         assert!(result.text.contains("you know"));
     }
 
+    #[test]
+    fn test_non_english_text_is_left_alone() {
+        // German "um" ("around"/"at") and "so" are real words, not fillers
+        let formatter = SmartFormatter::for_language("de");
+        let text = "Wir treffen uns um fünf Uhr, so wie besprochen.";
+        assert_eq!(formatter.format(text).text, text);
+
+        let english = SmartFormatter::for_language("en");
+        assert!(!english.format("Um, I think so.").text.contains("Um"));
+    }
+
     #[test]
     fn test_performance_with_large_text() {
         use std::time::Instant;
@@ -0,0 +1,542 @@
+// src-tauri/src/style_profile.rs
+//
+// The user's writing style, so rewrite actions ("written_form", "email", ...) sound like
+// them instead of like a generic assistant. The profile holds a tone description, the
+// sign-offs they use, preferred spellings and phrases to never use. It is kept in
+// style_profile.json in the app config dir and is fully editable.
+//
+// build_style_profile fills in tone, sign-offs and spellings from sample texts the user
+// pastes and, if they opt in, from history entries they edited. The analysis is local
+// and heuristic; nothing is sent anywhere to build the profile.
+//
+// The compact guide rendered from the profile is appended to the prompts of the actions in
+// `apply_to`, and is available to any template as ${style_guide}.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use log::info;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+const STYLE_PROFILE_FILENAME: &str = "style_profile.json";
+const PROFILE_VERSION: u32 = 1;
+
+const MAX_SAMPLES: usize = 20;
+const MAX_SAMPLE_CHARS: usize = 5000;
+const MAX_LIST_ITEMS: usize = 30;
+// validate() counts both words of a spelling pair against MAX_LIST_ITEMS
+const MAX_SPELLINGS: usize = MAX_LIST_ITEMS / 2;
+const MAX_ITEM_CHARS: usize = 200;
+const MAX_TONE_CHARS: usize = 500;
+// Edited history entries looked at when mining is on
+const MAX_MINED_ENTRIES: usize = 100;
+// Guide lines list at most this many sign-offs / spellings / phrases, to keep prompts short
+const MAX_GUIDE_ITEMS: usize = 8;
+
+const SIGN_OFFS: &[&str] = &[
+    "all the best", "best", "best regards", "best wishes", "cheers", "kind regards", "many thanks",
+    "regards", "sincerely", "speak soon", "take care", "talk soon", "thank you", "thanks",
+    "thanks again", "warm regards", "warmly", "yours sincerely",
+];
+
+const GREETINGS: &[&str] = &["Good morning", "Hello", "Dear", "Hey", "Hi"];
+
+// Spelling variants of the same word (American, British); whichever the user writes wins
+const SPELLING_VARIANTS: &[(&str, &str)] = &[
+    ("analyze", "analyse"), ("apologize", "apologise"), ("behavior", "behaviour"),
+    ("canceled", "cancelled"), ("catalog", "catalogue"), ("center", "centre"),
+    ("color", "colour"), ("defense", "defence"), ("dialog", "dialogue"),
+    ("enroll", "enrol"), ("favor", "favour"), ("favorite", "favourite"),
+    ("flavor", "flavour"), ("fulfill", "fulfil"), ("gray", "grey"),
+    ("honor", "honour"), ("judgment", "judgement"), ("labor", "labour"),
+    ("modeling", "modelling"), ("neighbor", "neighbour"), ("organization", "organisation"),
+    ("organize", "organise"), ("organized", "organised"), ("prioritize", "prioritise"),
+    ("realize", "realise"), ("realized", "realised"), ("recognize", "recognise"),
+    ("summarize", "summarise"), ("theater", "theatre"), ("traveling", "travelling"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreferredSpelling {
+    pub prefer: String,
+    pub avoid: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StyleProfile {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub tone: String, // Free text, e.g. "Casual and direct, short sentences"
+    #[serde(default)]
+    pub generated_tone: String, // What the last build wrote; a different tone was typed by the user
+    #[serde(default)]
+    pub sign_offs: Vec<String>,
+    #[serde(default)]
+    pub preferred_spellings: Vec<PreferredSpelling>,
+    #[serde(default)]
+    pub forbidden_phrases: Vec<String>,
+    #[serde(default)]
+    pub samples: Vec<String>, // Texts the user wrote, analysed by build_style_profile
+    #[serde(default)]
+    pub mine_history: bool, // Opt-in: analyse edited history entries too
+    #[serde(default = "default_apply_to")]
+    pub apply_to: Vec<String>, // Action ids whose prompts get the guide
+}
+
+fn default_version() -> u32 {
+    PROFILE_VERSION
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_apply_to() -> Vec<String> {
+    vec!["written_form".to_string(), "email".to_string()]
+}
+
+impl Default for StyleProfile {
+    fn default() -> Self {
+        Self {
+            version: default_version(),
+            enabled: default_enabled(),
+            tone: String::new(),
+            generated_tone: String::new(),
+            sign_offs: Vec::new(),
+            preferred_spellings: Vec::new(),
+            forbidden_phrases: Vec::new(),
+            samples: Vec::new(),
+            mine_history: false,
+            apply_to: default_apply_to(),
+        }
+    }
+}
+
+fn check_items(what: &str, items: &[String]) -> Result<(), String> {
+    if items.len() > MAX_LIST_ITEMS {
+        return Err(format!("At most {} {} (got {})", MAX_LIST_ITEMS, what, items.len()));
+    }
+    if let Some(item) = items.iter().find(|item| item.chars().count() > MAX_ITEM_CHARS) {
+        return Err(format!("{} must be at most {} characters: '{:.40}...'", what, MAX_ITEM_CHARS, item));
+    }
+    Ok(())
+}
+
+// Trimmed, without blanks and case-insensitive duplicates
+fn clean_list(items: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for item in items {
+        let item = item.trim().to_string();
+        if !item.is_empty() && !cleaned.iter().any(|kept| kept.eq_ignore_ascii_case(&item)) {
+            cleaned.push(item);
+        }
+    }
+    cleaned
+}
+
+impl StyleProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version > PROFILE_VERSION {
+            return Err(format!(
+                "Style profile is version {}, this version of Fethr only reads up to {}",
+                self.version, PROFILE_VERSION
+            ));
+        }
+        if self.tone.chars().count() > MAX_TONE_CHARS {
+            return Err(format!("The tone description must be at most {} characters", MAX_TONE_CHARS));
+        }
+        if self.samples.len() > MAX_SAMPLES {
+            return Err(format!("At most {} sample texts (got {})", MAX_SAMPLES, self.samples.len()));
+        }
+        if self.samples.iter().any(|sample| sample.chars().count() > MAX_SAMPLE_CHARS) {
+            return Err(format!("Sample texts must be at most {} characters each", MAX_SAMPLE_CHARS));
+        }
+        check_items("sign-offs", &self.sign_offs)?;
+        check_items("forbidden phrases", &self.forbidden_phrases)?;
+        check_items("actions", &self.apply_to)?;
+        let spellings: Vec<String> = self.preferred_spellings.iter().flat_map(|s| [s.prefer.clone(), s.avoid.clone()]).collect();
+        check_items("spellings", &spellings)?;
+        if self.preferred_spellings.iter().any(|s| s.prefer.trim().is_empty() || s.avoid.trim().is_empty()) {
+            return Err("A preferred spelling needs both the spelling to use and the one to avoid".to_string());
+        }
+        Ok(())
+    }
+
+    fn normalized(self) -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            tone: self.tone.trim().to_string(),
+            sign_offs: clean_list(self.sign_offs),
+            forbidden_phrases: clean_list(self.forbidden_phrases),
+            samples: self.samples.into_iter().filter(|sample| !sample.trim().is_empty()).collect(),
+            apply_to: clean_list(self.apply_to),
+            preferred_spellings: self.preferred_spellings.into_iter()
+                .map(|s| PreferredSpelling { prefer: s.prefer.trim().to_string(), avoid: s.avoid.trim().to_string() })
+                .collect(),
+            ..self
+        }
+    }
+
+    /// The guide added to prompts; None when the profile is off or says nothing
+    pub fn guide(&self) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let quoted = |items: &[String]| {
+            items.iter().take(MAX_GUIDE_ITEMS).map(|item| format!("\"{}\"", item)).collect::<Vec<_>>().join(", ")
+        };
+        let mut lines = Vec::new();
+        if !self.tone.trim().is_empty() {
+            lines.push(format!("- Tone: {}", self.tone.trim()));
+        }
+        if !self.sign_offs.is_empty() {
+            lines.push(format!("- When the text needs a sign-off, use one of: {}", quoted(&self.sign_offs)));
+        }
+        if !self.preferred_spellings.is_empty() {
+            let spellings: Vec<String> = self.preferred_spellings.iter()
+                .take(MAX_GUIDE_ITEMS)
+                .map(|s| format!("{} (not {})", s.prefer, s.avoid))
+                .collect();
+            lines.push(format!("- Spell: {}", spellings.join(", ")));
+        }
+        if !self.forbidden_phrases.is_empty() {
+            lines.push(format!("- Never use these phrases: {}", quoted(&self.forbidden_phrases)));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        Some(format!("Write in the user's own style:\n{}", lines.join("\n")))
+    }
+
+    /// Merge what `analysis` found: a new tone replaces a generated one, but a tone the user
+    /// typed stays until they clear it. Sign-offs and spellings are added to the ones already
+    /// there, up to the list limits. Forbidden phrases are the user's alone.
+    fn merge(&mut self, analysis: StyleAnalysis) {
+        let hand_set = !self.tone.trim().is_empty() && self.tone.trim() != self.generated_tone.trim();
+        if !analysis.tone.is_empty() && !hand_set {
+            self.tone = analysis.tone.clone();
+            self.generated_tone = analysis.tone;
+        }
+        let mut sign_offs = std::mem::take(&mut self.sign_offs);
+        sign_offs.extend(analysis.sign_offs);
+        self.sign_offs = clean_list(sign_offs);
+        self.sign_offs.truncate(MAX_LIST_ITEMS);
+        for spelling in analysis.preferred_spellings {
+            if self.preferred_spellings.len() >= MAX_SPELLINGS {
+                break;
+            }
+            // A spelling the user already decided on either way stays as it is
+            let decided = |word: &str| self.preferred_spellings.iter().any(|s| s.prefer.eq_ignore_ascii_case(word) || s.avoid.eq_ignore_ascii_case(word));
+            if !decided(&spelling.prefer) && !decided(&spelling.avoid) {
+                self.preferred_spellings.push(spelling);
+            }
+        }
+    }
+}
+
+// --- Analysis ---
+
+#[derive(Debug, Default, PartialEq)]
+pub struct StyleAnalysis {
+    pub tone: String,
+    pub sign_offs: Vec<String>,
+    pub preferred_spellings: Vec<PreferredSpelling>,
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’' && c != '-')
+        .map(|word| word.trim_matches(|c| c == '\'' || c == '’' || c == '-'))
+        .filter(|word| !word.is_empty())
+}
+
+// Greeting and sign-off lines ("Hi Sam," / "Cheers,") aren't sentences
+fn sentences(text: &str) -> Vec<&str> {
+    text.split_inclusive(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|sentence| sentence.ends_with(['.', '!', '?']) && words(sentence).next().is_some())
+        .collect()
+}
+
+fn is_contraction(word: &str) -> bool {
+    let word = word.to_lowercase().replace('’', "'");
+    ["n't", "'re", "'ll", "'ve", "'m", "'d"].iter().any(|suffix| word.ends_with(suffix))
+        || ["it's", "that's", "there's", "what's", "let's", "here's", "who's"].contains(&word.as_str())
+}
+
+// "Cheers," / "Best regards" / "Thanks!" at the end of a text (a name may follow)
+fn sign_off_of(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    if lines.len() < 2 {
+        return None; // A one-line text has no separate sign-off
+    }
+    lines.iter().rev().take(2).find_map(|line| {
+        let bare = line.trim_end_matches([',', '.', '!']).to_lowercase();
+        SIGN_OFFS.contains(&bare.as_str()).then(|| line.to_string())
+    })
+}
+
+fn describe_tone(texts: &[&str]) -> String {
+    let sentences: Vec<&str> = texts.iter().flat_map(|text| sentences(text)).collect();
+    if sentences.len() < 3 {
+        return String::new(); // Too little to say anything
+    }
+    let word_count: usize = sentences.iter().map(|sentence| words(sentence).count()).sum();
+    let contractions = sentences.iter().flat_map(|sentence| words(sentence)).filter(|word| is_contraction(word)).count();
+    let exclamations = sentences.iter().filter(|sentence| sentence.ends_with('!')).count();
+    let average_words = word_count / sentences.len();
+    let contraction_rate = contractions as f32 / sentences.len() as f32;
+
+    let mut parts = vec![if contraction_rate >= 0.3 {
+        "casual and direct, uses contractions".to_string()
+    } else if contraction_rate < 0.05 && average_words >= 12 {
+        "formal, no contractions".to_string()
+    } else {
+        "neutral, occasional contractions".to_string()
+    }];
+    let length = match average_words {
+        0..=10 => "short",
+        11..=20 => "medium-length",
+        _ => "long",
+    };
+    parts.push(format!("{} sentences (about {} words)", length, average_words));
+    if exclamations == 0 {
+        parts.push("no exclamation marks".to_string());
+    } else if exclamations * 5 > sentences.len() {
+        parts.push("frequent exclamation marks".to_string());
+    }
+    let mut greeting_counts: HashMap<&str, usize> = HashMap::new();
+    for text in texts {
+        let opening = text.trim_start();
+        if let Some(greeting) = GREETINGS.iter().find(|greeting| {
+            opening.get(..greeting.len()).is_some_and(|start| start.eq_ignore_ascii_case(greeting))
+                && !opening[greeting.len()..].starts_with(char::is_alphanumeric)
+        }) {
+            *greeting_counts.entry(greeting).or_default() += 1;
+        }
+    }
+    if let Some((greeting, _)) = greeting_counts.into_iter().max_by_key(|(greeting, count)| (*count, *greeting)) {
+        parts.push(format!("opens with \"{}\"", greeting));
+    }
+    let tone = parts.join("; ");
+    let mut chars = tone.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn preferred_spellings(texts: &[&str]) -> Vec<PreferredSpelling> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in texts.iter().flat_map(|text| words(text)) {
+        *counts.entry(word.to_lowercase()).or_default() += 1;
+    }
+    SPELLING_VARIANTS.iter()
+        .filter_map(|&(american, british)| {
+            let used = |word: &str| counts.get(word).copied().unwrap_or(0);
+            match (used(american), used(british)) {
+                (0, 0) => None,
+                (_, 0) => Some(PreferredSpelling { prefer: american.to_string(), avoid: british.to_string() }),
+                (0, _) => Some(PreferredSpelling { prefer: british.to_string(), avoid: american.to_string() }),
+                _ => None, // Both used: no preference
+            }
+        })
+        .collect()
+}
+
+/// What the user's texts say about their style
+pub fn analyze(texts: &[&str]) -> StyleAnalysis {
+    let mut sign_off_counts: Vec<(String, usize)> = Vec::new();
+    for sign_off in texts.iter().filter_map(|text| sign_off_of(text)) {
+        match sign_off_counts.iter_mut().find(|(seen, _)| seen.eq_ignore_ascii_case(&sign_off)) {
+            Some((_, count)) => *count += 1,
+            None => sign_off_counts.push((sign_off, 1)),
+        }
+    }
+    sign_off_counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count)); // Stable: ties keep the order first seen
+    StyleAnalysis {
+        tone: describe_tone(texts),
+        sign_offs: sign_off_counts.into_iter().take(3).map(|(sign_off, _)| sign_off).collect(),
+        preferred_spellings: preferred_spellings(texts),
+    }
+}
+
+// --- Storage ---
+
+fn profile_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app_handle
+        .path_resolver()
+        .app_config_dir()
+        .ok_or_else(|| "Failed to get app config directory".to_string())?;
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    Ok(config_dir.join(STYLE_PROFILE_FILENAME))
+}
+
+pub fn load_profile(app_handle: &AppHandle) -> Result<StyleProfile, String> {
+    let path = profile_path(app_handle)?;
+    if !path.exists() {
+        return Ok(StyleProfile::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| format!("Failed to read style profile: {}", e))?;
+    let profile: StyleProfile = serde_json::from_str(&data).map_err(|e| format!("Failed to parse style profile: {}", e))?;
+    profile.validate()?;
+    Ok(profile)
+}
+
+fn write_profile(app_handle: &AppHandle, profile: &StyleProfile) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(profile).map_err(|e| format!("Failed to serialize style profile: {}", e))?;
+    fs::write(profile_path(app_handle)?, data).map_err(|e| format!("Failed to write style profile: {}", e))
+}
+
+/// The guide for ${style_guide}; empty when there is none
+pub fn guide(app_handle: &AppHandle) -> Option<String> {
+    load_profile(app_handle).ok()?.guide()
+}
+
+/// The guide to add to `action_id`'s prompt, if the profile applies to that action
+pub fn guide_for_action(app_handle: &AppHandle, action_id: &str) -> Option<String> {
+    let profile = load_profile(app_handle).ok()?;
+    if !profile.apply_to.iter().any(|id| id == action_id) {
+        return None;
+    }
+    profile.guide()
+}
+
+#[tauri::command]
+pub fn get_style_profile(app_handle: AppHandle) -> Result<StyleProfile, String> {
+    load_profile(&app_handle)
+}
+
+#[tauri::command]
+pub fn save_style_profile(app_handle: AppHandle, profile: StyleProfile) -> Result<StyleProfile, String> {
+    let profile = profile.normalized();
+    profile.validate()?;
+    write_profile(&app_handle, &profile)?;
+    info!("[Style Profile] Saved ({} sample(s), history mining {})", profile.samples.len(), if profile.mine_history { "on" } else { "off" });
+    Ok(profile)
+}
+
+/// Analyse the samples (and edited history entries, if opted in) and merge the result
+/// into the stored profile
+#[tauri::command]
+pub fn build_style_profile(app_handle: AppHandle) -> Result<StyleProfile, String> {
+    let mut profile = load_profile(&app_handle)?;
+    let mined = if profile.mine_history {
+        let mut texts = crate::transcription::edited_history_texts(&app_handle);
+        texts.truncate(MAX_MINED_ENTRIES);
+        texts
+    } else {
+        Vec::new()
+    };
+    let texts: Vec<&str> = profile.samples.iter().chain(&mined).map(String::as_str).collect();
+    if texts.is_empty() {
+        return Err("Add some sample texts, or allow edited history entries to be used, to build a style profile".to_string());
+    }
+    profile.merge(analyze(&texts));
+    let profile = profile.normalized();
+    profile.validate()?;
+    write_profile(&app_handle, &profile)?;
+    info!("[Style Profile] Built from {} sample(s) and {} history entries", profile.samples.len(), mined.len());
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMAIL: &str = "Hey Sam,\n\nQuick one: I've moved the review to Friday. It's in the big room. Let me know if that doesn't work for you.\n\nCheers,\nAlex";
+    const NOTE: &str = "Hey team,\nThe new colour scheme is live. We'll organise a demo next week. Don't forget to send feedback.\n\nCheers,\nAlex";
+
+    #[test]
+    fn analyses_tone_sign_offs_and_spellings() {
+        let analysis = analyze(&[EMAIL, NOTE]);
+        assert!(analysis.tone.starts_with("Casual and direct"), "{}", analysis.tone);
+        assert!(analysis.tone.contains("short sentences"), "{}", analysis.tone);
+        assert!(analysis.tone.contains("opens with \"Hey\""), "{}", analysis.tone);
+        assert_eq!(analysis.sign_offs, vec!["Cheers,"]);
+        assert_eq!(analysis.preferred_spellings, vec![
+            PreferredSpelling { prefer: "colour".to_string(), avoid: "color".to_string() },
+            PreferredSpelling { prefer: "organise".to_string(), avoid: "organize".to_string() },
+        ]);
+
+        let formal = "Dear Ms Patel,\n\nThank you for the detailed proposal that you sent to our committee last week. The committee has reviewed every section of the document with considerable care. We would like to discuss the budget and the timeline at our next scheduled meeting.\n\nKind regards,\nAlex";
+        let analysis = analyze(&[formal]);
+        assert!(analysis.tone.starts_with("Formal"), "{}", analysis.tone);
+        assert_eq!(analysis.sign_offs, vec!["Kind regards,"]);
+        assert!(analyze(&["ok"]).tone.is_empty());
+    }
+
+    #[test]
+    fn merging_keeps_the_users_edits() {
+        let mut profile = StyleProfile {
+            tone: "Warm".to_string(),
+            sign_offs: vec!["cheers,".to_string()],
+            preferred_spellings: vec![PreferredSpelling { prefer: "color".to_string(), avoid: "colour".to_string() }],
+            forbidden_phrases: vec!["I hope this email finds you well".to_string()],
+            ..Default::default()
+        };
+        profile.merge(analyze(&[EMAIL, NOTE]));
+        assert_eq!(profile.tone, "Warm");
+        assert_eq!(profile.sign_offs, vec!["cheers,"]);
+        assert_eq!(profile.preferred_spellings.len(), 2); // color kept, organise added
+        assert_eq!(profile.preferred_spellings[0].prefer, "color");
+        assert_eq!(profile.forbidden_phrases.len(), 1);
+
+        // Cleared by the user: the next build fills it in, and keeps updating its own tone
+        profile.tone.clear();
+        profile.merge(analyze(&[EMAIL]));
+        assert!(profile.tone.starts_with("Casual"), "{}", profile.tone);
+        profile.merge(analyze(&[EMAIL, NOTE]));
+        assert_eq!(profile.tone, analyze(&[EMAIL, NOTE]).tone);
+    }
+
+    #[test]
+    fn merging_stays_within_the_list_limits() {
+        let british: Vec<&str> = SPELLING_VARIANTS.iter().map(|&(_, british)| british).collect();
+        let text = format!("{}.\n\nThanks,\nAlex", british.join(" "));
+        assert_eq!(analyze(&[&text]).preferred_spellings.len(), SPELLING_VARIANTS.len());
+
+        let mut profile = StyleProfile {
+            sign_offs: (0..MAX_LIST_ITEMS).map(|i| format!("Sign-off {}", i)).collect(),
+            ..Default::default()
+        };
+        profile.merge(analyze(&[&text]));
+        assert_eq!(profile.sign_offs.len(), MAX_LIST_ITEMS);
+        assert_eq!(profile.preferred_spellings.len(), MAX_SPELLINGS);
+        assert!(profile.normalized().validate().is_ok());
+    }
+
+    #[test]
+    fn renders_a_compact_guide() {
+        assert_eq!(StyleProfile::default().guide(), None);
+        let profile = StyleProfile {
+            tone: "Casual".to_string(),
+            sign_offs: vec!["Cheers,".to_string()],
+            preferred_spellings: vec![PreferredSpelling { prefer: "colour".to_string(), avoid: "color".to_string() }],
+            forbidden_phrases: vec!["Per my last email".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(profile.guide().unwrap(), "Write in the user's own style:\n- Tone: Casual\n- When the text needs a sign-off, use one of: \"Cheers,\"\n- Spell: colour (not color)\n- Never use these phrases: \"Per my last email\"");
+        assert_eq!(StyleProfile { enabled: false, ..profile }.guide(), None);
+    }
+
+    #[test]
+    fn validates_and_normalizes_edits() {
+        let profile = StyleProfile {
+            sign_offs: vec![" Best, ".to_string(), "best,".to_string(), "".to_string()],
+            ..Default::default()
+        }.normalized();
+        assert_eq!(profile.sign_offs, vec!["Best,"]);
+        assert!(profile.validate().is_ok());
+
+        let too_many = StyleProfile { samples: vec!["text".to_string(); MAX_SAMPLES + 1], ..Default::default() };
+        assert!(too_many.validate().is_err());
+        let half_spelling = StyleProfile {
+            preferred_spellings: vec![PreferredSpelling { prefer: "colour".to_string(), avoid: " ".to_string() }],
+            ..Default::default()
+        };
+        assert!(half_spelling.validate().is_err());
+        assert!(serde_json::from_str::<StyleProfile>("{}").unwrap().apply_to.contains(&"email".to_string()));
+    }
+}
@@ -4,36 +4,46 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
-use tauri::api::path::resource_dir;
-use std::sync::atomic::{AtomicBool, Ordering};
-use scopeguard;
 use uuid::Uuid;
-use log::{error, info};
+use log::{error, info, warn};
 use crate::config; // Make sure this line is present
-use crate::config::SETTINGS; // Import the global settings
-use std::process::{Command, Stdio}; // Add these imports for FFmpeg
+use crate::config::{AiProviderKind, SETTINGS}; // Import the global settings
+use std::process::Command; // For FFmpeg and whisper
 use chrono::{DateTime, Utc}; // For timestamp in history entries
 use serde_json;
 use crate::get_history_path; // <-- IMPORT the helper from main.rs
 use crate::dictionary_manager;
 use crate::smart_formatter::{SmartFormatter};
+use crate::language::{self, LanguageMode};
+use crate::translation::{TranslationMethod, TranslationRecord};
+use crate::ai_chain::AiChainRecord;
+use crate::hallucination_filter::{self, SuspectSegment};
+use crate::word_confidence::{self, ConfidenceGate, ConfidenceSpan, WordConfidence};
+use crate::transcription_backend::{self, BackendRequest, LocalModel, TranscriptionError};
+use crate::process_runner;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::transcription_queue::{self, Job, JobControl};
 
 // REMOVED: use crate::{write_to_clipboard_internal, paste_text_to_cursor};
 
-// Add a static flag to prevent multiple transcription processes
-static TRANSCRIPTION_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
-
 // Session tracking
 use std::sync::Mutex as StdMutex;
 
 lazy_static::lazy_static! {
     static ref CURRENT_SESSION: StdMutex<Option<(Uuid, chrono::DateTime<Utc>)>> = StdMutex::new(None);
+    // Flags each whisper binary lists in its --help, probed once per binary
+    static ref WHISPER_FLAGS: StdMutex<HashMap<PathBuf, HashSet<String>>> = StdMutex::new(HashMap::new());
+    // Held across every read-modify-write of history.json, see lock_history
+    static ref HISTORY_LOCK: StdMutex<()> = StdMutex::new(());
 }
 
 // Define maximum number of history entries to keep
 const MAX_HISTORY_ENTRIES: usize = 200;
 // Session timeout - new session if more than 5 minutes since last transcription
 const SESSION_TIMEOUT_MINUTES: i64 = 5;
+// whisper --help only prints usage; anything slower is a broken binary
+const WHISPER_HELP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
 // Get or create a session ID
 fn get_or_create_session() -> Uuid {
@@ -66,6 +76,63 @@ pub struct HistoryEntry {
     pub text: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub corrections: Option<Vec<WordCorrection>>,
+    // Whisper model that produced `text` (resolved, never "auto")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_used: Option<String>,
+    // File name of the retained compressed audio (in the app data audio dir), if kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_file: Option<String>,
+    // Language the text is in (ISO code), and whisper's detection probability when it was auto-detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_confidence: Option<f32>,
+    // Translation dictation output; `text` keeps the original
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<TranslationRecord>,
+    // Whisper output the hallucination filter dropped or flagged
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suspect_segments: Vec<SuspectSegment>,
+    // Whisper's per-word confidence for `text`, before correction and formatting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub word_confidence: Vec<WordConfidence>,
+    // Re-transcriptions of the retained audio, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<TranscriptRevision>,
+    // What the automatic AI chain made of the dictation; `text` keeps the raw transcription
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_output: Option<AiChainRecord>,
+    // AI action results for this entry (summaries, emails, ...), oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub derived_versions: Vec<DerivedVersion>,
+    // The user changed `text` by hand (history editor, word correction); machine rewrites don't count
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub edited: bool,
+}
+
+// An AI action's result kept with the history entry it was run on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedVersion {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub action: String, // "edit" for text replaced by a restore
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<AiProviderKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_key: Option<String>, // ai_cache::CacheKey::id of the result
+}
+
+// A re-transcription of a history entry with a different model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptRevision {
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+    pub model_used: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
 }
 
 // Track individual word corrections for UI feedback
@@ -117,11 +184,8 @@ pub fn check_model_exists(model_directory: &PathBuf, model_name: &str) -> bool {
     model_directory.join(format!("ggml-{}.bin", model_name)).exists()
 }
 
-// Helper function to convert to WAV with predictable output path & error checking
-async fn run_ffmpeg_conversion(input_path: &Path, output_path: &Path, _app_handle: &AppHandle) -> Result<(), String> {
-    println!("[RUST FFMPEG] Converting {} to 16kHz WAV at {}", input_path.display(), output_path.display());
-
-    // --- Resolve FFmpeg Path (Debug vs Release) ---
+// Resolve the bundled ffmpeg binary and the directory to run it from (Debug vs Release)
+pub(crate) fn resolve_ffmpeg_path() -> Result<(PathBuf, PathBuf), String> {
     let ffmpeg_path: PathBuf;
     let ffmpeg_cwd: PathBuf; // Directory to run ffmpeg from
 
@@ -148,7 +212,6 @@ async fn run_ffmpeg_conversion(input_path: &Path, output_path: &Path, _app_handl
         ffmpeg_cwd = exe_dir.clone(); // Use executable dir as CWD for release
         // Using production FFmpeg bundle
     }
-    // --- End Path Resolution ---
 
     // --- Check if ffmpeg exists ---
     if !ffmpeg_path.exists() {
@@ -157,6 +220,77 @@ async fn run_ffmpeg_conversion(input_path: &Path, output_path: &Path, _app_handl
          return Err(err_msg);
     }
 
+    Ok((ffmpeg_path, ffmpeg_cwd))
+}
+
+// Resolve the whisper binary and the directory to run it from (Debug vs Release)
+pub(crate) fn resolve_whisper_binary() -> Result<(PathBuf, PathBuf), String> {
+    if cfg!(debug_assertions) {
+        // DEBUG MODE: Point to the source vendor directory using CARGO_MANIFEST_DIR
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")); // Path to src-tauri
+        let vendor_dir = manifest_dir.join("vendor");
+
+        // --- Construct platform-specific binary name for DEBUG ---
+        // This needs to match the actual file name required by the build script
+        let binary_name = if cfg!(target_os = "windows") {
+            // Assuming x86_64 MSVC build target, adjust if different
+             "whisper-x86_64-pc-windows-msvc.exe"
+        } else if cfg!(target_os = "macos") {
+             // Assuming x86_64 or aarch64, adjust target triple as needed
+             if cfg!(target_arch = "aarch64") {
+                 "whisper-aarch64-apple-darwin"
+             } else {
+                 "whisper-x86_64-apple-darwin"
+             }
+        } else if cfg!(target_os = "linux") {
+             // Assuming x86_64 GNU target, adjust target triple as needed
+             "whisper-x86_64-unknown-linux-gnu"
+        } else {
+            // Fallback or error for unsupported OS during debug build
+             panic!("Unsupported OS for debug build path construction");
+        };
+
+        Ok((vendor_dir.join(binary_name), vendor_dir.clone())) // Use vendor dir as CWD
+    } else {
+        // RELEASE MODE: Tauri places externalBin next to the main executable,
+        // NOT necessarily in the resource dir like resources.
+        let exe_dir = std::env::current_exe()
+             .ok().and_then(|p| p.parent().map(|p| p.to_path_buf()))
+             .ok_or_else(|| "Could not determine executable directory in release build".to_string())?;
+
+        // Assume whisper binary is in the same directory as the main app executable in release
+        let binary_name_release = if cfg!(target_os = "windows") {
+            "whisper.exe" // In release, it should have the simple name next to app exe
+        } else {
+            "whisper" // No extension on Linux/macOS
+        };
+
+        Ok((exe_dir.join(binary_name_release), exe_dir.clone()))
+    }
+}
+
+// Resolve a model file name: a copy downloaded through the model manager wins over the bundled one
+pub(crate) fn resolve_model_path(app_handle: &AppHandle, model_name: &str) -> PathBuf {
+    if let Some(path) = crate::model_manager::downloaded_model_path(app_handle, model_name) {
+        return path;
+    }
+    crate::model_manager::bundled_models_dir(app_handle)
+        .unwrap_or_default()
+        .join(model_name)
+}
+
+// Helper function to convert to WAV with predictable output path & error checking
+// Killed after the configured ffmpeg timeout, or when `cancel` is set.
+pub(crate) async fn run_ffmpeg_conversion(
+    input_path: &Path,
+    output_path: &Path,
+    _app_handle: &AppHandle,
+    cancel: Option<Arc<JobControl>>,
+) -> Result<(), TranscriptionError> {
+    println!("[RUST FFMPEG] Converting {} to 16kHz WAV at {}", input_path.display(), output_path.display());
+
+    let (ffmpeg_path, ffmpeg_cwd) = resolve_ffmpeg_path()?;
+
     // Execute FFmpeg Command
     let mut command = Command::new(&ffmpeg_path);
     command.current_dir(&ffmpeg_cwd)
@@ -169,25 +303,21 @@ async fn run_ffmpeg_conversion(input_path: &Path, output_path: &Path, _app_handl
         .arg("-c:a")
         .arg("pcm_s16le")
         .arg("-y")
-        .arg(output_path)
-        .stdout(Stdio::null())
-        .stderr(Stdio::piped());
-
-    let output = command.output() // Use output() to capture stderr
-        .map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let err_msg = format!("ffmpeg command failed with status: {}. Stderr: {}", output.status, stderr.trim());
-        eprintln!("[RUST FFMPEG ERROR] {}", err_msg);
-        return Err(err_msg);
+        .arg(output_path);
+
+    let timeout = SETTINGS.lock().unwrap().process_timeouts.ffmpeg_timeout();
+    if let Err(e) = process_runner::run(command, timeout, cancel).await {
+        let error = TranscriptionError::from_process("ffmpeg", e);
+        eprintln!("[RUST FFMPEG ERROR] {}", error);
+        let _ = fs::remove_file(output_path); // May be half-written
+        return Err(error);
     }
 
     // Verify output file exists and has size
     if !output_path.exists() || fs::metadata(output_path).map(|m| m.len()).unwrap_or(0) == 0 {
          let err_msg = format!("ffmpeg conversion failed: Output file {} is missing or empty.", output_path.display());
          eprintln!("[RUST FFMPEG ERROR] {}", err_msg);
-         return Err(err_msg);
+         return Err(err_msg.into());
     }
 
     let size = fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
@@ -207,24 +337,48 @@ pub async fn transcribe_audio_file(
     duration_seconds: Option<i32>, // NEW ARGUMENT for recording duration
     timezone: Option<String> // NEW ARGUMENT for user timezone
 ) -> Result<String, String> {
-    // Starting transcription
-
-    // Check if transcription is already in progress
-    if TRANSCRIPTION_IN_PROGRESS.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-        println!("[RUST DEBUG] Another transcription is already in progress, skipping this request");
-        let error_message = "Another transcription is already in progress".to_string();
-        error!("[RUST Emit Error] Emitting fethr-error-occurred: {}", error_message);
-        if let Err(emit_err) = app_handle.emit_all("fethr-error-occurred", error_message.clone()) {
-            error!("[RUST ERROR] Failed to emit fethr-error-occurred event: {}", emit_err);
-        }
-        return Err(error_message);
+    // Starting transcription. Files are never translated: the Shift flag belongs to the live take
+    // Waits for any transcription still running instead of dropping this one
+    let mut job = transcription_queue::submit(&app_handle).map_err(|e| queue_full_error(&app_handle, e))?;
+    let result = transcribe_job(&mut job, app_handle, audio_path, auto_paste, user_id_opt, access_token_opt, duration_seconds, timezone, None, false).await; // Shift's translate flag belongs to the live take
+    println!("[RUST DEBUG] transcribe_local_audio_impl completed. Success? {}", result.is_ok());
+    job.finish(&result);
+    result
+}
+
+// The audio stays where it is: a dictation WAV is offered again by crash recovery on next launch
+pub(crate) fn queue_full_error(app_handle: &AppHandle, e: String) -> String {
+    let error_message = format!("{}; the recording was kept and will be offered for recovery", e);
+    error!("[RUST Emit Error] Emitting fethr-error-occurred: {}", error_message);
+    if let Err(emit_err) = app_handle.emit_all("fethr-error-occurred", error_message.clone()) {
+        error!("[RUST ERROR] Failed to emit fethr-error-occurred event: {}", emit_err);
     }
+    error_message
+}
 
-    // Ensure lock is released even on panic
-    scopeguard::defer!({
-        TRANSCRIPTION_IN_PROGRESS.store(false, Ordering::SeqCst);
-        println!("[RUST DEBUG] Transcription lock released via scopeguard");
-    });
+// Run a submitted job once its turn comes. The caller finishes the job - after pasting,
+// so the next dictation can't paste first. `translation_target` is taken from the
+// translation flag at submit time; by the time the job runs the next take may have re-armed it.
+// A command-mode take (`is_command`) is an instruction, not text: it isn't kept in history.
+#[allow(clippy::too_many_arguments)]
+pub async fn transcribe_job(
+    job: &mut Job,
+    app_handle: AppHandle,
+    audio_path: String,
+    auto_paste: bool,
+    user_id_opt: Option<String>,
+    access_token_opt: Option<String>,
+    duration_seconds: Option<i32>,
+    timezone: Option<String>,
+    translation_target: Option<String>,
+    is_command: bool,
+) -> Result<String, String> {
+    if let Err(e) = job.wait_turn().await {
+        // Cancelled while queued: the recording is discarded
+        info!("[Transcription] Job {} did not run: {}", job.id(), e);
+        cleanup_files(Path::new(&audio_path), None::<&Path>);
+        return Err(e);
+    }
 
     // Get auto_paste setting from config if not provided
     let effective_auto_paste = {
@@ -239,21 +393,22 @@ pub async fn transcribe_audio_file(
     };
     
     // Call the implementation with appropriate auto_paste
-    let result = transcribe_local_audio_impl(
+    transcribe_local_audio_impl(
         app_handle, // Pass app_handle
         audio_path, 
         effective_auto_paste, 
         user_id_opt,      // Pass new argument
         access_token_opt,  // Pass new argument
         duration_seconds,  // Pass duration
-        timezone          // Pass timezone
-    ).await;
-    println!("[RUST DEBUG] transcribe_local_audio_impl completed. Success? {}", result.is_ok());
-    
-    result
+        timezone,          // Pass timezone
+        translation_target,
+        is_command,
+        job.control(),
+    ).await
 }
 
 // The main implementation function - now returns only the transcription text
+#[allow(clippy::too_many_arguments)]
 pub async fn transcribe_local_audio_impl(
     app_handle: AppHandle, // Add app_handle here
     wav_path_in: String,
@@ -261,18 +416,340 @@ pub async fn transcribe_local_audio_impl(
     user_id_opt: Option<String>,    // NEW ARGUMENT
     access_token_opt: Option<String>, // NEW ARGUMENT
     duration_seconds: Option<i32>, // NEW ARGUMENT for recording duration
-    timezone: Option<String> // NEW ARGUMENT for user timezone
+    timezone: Option<String>, // NEW ARGUMENT for user timezone
+    translation_target: Option<String>, // Target language if this take was a translation dictation
+    is_command: bool, // Command-mode instruction: no history entry, no retained audio
+    job_control: Arc<JobControl>, // Cancellation of the queued job
 ) -> Result<String, String> {
     // Processing audio file
     log::info!("[Transcription] Starting transcription with timezone: {:?}", timezone);
 
-    // --- Get settings from global config (model name, language only now) ---
-    let (model_name_string, language_string) = {
+    let (configured_model, retain_audio) = {
+        let settings_guard = config::SETTINGS.lock().unwrap();
+        (settings_guard.model_name.clone(), settings_guard.audio_retention.enabled && !is_command)
+    };
+
+    // "auto": pick per recording from benchmark results, audio length and the latency budget
+    let model_name_string = if configured_model == crate::model_selector::AUTO_MODEL {
+        let audio_seconds = crate::model_selector::wav_duration_seconds(Path::new(&wav_path_in))
+            .or(duration_seconds.map(|s| s as f64))
+            .unwrap_or(0.0);
+        crate::model_selector::select_model_for(&app_handle, audio_seconds)
+    } else {
+        configured_model
+    };
+
+    // Translation dictation: English targets are translated by whisper inside the pipeline
+    let translate_with_whisper = translation_target.as_deref() == Some(language::ENGLISH);
+
+    let PipelineOutput { result, audio_file, dictionary_words, language, language_confidence, whisper_translation, suspect_segments, word_confidence } =
+        run_whisper_pipeline(app_handle.clone(), wav_path_in, model_name_string, retain_audio, translate_with_whisper, Some(job_control.clone())).await?;
+    let TranscriptionResult { text: trimmed_output, model_used } = result;
+
+    // Cancelled while whisper was running: no history, stats or paste
+    if job_control.is_cancelled() {
+        info!("[Transcription] Job was cancelled, discarding the result");
+        return Err("Transcription cancelled".to_string());
+    }
+
+    let translation = match translation_target {
+        Some(target) if !trimmed_output.is_empty() => {
+            translate_transcript(&app_handle, &trimmed_output, language.as_deref(), target, whisper_translation).await
+        }
+        _ => None,
+    };
+    // What gets pasted: the translation when there is one, the original otherwise
+    let output_text = translation.as_ref().map_or_else(|| trimmed_output.clone(), |t| t.text.clone());
+
+    // Track dictionary word usage for smart prompt rotation
+    if !dictionary_words.is_empty() {
+        crate::word_usage_tracker::UsageTracker::record_transcription_words(&trimmed_output, &dictionary_words);
+    }
+    
+    let success_status = TranscriptionStatus::Complete { text: output_text.clone() };
+    let _ = app_handle.emit_all("transcription_status_changed", success_status); // Use snake_case event name
+
+    // Save transcription to history; a spoken command isn't a dictation
+    if !trimmed_output.is_empty() && !is_command {
+        info!("[RUST HISTORY] Saving transcription result to history file");
+        
+        let new_entry = HistoryEntry {
+            timestamp: Utc::now(),
+            text: trimmed_output.clone(),
+            corrections: None, // No correction tracking for now
+            model_used: Some(model_used),
+            audio_file,
+            language,
+            language_confidence,
+            translation,
+            suspect_segments,
+            word_confidence,
+            revisions: Vec::new(),
+            ai_output: None,
+            derived_versions: Vec::new(),
+            edited: false,
+        };
+        
+        let _history_guard = lock_history();
+        match get_history_path(&app_handle) {
+            Ok(history_path) => {
+                info!("[RUST HISTORY] History file path (via helper): {:?}", history_path);
+                
+                // Retained audio is only pruned against a history that was actually read;
+                // otherwise every retained file would look unlinked and be deleted
+                let mut history_read = true;
+
+                // Read existing history file or default to empty JSON array
+                let history_content = match fs::read_to_string(&history_path) {
+                    Ok(content) => {
+                        info!("[RUST HISTORY] Read existing history file");
+                        content
+                    },
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        info!("[RUST HISTORY] No history file yet");
+                        "[]".to_string() // Default to empty array
+                    },
+                    Err(e) => {
+                        warn!("[RUST HISTORY] Failed to read history file: {}", e);
+                        history_read = false;
+                        "[]".to_string() // Default to empty array
+                    }
+                };
+                
+                // Parse JSON to vector of HistoryEntry
+                let mut history_vec: Vec<HistoryEntry> = match serde_json::from_str::<Vec<HistoryEntry>>(&history_content) {
+                    Ok(vec) => {
+                        info!("[RUST HISTORY] Successfully parsed history JSON with {} entries", vec.len());
+                        vec
+                    },
+                    Err(e) => {
+                        warn!("[RUST HISTORY] Failed to parse history JSON: {}. Starting fresh.", e);
+                        history_read = false;
+                        Vec::new() // Default to empty vector
+                    }
+                };
+                
+                // Append new entry
+                history_vec.push(new_entry);
+                info!("[RUST HISTORY] Added new entry, history now has {} entries", history_vec.len());
+                
+                // Cap history if needed
+                if history_vec.len() > MAX_HISTORY_ENTRIES {
+                    let removed_count = history_vec.len() - MAX_HISTORY_ENTRIES;
+                    history_vec.drain(0..removed_count);
+                    info!("[RUST HISTORY] Capped history by removing {} oldest entries, now at {} entries", 
+                         removed_count, history_vec.len());
+                }
+                
+                // Drop retained audio that fell out of history or out of the retention limits
+                if history_read {
+                    crate::audio_retention::enforce_retention_limits(&app_handle, &mut history_vec);
+                } else {
+                    warn!("[AudioRetention] History couldn't be read; skipping retention limits this time");
+                }
+                
+                // Serialize back to JSON
+                match serde_json::to_string_pretty(&history_vec) {
+                    Ok(json) => {
+                        // Write to file
+                        match fs::write(&history_path, json) {
+                            Ok(_) => {
+                                info!("[RUST HISTORY] Successfully wrote history to file");
+                                info!("[RUST HISTORY] Successfully wrote updated history. Emitting update event.");
+                                app_handle.emit_all("fethr-history-updated", ()).unwrap_or_else(|e| {
+                                    error!("[RUST HISTORY] Failed to emit history update event: {}", e);
+                                });
+                            },
+                            Err(e) => error!("[RUST HISTORY] Failed to write history to file: {}", e)
+                        }
+                    },
+                    Err(e) => error!("[RUST HISTORY] Failed to serialize history to JSON: {}", e)
+                }
+            },
+            Err(e) => error!("[RUST HISTORY] Failed to get history file path via helper: {}", e)
+        }
+    }
+
+    // Note: Auto-paste is now handled in audio_manager_rs.rs
+    if auto_paste {
+        println!("[RUST DEBUG] Auto-paste is enabled but will be handled by the calling function.");
+    } else {
+        println!("[RUST DEBUG] Auto-paste is disabled.");
+    }
+
+    // --- BEGIN SUPABASE WORD COUNT UPDATE ---
+    println!("[DEBUG] Auth check - user_id_opt: {:?}, access_token_opt present: {}", 
+        user_id_opt, access_token_opt.is_some());
+    
+    if let (Some(user_id), Some(access_token)) = (user_id_opt, access_token_opt) {
+        println!("[DEBUG] Auth check PASSED - user_id: {}", user_id);
+        if !user_id.is_empty() && !access_token.is_empty() {
+            let words_transcribed = trimmed_output.split_whitespace().count() as i32;
+            log::info!(
+                "[Transcription] User details found (User ID: {}), proceeding with word count update for {} words.",
+                user_id, // Log only user_id for privacy if access_token is sensitive
+                words_transcribed
+            );
+
+            if words_transcribed > 0 {
+                let app_handle_clone_for_supabase = app_handle.clone(); // Clone for the async block
+                
+                // Get or create session ID
+                let session_id = get_or_create_session();
+                
+                // Update both word usage and user statistics
+                log::info!("[Transcription] About to call usage and stats updates...");
+                let usage_result = crate::supabase_manager::execute_increment_word_usage_rpc(user_id.clone(), access_token.clone(), words_transcribed).await;
+                log::info!("[Transcription] Usage update complete, now calling stats sync...");
+                let stats_result = crate::user_statistics::sync_transcription_to_supabase(
+                    words_transcribed as i64, 
+                    &user_id, 
+                    &access_token, 
+                    duration_seconds,
+                    Some(session_id.to_string()),
+                    timezone.clone() // Pass user timezone
+                ).await;
+                log::info!("[Transcription] Stats sync complete");
+                
+                match (usage_result, stats_result) {
+                    (Ok(_), Ok(_)) => {
+                        log::info!("[Transcription] Word usage and statistics update process reported success.");
+                        // Successfully updated or limit was fine, now emit event
+                        log::info!("[Transcription] Emitting 'word_usage_updated' event to frontend.");
+                        if let Err(e) = app_handle_clone_for_supabase.emit_all("word_usage_updated", ()) {
+                            log::error!("[Transcription] Failed to emit 'word_usage_updated' event: {}", e);
+                        }
+                    }
+                    (Err(usage_err), _) => {
+                        log::error!("[Transcription] Word usage update process failed: {}", usage_err);
+                        // Propagate this error. This will become the error for transcribe_local_audio_impl
+                        // The frontend should receive this error message.
+                        // We still emit "word_usage_updated" because an attempt was made, and SettingsPage might want to refresh.
+                        log::info!("[Transcription] Emitting 'word_usage_updated' event to frontend despite error (to allow UI refresh).");
+                        if let Err(ev_err) = app_handle_clone_for_supabase.emit_all("word_usage_updated", ()) {
+                            log::error!("[Transcription] Failed to emit 'word_usage_updated' event after error: {}", ev_err);
+                        }
+                        return Err(usage_err); // Return the error from execute_increment_word_usage_rpc
+                    }
+                    (Ok(_), Err(stats_err)) => {
+                        // Usage update succeeded but stats update failed - queue for retry
+                        log::error!("[Transcription] Statistics update failed: {}, queuing for retry", stats_err);
+                        
+                        // Queue the failed stats update for retry
+                        if let Err(queue_err) = crate::stats_queue::enqueue_stats_update(
+                            user_id.clone(),
+                            words_transcribed as i64,
+                            duration_seconds.unwrap_or(0),
+                            session_id.to_string(),
+                        ) {
+                            log::error!("[Transcription] Failed to queue stats update: {}", queue_err);
+                        } else {
+                            log::info!("[Transcription] Stats update queued for retry");
+                        }
+                        
+                        log::info!("[Transcription] Emitting 'word_usage_updated' event to frontend.");
+                        if let Err(e) = app_handle_clone_for_supabase.emit_all("word_usage_updated", ()) {
+                            log::error!("[Transcription] Failed to emit 'word_usage_updated' event: {}", e);
+                        }
+                    }
+                }
+            } else {
+                log::info!("[Transcription] No words transcribed, skipping word count update.");
+            }
+        } else {
+            println!("[DEBUG] Auth credentials are empty - user_id empty: {}, access_token empty: {}", 
+                user_id.is_empty(), access_token.is_empty());
+            log::warn!("[Transcription] User ID or Access Token is empty. Skipping word count update.");
+        }
+    } else {
+        println!("[DEBUG] Auth check FAILED - missing credentials");
+        log::warn!("[Transcription] User ID or Access Token not found in settings (or not passed). Skipping word count update.");
+    }
+    // --- END SUPABASE WORD COUNT UPDATE ---
+
+    // Return the text
+    Ok(output_text)
+}
+
+// Produce the translation for a translation-dictation take. Failures are reported
+// to the UI but never lose the dictation - the original text is pasted instead.
+async fn translate_transcript(
+    app_handle: &AppHandle,
+    original: &str,
+    source_language: Option<&str>,
+    target_language: String,
+    whisper_translation: Option<String>,
+) -> Option<TranslationRecord> {
+    let result = match crate::translation::route_for(source_language, &target_language) {
+        None => {
+            info!("[Transcription] Text is already in '{}', nothing to translate", target_language);
+            return None;
+        }
+        Some(TranslationMethod::Whisper) => whisper_translation
+            .ok_or_else(|| "Whisper could not translate this recording".to_string())
+            .map(|text| (text, TranslationMethod::Whisper)),
+        Some(TranslationMethod::AiAction) => {
+            crate::translation::translate_with_ai(app_handle, original.to_string(), target_language.clone()).await
+                .map(|text| (text, TranslationMethod::AiAction))
+        }
+    };
+
+    match result {
+        Ok((text, method)) if !text.is_empty() => {
+            info!("[Transcription] Translated to '{}' via {:?}", target_language, method);
+            Some(TranslationRecord { target_language, text, method })
+        }
+        Ok(_) => None,
+        Err(e) => {
+            error!("[Transcription] Translation to '{}' failed: {}", target_language, e);
+            let _ = app_handle.emit_all("fethr-translation-failed", e);
+            None
+        }
+    }
+}
+
+/// Output of the whisper stage, before any history/stats side effects
+pub struct PipelineOutput {
+    pub result: TranscriptionResult,
+    pub audio_file: Option<String>, // Retained compressed audio, if enabled
+    pub dictionary_words: Vec<String>,
+    pub language: Option<String>,
+    pub language_confidence: Option<f32>, // Only when whisper auto-detected the language
+    pub whisper_translation: Option<String>, // English translation, when requested
+    pub suspect_segments: Vec<SuspectSegment>, // Hallucination filter hits
+    pub word_confidence: Vec<WordConfidence>, // Empty if whisper's JSON output wasn't available
+}
+
+// Convert, run whisper and post-process (dictionary + smart formatting).
+// Deletes its input WAV. No history, stats or paste - shared by dictation and re-transcription.
+pub async fn run_whisper_pipeline(
+    app_handle: AppHandle,
+    wav_path_in: String,
+    model_name_string: String,
+    retain_audio: bool,
+    translate_to_english: bool, // Also run whisper's --translate pass (translation dictation)
+    cancel: Option<Arc<JobControl>>, // Kills whisper/ffmpeg when the job is cancelled
+) -> Result<PipelineOutput, String> {
+    // --- Get settings from global config (language only; model is chosen by the caller) ---
+    let (language_string, allowed_languages, decoding, hallucination_settings, confidence_settings, backend_settings, whisper_timeout) = {
         let settings_guard = config::SETTINGS.lock().unwrap();
-        (settings_guard.model_name.clone(), settings_guard.language.clone())
+        (settings_guard.language.clone(), settings_guard.allowed_languages.clone(),
+         settings_guard.whisper_decoding.clone(), settings_guard.hallucination_filter.clone(),
+         settings_guard.word_confidence.clone(), settings_guard.transcription_backends.clone(),
+         settings_guard.process_timeouts.whisper_timeout())
     };
+    let mut language_mode = language::resolve_language_mode(&language_string, &allowed_languages);
+
+    // English gets the .en model, anything else the multilingual one - if that twin is installed
+    let installed_models = crate::model_manager::list_installed_model_files(&app_handle);
+    let model_name_string = language::model_for_language(&model_name_string, &language_mode, &installed_models);
+    if language::is_english_only_model(&model_name_string) && language_mode.needs_multilingual_model() {
+        warn!("[Transcription] '{}' is English-only and no multilingual version is installed; transcribing as English",
+              model_name_string);
+        language_mode = LanguageMode::Fixed(language::ENGLISH.to_string());
+    }
     println!("[RUST DEBUG transcription.rs] Using Model: '{}', Language: '{}'", model_name_string, language_string);
-    info!("[RUST WHISPER PREP] Language read from settings: {}", language_string);
+    info!("[RUST WHISPER PREP] Language read from settings: {}, mode: {:?}", language_string, language_mode);
 
     // --- BEGINNING OF INSERTED BLOCK 1: Fetch and Prepare Dictionary Prompt ---
     let dictionary_words = match dictionary_manager::get_dictionary(app_handle.clone()) {
@@ -338,7 +815,12 @@ pub async fn transcribe_local_audio_impl(
             prompt_parts.push(format!("Also includes {}", other.join(", ")));
         }
         
-        let prompt = prompt_parts.join(". ") + ".";
+        // The English sentence framing would pull detection and decoding towards English
+        let prompt = if language_mode == LanguageMode::Fixed(language::ENGLISH.to_string()) {
+            prompt_parts.join(". ") + "."
+        } else {
+            prompt_words.join(", ")
+        };
         
         // Log info about prompt rotation
         if total_words > prompt_words.len() {
@@ -353,128 +835,35 @@ pub async fn transcribe_local_audio_impl(
     };
     // --- END OF INSERTED BLOCK 1 ---
 
-    // --- Resolve Paths (Debug vs Release) ---
-
-    let whisper_binary_path: PathBuf;
-    let model_path: PathBuf;
-    let whisper_working_dir: PathBuf;
-
-    if cfg!(debug_assertions) {
-        // DEBUG MODE: Point to the source vendor directory using CARGO_MANIFEST_DIR
-        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")); // Path to src-tauri
-        let vendor_dir = manifest_dir.join("vendor");
-
-        println!("[RUST DEBUG transcription.rs] Detected DEBUG build. Using source vendor path: {}", vendor_dir.display());
-
-        // --- Construct platform-specific binary name for DEBUG ---
-        // This needs to match the actual file name required by the build script
-        let binary_name = if cfg!(target_os = "windows") {
-            // Assuming x86_64 MSVC build target, adjust if different
-             "whisper-x86_64-pc-windows-msvc.exe"
-        } else if cfg!(target_os = "macos") {
-             // Assuming x86_64 or aarch64, adjust target triple as needed
-             if cfg!(target_arch = "aarch64") {
-                 "whisper-aarch64-apple-darwin"
-             } else {
-                 "whisper-x86_64-apple-darwin"
-             }
-        } else if cfg!(target_os = "linux") {
-             // Assuming x86_64 GNU target, adjust target triple as needed
-             "whisper-x86_64-unknown-linux-gnu"
-        } else {
-            // Fallback or error for unsupported OS during debug build
-             panic!("Unsupported OS for debug build path construction");
-        };
-
-        whisper_binary_path = vendor_dir.join(binary_name);
-        model_path = vendor_dir.join("models").join(&model_name_string);
-        whisper_working_dir = vendor_dir.clone(); // Use vendor dir as CWD
-
-        // Using development Whisper binaries
-
-    } else {
-        // RELEASE MODE: Use Tauri's resource resolver
-        // This part remains tricky and might need testing in a real release build.
-        // We'll try resolving the external binary name relative to the resource dir.
-        println!("[RUST DEBUG transcription.rs] Detected RELEASE build.");
-        let resource_path = resource_dir(app_handle.package_info(), &app_handle.env())
-            .ok_or_else(|| "Failed to resolve resource directory".to_string())?;
-        println!("[RUST DEBUG transcription.rs] Resource Dir: {}", resource_path.display());
-
-        // Construct the expected release binary name based on externalBin entry and target
-        // Note: Tauri usually places externalBin next to the main executable,
-        // NOT necessarily in the resource dir like resources.
-        // Let's try getting the *executable's directory* instead.
-        let exe_dir = std::env::current_exe()
-             .ok().and_then(|p| p.parent().map(|p| p.to_path_buf()))
-             .ok_or_else(|| "Could not determine executable directory in release build".to_string())?;
-
-        println!("[RUST DEBUG transcription.rs] Executable Dir: {}", exe_dir.display());
-
-        // Assume whisper binary is in the same directory as the main app executable in release
-        let binary_name_release = if cfg!(target_os = "windows") {
-            "whisper.exe" // In release, it should have the simple name next to app exe
-        } else {
-            "whisper" // No extension on Linux/macOS
-        };
-
-        whisper_binary_path = exe_dir.join(binary_name_release);
-        // Models are still in the resource directory
-        model_path = resource_path.join(format!("vendor/models/{}", model_name_string));
-        whisper_working_dir = exe_dir.clone(); // Use exe dir as CWD? Or resource dir? Try exe dir.
-
-        println!("[RUST DEBUG transcription.rs] RELEASE PATHS (Attempted):");
-        println!("  -> Binary: {}", whisper_binary_path.display());
-        println!("  -> Model: {}", model_path.display());
-        println!("  -> CWD: {}", whisper_working_dir.display());
-    }
-    // --- End Path Resolution ---
-
-    // --- Check if paths/files exist ---
-    if !whisper_binary_path.exists() {
-        let err_msg = format!("Bundled Whisper binary not found at: {}", whisper_binary_path.display());
-        eprintln!("[RUST ERROR] {}", err_msg);
-        
-        error!("[RUST Emit Error] Emitting fethr-error-occurred: {}", err_msg);
-        if let Err(emit_err) = app_handle.emit_all("fethr-error-occurred", err_msg.clone()) {
-            error!("[RUST ERROR] Failed to emit fethr-error-occurred event: {}", emit_err);
-        }
-        
-        // Call signal_reset_complete to ensure UI doesn't get stuck
-        let _ = crate::signal_reset_complete(app_handle.clone());
-        
-        return Err(err_msg);
-    }
-     if !model_path.exists() {
-        let err_msg = format!("Bundled Whisper model not found at: {}", model_path.display());
-        eprintln!("[RUST ERROR] {}", err_msg);
-        
-        error!("[RUST Emit Error] Emitting fethr-error-occurred: {}", err_msg);
-        if let Err(emit_err) = app_handle.emit_all("fethr-error-occurred", err_msg.clone()) {
-            error!("[RUST ERROR] Failed to emit fethr-error-occurred event: {}", emit_err);
-        }
-        
-        // Call signal_reset_complete to ensure UI doesn't get stuck
-        let _ = crate::signal_reset_complete(app_handle.clone());
-        
-        return Err(err_msg);
-    }
-    // --- End Resource Path Resolution ---
+    // --- Transcription backends, in the configured fallback order ---
+    let local_model = LocalModel {
+        path: resolve_model_path(&app_handle, &model_name_string),
+        name: model_name_string.clone(),
+        decoding,
+        timeout: whisper_timeout,
+    };
+    println!("[RUST DEBUG transcription.rs] Backends: {:?}, local model: {}",
+             backend_settings.order, local_model.path.display());
+    let backends = transcription_backend::build_backends(&backend_settings, local_model);
 
     let input_wav_path = Path::new(&wav_path_in);
     let mut converted_wav_path_opt: Option<PathBuf> = None;
 
     // --- FFMPEG resampling logic ---
     let unique_id = Uuid::new_v4().to_string();
-    let temp_dir = std::env::temp_dir();
-    let converted_wav_path = temp_dir.join(format!("fethr_converted_{}.wav", unique_id));
+    let recordings_dir = crate::recordings::recordings_dir_or_temp(&app_handle);
+    let converted_wav_path = recordings_dir.join(format!("{}{}.wav", crate::recordings::CONVERTED_PREFIX, unique_id));
     println!("[RUST DEBUG] Attempting FFmpeg resampling to: {}", converted_wav_path.display());
 
-    match run_ffmpeg_conversion(input_wav_path, &converted_wav_path, &app_handle).await {
+    match run_ffmpeg_conversion(input_wav_path, &converted_wav_path, &app_handle, cancel.clone()).await {
         Ok(_) => {
             println!("[RUST DEBUG] FFmpeg resampling successful.");
             converted_wav_path_opt = Some(converted_wav_path.clone());
         },
+        Err(TranscriptionError::Cancelled) => {
+            cleanup_files(input_wav_path, None::<&Path>);
+            return Err(report_pipeline_failure(&app_handle, TranscriptionError::Cancelled));
+        }
         Err(e) => {
             println!("[RUST DEBUG ERROR] FFmpeg resampling failed: {}. Proceeding with original.", e);
         }
@@ -507,27 +896,9 @@ pub async fn transcribe_local_audio_impl(
         
         return Err(error_msg);
     }
-    // Processing audio with Whisper
-
-    // --- Prepare Whisper command ---
-    // Starting Whisper transcription
-
-    // --- Setup Whisper command ---
-    let mut command = std::process::Command::new(&whisper_binary_path);
-    command.current_dir(&whisper_working_dir)
-           .arg("-m").arg(&model_path); // Model argument
-
-    // Add language argument if not auto
-    if language_string != "auto" {
-        command.arg("-l").arg(&language_string);
-    }
-
-    command.arg("--split-on-word"); // Keep this from the previous fix
-    
-    command.arg("-nt"); // No Timestamps flag - RETAINED
 
     // --- ENHANCED PROMPT ADDITION ---
-    if !initial_prompt_string.is_empty() {
+    let prompt = if !initial_prompt_string.is_empty() {
         // Always use prompts for all models - removing tiny model restriction
         log::info!(
             "[Transcription] Using initial prompt ({} chars) for model '{}': \"{}\"", 
@@ -535,272 +906,250 @@ pub async fn transcribe_local_audio_impl(
             model_name_string,
             initial_prompt_string 
         ); 
-        command.arg("--prompt").arg(&initial_prompt_string);
+        Some(initial_prompt_string)
     } else {
         log::info!("[Transcription] Dictionary is empty or failed to load; no prompt will be passed.");
-    }
-    // --- END RE-ENABLE PROMPT ---
-           
-    command.arg(whisper_input_path); // Input file
+        None
+    };
+    let backend_request = |language_arg: &str, translate: bool| BackendRequest {
+        audio_path: whisper_input_path.to_path_buf(),
+        language: language_arg.to_string(),
+        prompt: prompt.clone(),
+        translate,
+        cancel: cancel.clone(),
+    };
 
-    // --- Run Whisper command and read output ---
-    // Running Whisper transcription
-    let output = match command.output() {
-        Ok(output) => output,
-        Err(e) => {
-            let err_msg = format!("Failed to execute Whisper: {}", e);
-            eprintln!("[RUST ERROR] {}", err_msg);
-            
-            error!("[RUST Emit Error] Emitting fethr-error-occurred: {}", err_msg);
-            if let Err(emit_err) = app_handle.emit_all("fethr-error-occurred", err_msg.clone()) {
-                error!("[RUST ERROR] Failed to emit fethr-error-occurred event: {}", emit_err);
+    // --- Run the transcription ---
+    let mut transcript_result = transcription_backend::transcribe(backends.clone(), backend_request(language_mode.whisper_arg(), false)).await;
+
+    // Detection outside the allowed list: redo it in the first allowed language
+    let mut forced_language = None;
+    if let Ok(first_transcript) = &transcript_result {
+        if let Some(code) = &first_transcript.language {
+            info!("[Transcription] Whisper detected language '{}' (p = {:.3})", code, first_transcript.language_confidence.unwrap_or(0.0));
+            if !language::is_allowed(&language_mode, code) {
+                if let Some(fallback) = language::fallback_language(&language_mode) {
+                    info!("[Transcription] '{}' is not an allowed language, re-running as '{}'", code, fallback);
+                    transcript_result = transcription_backend::transcribe(backends.clone(), backend_request(&fallback, false)).await;
+                    forced_language = Some(fallback);
+                }
             }
-            
+        }
+    }
+
+    let transcript = match transcript_result {
+        Ok(transcript) => transcript,
+        Err(error) => {
             cleanup_files(input_wav_path, converted_wav_path_opt.as_ref().map(|v| &**v));
-            let _ = app_handle.emit_all("transcription_status_changed", TranscriptionStatus::Failed(err_msg.clone())); // Use snake_case
-            
-            // Call signal_reset_complete to ensure UI doesn't get stuck
-            let _ = crate::signal_reset_complete(app_handle.clone());
-            
-            return Err(err_msg);
+            return Err(report_pipeline_failure(&app_handle, error));
         }
     };
 
-    let exit_status = output.status;
-    let stdout_bytes = output.stdout;
-    let stderr_bytes = output.stderr;
-    let stdout_text = String::from_utf8_lossy(&stdout_bytes).to_string();
-    let stderr_text = String::from_utf8_lossy(&stderr_bytes).to_string();
+    let (text_language, language_confidence) = match (forced_language, &transcript.language, &language_mode) {
+        (Some(forced), _, _) => (Some(forced), None),
+        (None, Some(code), LanguageMode::Detect { .. }) => (Some(code.clone()), transcript.language_confidence),
+        (None, _, LanguageMode::Fixed(fixed)) => (Some(fixed.clone()), None),
+        (None, None, LanguageMode::Detect { .. }) => (None, None),
+    };
+    let model_used = transcript.model_used;
+    let word_confidence = transcript.words;
+
+    // Hallucinations are removed from the raw output, before dictionary correction can dress them up
+    let speech_seconds = hallucination_filter::wav_speech_seconds(whisper_input_path);
+    let filtered = hallucination_filter::filter_output(&transcript.text, speech_seconds, &hallucination_settings);
+
+    // Translation dictation to English: a second pass in the source language with --translate
+    let whisper_translation = if translate_to_english && text_language.as_deref() != Some(language::ENGLISH) {
+        let source_arg = text_language.clone().unwrap_or_else(|| language::AUTO_LANGUAGE.to_string());
+        info!("[Transcription] Running whisper translation pass from '{}' to English", source_arg);
+        match transcription_backend::transcribe(backends.clone(), backend_request(&source_arg, true)).await {
+            Ok(translation) => {
+                let filtered_translation = hallucination_filter::filter_output(&translation.text, speech_seconds, &hallucination_settings);
+                hallucination_filter::log_for_review(&app_handle, &translation.model_used, speech_seconds, &translation.text, &filtered_translation.suspects);
+                let translated = whisper_output_trim(&filtered_translation.text, &app_handle, language::ENGLISH, None);
+                if translated.is_empty() { None } else { Some(translated) }
+            }
+            Err(TranscriptionError::Cancelled) => {
+                cleanup_files(input_wav_path, converted_wav_path_opt.as_ref().map(|v| &**v));
+                return Err(report_pipeline_failure(&app_handle, TranscriptionError::Cancelled));
+            }
+            Err(e) => {
+                warn!("[Transcription] Whisper translation pass failed: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    // Whisper processing complete
+    // Keep a compressed copy linked to the history entry before the WAVs are deleted
+    let audio_file = if retain_audio {
+        crate::audio_retention::retain_audio(&app_handle, whisper_input_path).await
+    } else {
+        None
+    };
 
     // Clean up temporary files
     cleanup_files(input_wav_path, converted_wav_path_opt.as_ref().map(|v| &**v));
 
-    // Process the result
-    if exit_status.success() {
-        // Process the output
-        let mut trimmed_output = whisper_output_trim(&stdout_text, &app_handle);
-        println!("[RUST DEBUG] Transcription successful. Raw result: {}", trimmed_output);
-        
-        // Apply smart formatting if enabled
-        let smart_formatting_enabled = {
-            let settings_guard = SETTINGS.lock().unwrap();
-            settings_guard.smart_formatting.enabled
-        };
+    // Process the output
+    let formatting_language = text_language.as_deref().unwrap_or(language::ENGLISH);
+    hallucination_filter::log_for_review(&app_handle, &model_used, speech_seconds, &transcript.text, &filtered.suspects);
+    let gate = ConfidenceGate { words: &word_confidence, threshold: confidence_settings.low_confidence_threshold };
+    let gate = (confidence_settings.gate_corrections && !word_confidence.is_empty()).then_some(&gate);
+    let mut trimmed_output = whisper_output_trim(&filtered.text, &app_handle, formatting_language, gate);
+    println!("[RUST DEBUG] Transcription successful. Raw result: {}", trimmed_output);
+    
+    // Apply smart formatting if enabled
+    let smart_formatting_enabled = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        settings_guard.smart_formatting.enabled
+    };
+    
+    if smart_formatting_enabled {
+        let formatter = SmartFormatter::for_language(formatting_language);
+        let formatted = formatter.format(&trimmed_output);
         
-        if smart_formatting_enabled {
-            let formatter = SmartFormatter::new();
-            let formatted = formatter.format(&trimmed_output);
-            
-            // Log formatting changes for debugging
-            if !formatted.formatting_applied.is_empty() {
-                println!("[RUST DEBUG] Smart formatting applied {} changes", formatted.formatting_applied.len());
-                for change in &formatted.formatting_applied {
-                    println!("[RUST DEBUG] - {} at position {} (confidence: {})", 
-                        change.change_type, change.position, change.confidence);
-                }
+        // Log formatting changes for debugging
+        if !formatted.formatting_applied.is_empty() {
+            println!("[RUST DEBUG] Smart formatting applied {} changes", formatted.formatting_applied.len());
+            for change in &formatted.formatting_applied {
+                println!("[RUST DEBUG] - {} at position {} (confidence: {})", 
+                    change.change_type, change.position, change.confidence);
             }
-            
-            trimmed_output = formatted.text;
-            println!("[RUST DEBUG] After smart formatting: {}", trimmed_output);
-        }
-        
-        // Track dictionary word usage for smart prompt rotation
-        if !dictionary_words.is_empty() {
-            crate::word_usage_tracker::UsageTracker::record_transcription_words(&trimmed_output, &dictionary_words);
         }
         
-        let success_status = TranscriptionStatus::Complete { text: trimmed_output.clone() };
-        let _ = app_handle.emit_all("transcription_status_changed", success_status); // Use snake_case event name
-
-        // Save transcription to history
-        if !trimmed_output.is_empty() {
-            info!("[RUST HISTORY] Saving transcription result to history file");
-            
-            let new_entry = HistoryEntry {
-                timestamp: Utc::now(),
-                text: trimmed_output.clone(),
-                corrections: None, // No correction tracking for now
-            };
-            
-            match get_history_path(&app_handle) {
-                Ok(history_path) => {
-                    info!("[RUST HISTORY] History file path (via helper): {:?}", history_path);
-                    
-                    // Read existing history file or default to empty JSON array
-                    let history_content = match fs::read_to_string(&history_path) {
-                        Ok(content) => {
-                            info!("[RUST HISTORY] Read existing history file");
-                            content
-                        },
-                        Err(e) => {
-                            info!("[RUST HISTORY] Failed to read history file (may not exist yet): {}", e);
-                            "[]".to_string() // Default to empty array
-                        }
-                    };
-                    
-                    // Parse JSON to vector of HistoryEntry
-                    let mut history_vec: Vec<HistoryEntry> = match serde_json::from_str::<Vec<HistoryEntry>>(&history_content) {
-                        Ok(vec) => {
-                            info!("[RUST HISTORY] Successfully parsed history JSON with {} entries", vec.len());
-                            vec
-                        },
-                        Err(e) => {
-                            info!("[RUST HISTORY] Failed to parse history JSON: {}. Starting fresh.", e);
-                            Vec::new() // Default to empty vector
-                        }
-                    };
-                    
-                    // Append new entry
-                    history_vec.push(new_entry);
-                    info!("[RUST HISTORY] Added new entry, history now has {} entries", history_vec.len());
-                    
-                    // Cap history if needed
-                    if history_vec.len() > MAX_HISTORY_ENTRIES {
-                        let removed_count = history_vec.len() - MAX_HISTORY_ENTRIES;
-                        history_vec.drain(0..removed_count);
-                        info!("[RUST HISTORY] Capped history by removing {} oldest entries, now at {} entries", 
-                             removed_count, history_vec.len());
-                    }
-                    
-                    // Serialize back to JSON
-                    match serde_json::to_string_pretty(&history_vec) {
-                        Ok(json) => {
-                            // Write to file
-                            match fs::write(&history_path, json) {
-                                Ok(_) => {
-                                    info!("[RUST HISTORY] Successfully wrote history to file");
-                                    info!("[RUST HISTORY] Successfully wrote updated history. Emitting update event.");
-                                    app_handle.emit_all("fethr-history-updated", ()).unwrap_or_else(|e| {
-                                        error!("[RUST HISTORY] Failed to emit history update event: {}", e);
-                                    });
-                                },
-                                Err(e) => error!("[RUST HISTORY] Failed to write history to file: {}", e)
-                            }
-                        },
-                        Err(e) => error!("[RUST HISTORY] Failed to serialize history to JSON: {}", e)
-                    }
-                },
-                Err(e) => error!("[RUST HISTORY] Failed to get history file path via helper: {}", e)
-            }
-        }
+        trimmed_output = formatted.text;
+        println!("[RUST DEBUG] After smart formatting: {}", trimmed_output);
+    }
+    
+    Ok(PipelineOutput {
+        result: TranscriptionResult { text: trimmed_output, model_used },
+        audio_file,
+        dictionary_words,
+        language: text_language,
+        language_confidence,
+        whisper_translation,
+        suspect_segments: filtered.suspects,
+        word_confidence,
+    })
+}
 
-        // Note: Auto-paste is now handled in audio_manager_rs.rs
-        if auto_paste {
-            println!("[RUST DEBUG] Auto-paste is enabled but will be handled by the calling function.");
-        } else {
-            println!("[RUST DEBUG] Auto-paste is disabled.");
+// Tell the UI why the pipeline stopped - both the plain message and the typed error - and
+// reset the pill so it doesn't stay in Transcribing
+fn report_pipeline_failure(app_handle: &AppHandle, error: TranscriptionError) -> String {
+    let err_msg = error.to_string();
+    eprintln!("[RUST ERROR] {}", err_msg);
+
+    if error != TranscriptionError::Cancelled {
+        error!("[RUST Emit Error] Emitting fethr-error-occurred: {}", err_msg);
+        if let Err(emit_err) = app_handle.emit_all("fethr-error-occurred", err_msg.clone()) {
+            error!("[RUST ERROR] Failed to emit fethr-error-occurred event: {}", emit_err);
         }
+    }
+    if let Err(emit_err) = app_handle.emit_all("fethr-transcription-error", &error) {
+        error!("[RUST ERROR] Failed to emit fethr-transcription-error event: {}", emit_err);
+    }
+    let _ = app_handle.emit_all("transcription_status_changed", TranscriptionStatus::Failed(err_msg.clone())); // Use snake_case
 
-        // --- BEGIN SUPABASE WORD COUNT UPDATE ---
-        println!("[DEBUG] Auth check - user_id_opt: {:?}, access_token_opt present: {}", 
-            user_id_opt, access_token_opt.is_some());
-        
-        if let (Some(user_id), Some(access_token)) = (user_id_opt, access_token_opt) {
-            println!("[DEBUG] Auth check PASSED - user_id: {}", user_id);
-            if !user_id.is_empty() && !access_token.is_empty() {
-                let words_transcribed = trimmed_output.split_whitespace().count() as i32;
-                log::info!(
-                    "[Transcription] User details found (User ID: {}), proceeding with word count update for {} words.",
-                    user_id, // Log only user_id for privacy if access_token is sensitive
-                    words_transcribed
-                );
-
-                if words_transcribed > 0 {
-                    let app_handle_clone_for_supabase = app_handle.clone(); // Clone for the async block
-                    
-                    // Get or create session ID
-                    let session_id = get_or_create_session();
-                    
-                    // Update both word usage and user statistics
-                    log::info!("[Transcription] About to call usage and stats updates...");
-                    let usage_result = crate::supabase_manager::execute_increment_word_usage_rpc(user_id.clone(), access_token.clone(), words_transcribed).await;
-                    log::info!("[Transcription] Usage update complete, now calling stats sync...");
-                    let stats_result = crate::user_statistics::sync_transcription_to_supabase(
-                        words_transcribed as i64, 
-                        &user_id, 
-                        &access_token, 
-                        duration_seconds,
-                        Some(session_id.to_string()),
-                        timezone.clone() // Pass user timezone
-                    ).await;
-                    log::info!("[Transcription] Stats sync complete");
-                    
-                    match (usage_result, stats_result) {
-                        (Ok(_), Ok(_)) => {
-                            log::info!("[Transcription] Word usage and statistics update process reported success.");
-                            // Successfully updated or limit was fine, now emit event
-                            log::info!("[Transcription] Emitting 'word_usage_updated' event to frontend.");
-                            if let Err(e) = app_handle_clone_for_supabase.emit_all("word_usage_updated", ()) {
-                                log::error!("[Transcription] Failed to emit 'word_usage_updated' event: {}", e);
-                            }
-                        }
-                        (Err(usage_err), _) => {
-                            log::error!("[Transcription] Word usage update process failed: {}", usage_err);
-                            // Propagate this error. This will become the error for transcribe_local_audio_impl
-                            // The frontend should receive this error message.
-                            // We still emit "word_usage_updated" because an attempt was made, and SettingsPage might want to refresh.
-                            log::info!("[Transcription] Emitting 'word_usage_updated' event to frontend despite error (to allow UI refresh).");
-                            if let Err(ev_err) = app_handle_clone_for_supabase.emit_all("word_usage_updated", ()) {
-                                log::error!("[Transcription] Failed to emit 'word_usage_updated' event after error: {}", ev_err);
-                            }
-                            return Err(usage_err); // Return the error from execute_increment_word_usage_rpc
-                        }
-                        (Ok(_), Err(stats_err)) => {
-                            // Usage update succeeded but stats update failed - queue for retry
-                            log::error!("[Transcription] Statistics update failed: {}, queuing for retry", stats_err);
-                            
-                            // Queue the failed stats update for retry
-                            if let Err(queue_err) = crate::stats_queue::enqueue_stats_update(
-                                user_id.clone(),
-                                words_transcribed as i64,
-                                duration_seconds.unwrap_or(0),
-                                session_id.to_string(),
-                            ) {
-                                log::error!("[Transcription] Failed to queue stats update: {}", queue_err);
-                            } else {
-                                log::info!("[Transcription] Stats update queued for retry");
-                            }
-                            
-                            log::info!("[Transcription] Emitting 'word_usage_updated' event to frontend.");
-                            if let Err(e) = app_handle_clone_for_supabase.emit_all("word_usage_updated", ()) {
-                                log::error!("[Transcription] Failed to emit 'word_usage_updated' event: {}", e);
-                            }
-                        }
-                    }
-                } else {
-                    log::info!("[Transcription] No words transcribed, skipping word count update.");
-                }
-            } else {
-                println!("[DEBUG] Auth credentials are empty - user_id empty: {}, access_token empty: {}", 
-                    user_id.is_empty(), access_token.is_empty());
-                log::warn!("[Transcription] User ID or Access Token is empty. Skipping word count update.");
-            }
-        } else {
-            println!("[DEBUG] Auth check FAILED - missing credentials");
-            log::warn!("[Transcription] User ID or Access Token not found in settings (or not passed). Skipping word count update.");
+    // Call signal_reset_complete to ensure UI doesn't get stuck
+    let _ = crate::signal_reset_complete(app_handle.clone());
+    err_msg
+}
+
+// whisper invocation shared by dictation and the decoding A/B harness:
+// model, language, word-boundary splitting and plain (timestamp-free) output
+pub(crate) fn base_whisper_command(binary: &Path, working_dir: &Path, model_path: &Path, language_arg: &str) -> Command {
+    let mut command = Command::new(binary);
+    command.current_dir(working_dir)
+           .arg("-m").arg(model_path); // Model argument
+
+    // Always explicit: whisper's own default is English, not detection
+    command.arg("-l").arg(language_arg);
+
+    command.arg("--split-on-word"); // Keep this from the previous fix
+    
+    command.arg("-nt"); // No Timestamps flag - RETAINED
+    command
+}
+
+// Every flag named in whisper's --help text, short and long ("-bs N, --beam-size N")
+pub(crate) fn parse_help_flags(help: &str) -> HashSet<String> {
+    help.split(|c: char| c.is_whitespace() || c == ',' || c == '[' || c == ']')
+        .filter(|token| token.len() > 1 && token.starts_with('-'))
+        .filter(|token| token[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '-'))
+        .map(str::to_string)
+        .collect()
+}
+
+// The flags `binary` accepts, from its --help (printed to stderr by whisper.cpp). Probed
+// once per binary; if it can't be run the set is empty and no decoding flags are passed.
+// Blocks for up to WHISPER_HELP_TIMEOUT, so async code calls whisper_supported_flags_async.
+pub(crate) fn whisper_supported_flags(binary: &Path) -> HashSet<String> {
+    if let Some(flags) = WHISPER_FLAGS.lock().unwrap().get(binary) {
+        return flags.clone();
+    }
+    let mut command = Command::new(binary);
+    command.arg("--help");
+    let help = match process_runner::run_blocking(command, WHISPER_HELP_TIMEOUT, None) {
+        Ok(output) => Some((output.stdout, output.stderr)),
+        // Some builds exit non-zero after printing usage
+        Err(process_runner::ProcessError::Exited { stdout, stderr, .. }) => Some((stdout.into_bytes(), stderr.into_bytes())),
+        Err(e) => {
+            warn!("[RUST WHISPER] {} --help {}", binary.display(), e);
+            None
         }
-        // --- END SUPABASE WORD COUNT UPDATE ---
+    };
+    let flags = help.map(|(stdout, stderr)| {
+        let mut help = String::from_utf8_lossy(&stdout).into_owned();
+        help.push_str(&String::from_utf8_lossy(&stderr));
+        parse_help_flags(&help)
+    }).unwrap_or_default();
+    if !flags.is_empty() {
+        info!("[RUST WHISPER] {} lists {} flags", binary.display(), flags.len());
+        // A failed probe is retried next time
+        WHISPER_FLAGS.lock().unwrap().insert(binary.to_path_buf(), flags.clone());
+    }
+    flags
+}
 
-        // Return the text
-        Ok(trimmed_output)
-    } else {
-        // Non-zero exit code
-        let error_msg = format!("Whisper command failed with status: {}. Stderr: {}. Stdout: {}", 
-                              output.status, stderr_text.trim(), stdout_text.trim());
-        println!("[RUST ERROR] {}", error_msg);
-        
-        error!("[RUST Emit Error] Emitting fethr-error-occurred: {}", error_msg);
-        if let Err(emit_err) = app_handle.emit_all("fethr-error-occurred", error_msg.clone()) {
-            error!("[RUST ERROR] Failed to emit fethr-error-occurred event: {}", emit_err);
+// whisper_supported_flags on the blocking thread pool
+pub(crate) async fn whisper_supported_flags_async(binary: &Path) -> HashSet<String> {
+    let binary = binary.to_path_buf();
+    tokio::task::spawn_blocking(move || whisper_supported_flags(&binary))
+        .await
+        .unwrap_or_default()
+}
+
+// Command-line flags for the decoding settings, limited to the ones the binary supports
+pub(crate) fn whisper_decoding_args(decoding: &config::WhisperDecodingSettings, supported: &HashSet<String>) -> Vec<String> {
+    let defaults = config::WhisperDecodingSettings::default();
+    let mut args = Vec::new();
+    let mut push = |flag: &str, value: Option<String>| {
+        if !supported.contains(flag) {
+            info!("[RUST WHISPER] The whisper binary doesn't support {}; leaving it out", flag);
+            return;
         }
-        
-        // Call signal_reset_complete to ensure UI doesn't get stuck
-        let _ = crate::signal_reset_complete(app_handle.clone());
-        
-        Err(error_msg)
+        args.push(flag.to_string());
+        args.extend(value);
+    };
+    push("-bs", Some(decoding.beam_size.to_string()));
+    push("-bo", Some(decoding.best_of.to_string()));
+    push("-tp", Some(decoding.temperature.to_string()));
+    push("-tpi", Some(decoding.temperature_increment.to_string()));
+    push("-et", Some(decoding.entropy_threshold.to_string()));
+    push("-lpt", Some(decoding.logprob_threshold.to_string()));
+    if decoding.no_fallback {
+        push("-nf", None);
     }
+    if decoding.threads > 0 {
+        push("-t", Some(decoding.threads.to_string()));
+    }
+    // Left at whisper's own default unless changed
+    if decoding.no_speech_threshold != defaults.no_speech_threshold {
+        push("-nth", Some(decoding.no_speech_threshold.to_string()));
+    }
+    args
 }
 
 // Cleanup helper - Restore body
@@ -830,7 +1179,7 @@ fn cleanup_files(original_temp_wav: &Path, converted_temp_wav: Option<&Path>) {
 }
 
 // Helper to clean up the output from Whisper and apply simple dictionary correction
-fn whisper_output_trim(output: &str, app_handle: &AppHandle) -> String {
+fn whisper_output_trim(output: &str, app_handle: &AppHandle, language: &str, gate: Option<&ConfidenceGate>) -> String {
     // First, apply basic cleanup
     let cleaned = output.trim()
         .replace("[BLANK_AUDIO]", "")
@@ -844,7 +1193,7 @@ fn whisper_output_trim(output: &str, app_handle: &AppHandle) -> String {
         Ok(dict) if !dict.is_empty() => {
             // Use the simple dictionary corrector for now
             println!("[RUST DEBUG] Applying simple dictionary correction with {} dictionary words", dict.len());
-            crate::dictionary_corrector::correct_text_with_dictionary(&cleaned, &dict)
+            crate::dictionary_corrector::correct_text_with_confidence(&cleaned, &dict, language, gate)
         },
         Ok(_) => {
             println!("[RUST DEBUG] Dictionary is empty, skipping correction");
@@ -892,4 +1241,267 @@ pub async fn get_history(app_handle: AppHandle) -> Result<Vec<HistoryEntry>, Str
             Err(format!("Failed to read history file: {}", e))
         }
     }
-}
\ No newline at end of file
+}
+
+/// Every change to history.json reads the file, edits it and writes it back; hold this
+/// guard from the read to the write so concurrent changes (a dictation being saved, an AI
+/// result, an edit in the UI) don't overwrite each other. Never held across an await.
+pub(crate) fn lock_history() -> std::sync::MutexGuard<'static, ()> {
+    HISTORY_LOCK.lock().unwrap()
+}
+
+// Read and parse history.json (missing file = empty history)
+fn read_history_file(app_handle: &AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    let path = get_history_path(app_handle)?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str::<Vec<HistoryEntry>>(&content)
+            .map_err(|e| format!("Failed to parse history file: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read history file: {}", e)),
+    }
+}
+
+fn write_history_file(app_handle: &AppHandle, history: &[HistoryEntry]) -> Result<(), String> {
+    let path = get_history_path(app_handle)?;
+    let json = serde_json::to_string_pretty(history)
+        .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write history file: {}", e))?;
+    app_handle.emit_all("fethr-history-updated", ()).unwrap_or_else(|e| {
+        error!("[RUST HISTORY] Failed to emit history update event: {}", e);
+    });
+    Ok(())
+}
+
+/// Attach the AI chain output to the entry just saved for `pasted_text` (the transcription,
+/// or its translation). Called within the dictation's queue turn, so it's the newest entry.
+pub fn record_ai_output(app_handle: &AppHandle, pasted_text: &str, record: AiChainRecord) -> Result<(), String> {
+    let _history_guard = lock_history();
+    let mut history = read_history_file(app_handle)?;
+    let entry = history.iter_mut()
+        .max_by_key(|entry| entry.timestamp)
+        .filter(|entry| entry.text == pasted_text || entry.translation.as_ref().is_some_and(|t| t.text == pasted_text))
+        .ok_or_else(|| "No history entry for this dictation".to_string())?;
+    entry.ai_output = Some(record);
+    write_history_file(app_handle, &history)
+}
+
+/// Keep an AI action result with the history entry at `timestamp`. A result already stored
+/// under the same cache key (the action re-run on unchanged text) isn't added again.
+pub fn add_derived_version(app_handle: &AppHandle, timestamp: &str, version: DerivedVersion) -> Result<(), String> {
+    let target = parse_history_timestamp(timestamp)?;
+    let _history_guard = lock_history();
+    let mut history = read_history_file(app_handle)?;
+    let entry = history.iter_mut()
+        .find(|entry| entry.timestamp == target)
+        .ok_or_else(|| format!("History entry with timestamp {} not found", timestamp))?;
+    if version.cache_key.is_some() && entry.derived_versions.iter().any(|v| v.cache_key == version.cache_key) {
+        return Ok(());
+    }
+    info!("[RUST HISTORY] Storing '{}' result with entry {}", version.action, timestamp);
+    entry.derived_versions.push(version);
+    write_history_file(app_handle, &history)
+}
+
+#[tauri::command]
+pub async fn list_derived_versions(app_handle: AppHandle, timestamp: String) -> Result<Vec<DerivedVersion>, String> {
+    let target = parse_history_timestamp(&timestamp)?;
+    read_history_file(&app_handle)?
+        .into_iter()
+        .find(|entry| entry.timestamp == target)
+        .map(|entry| entry.derived_versions)
+        .ok_or_else(|| format!("History entry with timestamp {} not found", timestamp))
+}
+
+// Make a derived version the entry's text. The text it replaces is kept as an "edit"
+// version unless it is one of the versions already. Returns the new text.
+#[tauri::command]
+pub async fn restore_derived_version(app_handle: AppHandle, timestamp: String, version_id: String) -> Result<String, String> {
+    let target = parse_history_timestamp(&timestamp)?;
+    let _history_guard = lock_history();
+    let mut history = read_history_file(&app_handle)?;
+    let entry = history.iter_mut()
+        .find(|entry| entry.timestamp == target)
+        .ok_or_else(|| format!("History entry with timestamp {} not found", timestamp))?;
+    let restored = entry.derived_versions.iter()
+        .find(|version| version.id == version_id)
+        .map(|version| version.text.clone())
+        .ok_or_else(|| format!("Entry {} has no version {}", timestamp, version_id))?;
+
+    let replaced = std::mem::replace(&mut entry.text, restored.clone());
+    if !entry.derived_versions.iter().any(|version| version.text == replaced) {
+        entry.derived_versions.push(DerivedVersion {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            action: "edit".to_string(),
+            text: replaced,
+            provider: None,
+            model: None,
+            cache_key: None,
+        });
+    }
+    write_history_file(&app_handle, &history)?;
+
+    info!("[RUST HISTORY] Restored version {} of entry {}", version_id, timestamp);
+    Ok(restored)
+}
+
+/// Text of the entries the user edited by hand after dictating, newest first (style profile mining)
+pub fn edited_history_texts(app_handle: &AppHandle) -> Vec<String> {
+    let mut history = read_history_file(app_handle).unwrap_or_default();
+    history.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    history.into_iter()
+        .filter(|entry| entry.edited)
+        .map(|entry| entry.text)
+        .collect()
+}
+
+// Newest history entry that isn't `current` (prompt templates' ${previous_transcription})
+pub fn previous_transcription(app_handle: &AppHandle, current: &str) -> Option<String> {
+    let mut history = read_history_file(app_handle).ok()?;
+    history.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    history.into_iter().map(|entry| entry.text).find(|text| text != current)
+}
+
+// Language of the newest dictation that recorded one
+pub fn latest_language(app_handle: &AppHandle) -> Option<String> {
+    read_history_file(app_handle).ok()?
+        .into_iter()
+        .filter(|entry| entry.language.is_some())
+        .max_by_key(|entry| entry.timestamp)
+        .and_then(|entry| entry.language)
+}
+
+// Re-run the full pipeline on a history entry's retained audio with another model.
+// The result is stored as a revision; the original text is left untouched.
+#[tauri::command]
+pub async fn retranscribe_history_entry(
+    app_handle: AppHandle,
+    timestamp: String,
+    model_name: String,
+) -> Result<TranscriptRevision, String> {
+    info!("[RUST HISTORY] Re-transcribing entry {} with model {}", timestamp, model_name);
+
+    if model_name.contains('/') || model_name.contains('\\') || model_name.contains("..") {
+        return Err(format!("Invalid model name: {}", model_name));
+    }
+    let target = parse_history_timestamp(&timestamp)?;
+
+    let audio_file = read_history_file(&app_handle)?
+        .into_iter()
+        .find(|entry| entry.timestamp == target)
+        .ok_or_else(|| format!("History entry with timestamp {} not found", timestamp))?
+        .audio_file
+        .ok_or_else(|| "This history entry has no retained audio".to_string())?;
+
+    // Queued with live dictation; whisper runs one job at a time
+    let mut job = transcription_queue::submit(&app_handle)?;
+    let result = retranscribe_job(&mut job, &app_handle, &audio_file, model_name, &timestamp, target).await;
+    job.finish(&result);
+    result
+}
+
+async fn retranscribe_job(
+    job: &mut Job,
+    app_handle: &AppHandle,
+    audio_file: &str,
+    model_name: String,
+    timestamp: &str,
+    target: DateTime<Utc>,
+) -> Result<TranscriptRevision, String> {
+    job.wait_turn().await?;
+    let wav_path = crate::audio_retention::decode_to_wav(app_handle, audio_file).await?;
+    let model_name = if model_name == crate::model_selector::AUTO_MODEL {
+        let audio_seconds = crate::model_selector::wav_duration_seconds(&wav_path).unwrap_or(0.0);
+        crate::model_selector::select_model_for(app_handle, audio_seconds)
+    } else {
+        model_name
+    };
+    let output = run_whisper_pipeline(
+        app_handle.clone(),
+        wav_path.to_string_lossy().into_owned(),
+        model_name,
+        false, // Audio is already retained
+        false,
+        Some(job.control()),
+    ).await?;
+    if job.is_cancelled() {
+        return Err("Transcription cancelled".to_string());
+    }
+
+    let revision = TranscriptRevision {
+        timestamp: Utc::now(),
+        text: output.result.text,
+        model_used: output.result.model_used,
+        language: output.language,
+    };
+
+    // Re-read: history may have changed while whisper was running
+    let _history_guard = lock_history();
+    let mut history = read_history_file(app_handle)?;
+    let entry = history.iter_mut()
+        .find(|entry| entry.timestamp == target)
+        .ok_or_else(|| format!("History entry with timestamp {} was removed during re-transcription", timestamp))?;
+    entry.revisions.push(revision.clone());
+    write_history_file(app_handle, &history)?;
+
+    info!("[RUST HISTORY] Stored revision from model {}", revision.model_used);
+    Ok(revision)
+}
+
+fn parse_history_timestamp(timestamp: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid timestamp '{}': {}", timestamp, e))
+}
+
+// Words of a history entry whisper was unsure about, for highlighting.
+// Computed against the current text, so edited words simply stop matching.
+#[tauri::command]
+pub async fn get_low_confidence_spans(app_handle: AppHandle, timestamp: String) -> Result<Vec<ConfidenceSpan>, String> {
+    let target = parse_history_timestamp(&timestamp)?;
+    let threshold = SETTINGS.lock().unwrap().word_confidence.low_confidence_threshold;
+    let entry = read_history_file(&app_handle)?
+        .into_iter()
+        .find(|entry| entry.timestamp == target)
+        .ok_or_else(|| format!("History entry with timestamp {} not found", timestamp))?;
+    Ok(word_confidence::low_confidence_spans(&entry.text, &entry.word_confidence, threshold))
+}
+
+// Quick correction of one highlighted word. `start`/`end` are the span's UTF-16 offsets.
+// The replacement counts as confirmed, so it isn't highlighted again. Returns the new text.
+#[tauri::command]
+pub async fn correct_low_confidence_word(
+    app_handle: AppHandle,
+    timestamp: String,
+    start: usize,
+    end: usize,
+    replacement: String,
+) -> Result<String, String> {
+    let target = parse_history_timestamp(&timestamp)?;
+    let _history_guard = lock_history();
+    let mut history = read_history_file(&app_handle)?;
+    let entry = history.iter_mut()
+        .find(|entry| entry.timestamp == target)
+        .ok_or_else(|| format!("History entry with timestamp {} not found", timestamp))?;
+
+    let byte_start = word_confidence::utf16_to_byte_index(&entry.text, start);
+    let byte_end = word_confidence::utf16_to_byte_index(&entry.text, end);
+    let (byte_start, byte_end) = match (byte_start, byte_end) {
+        (Some(s), Some(e)) if s < e => (s, e),
+        _ => return Err(format!("Invalid span {}..{} for this entry", start, end)),
+    };
+
+    let replacement = replacement.trim();
+    if let Some(index) = word_confidence::word_index_at(&entry.text, &entry.word_confidence, byte_start) {
+        let word = &mut entry.word_confidence[index];
+        word.word = replacement.to_string(); // Keeps later words aligned with the edited text
+        word.confidence = 1.0;
+    }
+    entry.text.replace_range(byte_start..byte_end, replacement);
+    entry.edited = true;
+    let new_text = entry.text.clone();
+    write_history_file(&app_handle, &history)?;
+
+    info!("[RUST HISTORY] Corrected low-confidence word in entry {}", timestamp);
+    Ok(new_text)
+}
@@ -0,0 +1,619 @@
+// src-tauri/src/transcription_backend.rs
+//
+// Where the speech-to-text step runs. The dictation pipeline (conversion, hallucination
+// filtering, dictionary correction, formatting, history) stays the same whichever
+// backend produced the raw text:
+//   - LocalCli:         the bundled whisper binary (default)
+//   - InProcess:        whisper.cpp linked into the app via whisper-rs (`in-process-whisper` feature)
+//   - OpenAiCompatible: any /v1/audio/transcriptions API - OpenAI, Groq, faster-whisper-server, ...
+//   - WhisperServer:    a self-hosted whisper.cpp server (`/inference`)
+//
+// Backends are tried in the configured order; the first one that succeeds wins, so a
+// cloud backend can fall back to the local model when offline (or the other way round).
+// Backends are blocking - call them through `transcribe` to keep them off the async runtime.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    OpenAiCompatibleBackendSettings, TranscriptionBackendKind, TranscriptionBackendSettings,
+    WhisperDecodingSettings, WhisperServerBackendSettings,
+};
+use crate::language;
+use crate::process_runner::{self, ProcessError};
+use crate::transcription;
+use crate::transcription_queue::JobControl;
+use crate::word_confidence::{self, WordConfidence};
+
+/// One transcription (or translation) of one audio file
+#[derive(Debug, Clone)]
+pub struct BackendRequest {
+    pub audio_path: PathBuf, // 16 kHz mono WAV
+    pub language: String,    // ISO code or "auto"
+    pub prompt: Option<String>,
+    pub translate: bool, // Output English whatever the spoken language
+    pub cancel: Option<Arc<JobControl>>,
+}
+
+/// Why a transcription failed; emitted to the UI as "fethr-transcription-error"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptionError {
+    TimedOut { stage: String, timeout_secs: u64 },
+    Cancelled,
+    Failed { message: String },
+}
+
+impl TranscriptionError {
+    // `stage` names the process or backend, e.g. "whisper", "ffmpeg"
+    pub fn from_process(stage: &str, error: ProcessError) -> Self {
+        match error {
+            ProcessError::TimedOut(timeout) => TranscriptionError::TimedOut { stage: stage.to_string(), timeout_secs: timeout.as_secs() },
+            ProcessError::Cancelled => TranscriptionError::Cancelled,
+            other => TranscriptionError::Failed { message: format!("{} {}", stage, other) },
+        }
+    }
+}
+
+impl fmt::Display for TranscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptionError::TimedOut { stage, timeout_secs } => write!(f, "{} timed out after {} seconds", stage, timeout_secs),
+            TranscriptionError::Cancelled => write!(f, "Transcription cancelled"),
+            TranscriptionError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for TranscriptionError {
+    fn from(message: String) -> Self {
+        TranscriptionError::Failed { message }
+    }
+}
+
+/// Raw backend output, before hallucination filtering and correction
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackendTranscript {
+    pub text: String, // One segment per line
+    pub language: Option<String>, // Detected language code, when `language` was "auto"
+    pub language_confidence: Option<f32>,
+    pub words: Vec<WordConfidence>, // Empty if the backend doesn't report per-word probabilities
+    pub model_used: String,
+}
+
+pub trait TranscriptionBackend: Send + Sync {
+    fn kind(&self) -> TranscriptionBackendKind;
+    fn transcribe(&self, request: &BackendRequest) -> Result<BackendTranscript, TranscriptionError>;
+}
+
+pub type Backends = Arc<Vec<Box<dyn TranscriptionBackend>>>;
+
+/// The local model the LocalCli and InProcess backends use
+pub struct LocalModel {
+    pub name: String,
+    pub path: PathBuf,
+    pub decoding: WhisperDecodingSettings,
+    pub timeout: Duration, // LocalCli only; whisper.cpp in-process can't be interrupted
+}
+
+/// Backends in the configured order
+pub fn build_backends(settings: &TranscriptionBackendSettings, local: LocalModel) -> Backends {
+    let local = Arc::new(local);
+    let backends = settings.order.iter().map(|kind| -> Box<dyn TranscriptionBackend> {
+        match kind {
+            TranscriptionBackendKind::LocalCli => Box::new(LocalCliBackend {
+                binary: transcription::resolve_whisper_binary(),
+                model: local.clone(),
+            }),
+            TranscriptionBackendKind::InProcess => in_process_backend(local.clone()),
+            TranscriptionBackendKind::OpenAiCompatible => Box::new(OpenAiCompatibleBackend {
+                settings: settings.openai_compatible.clone(),
+            }),
+            TranscriptionBackendKind::WhisperServer => Box::new(WhisperServerBackend {
+                settings: settings.whisper_server.clone(),
+            }),
+        }
+    }).collect();
+    Arc::new(backends)
+}
+
+/// Try each backend in turn. A cancelled request stops the chain; otherwise a single
+/// failure is returned as is and several are listed together.
+pub fn transcribe_with_fallback(backends: &[Box<dyn TranscriptionBackend>], request: &BackendRequest) -> Result<BackendTranscript, TranscriptionError> {
+    let mut errors = Vec::new();
+    for backend in backends {
+        if request.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(TranscriptionError::Cancelled);
+        }
+        match backend.transcribe(request) {
+            Ok(transcript) => {
+                if !errors.is_empty() {
+                    info!("[TranscriptionBackend] {:?} succeeded after {} failed backend(s)", backend.kind(), errors.len());
+                }
+                return Ok(transcript);
+            }
+            Err(TranscriptionError::Cancelled) => return Err(TranscriptionError::Cancelled),
+            Err(e) => {
+                warn!("[TranscriptionBackend] {:?} failed: {}", backend.kind(), e);
+                errors.push((backend.kind(), e));
+            }
+        }
+    }
+    match errors.len() {
+        0 => Err("No transcription backend is configured".to_string().into()),
+        1 => Err(errors.remove(0).1),
+        _ => {
+            let listed: Vec<String> = errors.iter().map(|(kind, e)| format!("{:?}: {}", kind, e)).collect();
+            Err(format!("All transcription backends failed ({})", listed.join("; ")).into())
+        }
+    }
+}
+
+/// `transcribe_with_fallback` on the blocking thread pool
+pub async fn transcribe(backends: Backends, request: BackendRequest) -> Result<BackendTranscript, TranscriptionError> {
+    tokio::task::spawn_blocking(move || transcribe_with_fallback(&backends, &request))
+        .await
+        .unwrap_or_else(|e| Err(format!("Transcription task failed: {}", e).into()))
+}
+
+// --- Local whisper binary ---
+
+struct LocalCliBackend {
+    binary: Result<(PathBuf, PathBuf), String>, // (binary, working dir)
+    model: Arc<LocalModel>,
+}
+
+impl TranscriptionBackend for LocalCliBackend {
+    fn kind(&self) -> TranscriptionBackendKind {
+        TranscriptionBackendKind::LocalCli
+    }
+
+    fn transcribe(&self, request: &BackendRequest) -> Result<BackendTranscript, TranscriptionError> {
+        let (binary, working_dir) = self.binary.clone()?;
+        if !binary.exists() {
+            return Err(format!("Bundled Whisper binary not found at: {}", binary.display()).into());
+        }
+        if !self.model.path.exists() {
+            return Err(format!("Whisper model '{}' not found (downloaded or bundled at {})", self.model.name, self.model.path.display()).into());
+        }
+
+        let mut command = transcription::base_whisper_command(&binary, &working_dir, &self.model.path, &request.language);
+        let supported = transcription::whisper_supported_flags(&binary);
+        command.args(transcription::whisper_decoding_args(&self.model.decoding, &supported));
+
+        // whisper appends ".json"; next to the converted WAV, so crash recovery cleans it up
+        let json_output_base = request.audio_path.with_extension("");
+        // Older binaries have no full JSON output: no word confidence then
+        let full_json = !request.translate && supported.contains("-ojf");
+        if request.translate {
+            command.arg("--translate");
+        } else if full_json {
+            command.arg("-ojf").arg("-of").arg(&json_output_base); // Full JSON with per-token probabilities
+        }
+        if let Some(prompt) = &request.prompt {
+            command.arg("--prompt").arg(prompt);
+        }
+        command.arg(&request.audio_path);
+
+        let result = process_runner::run_blocking(command, self.model.timeout, request.cancel.as_deref());
+        // Taken whatever happened, so a killed run doesn't leave its JSON behind
+        let words = if full_json {
+            word_confidence::take_whisper_json(&json_output_base.with_extension("json"))
+        } else {
+            Vec::new()
+        };
+        let output = result.map_err(|e| TranscriptionError::from_process("Whisper", e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        let detected = language::parse_detected_language(&stderr);
+        Ok(BackendTranscript {
+            text: stdout,
+            language: detected.as_ref().map(|(code, _)| code.clone()),
+            language_confidence: detected.map(|(_, probability)| probability),
+            words,
+            model_used: self.model.name.clone(),
+        })
+    }
+}
+
+// --- whisper.cpp linked in-process ---
+
+#[cfg(feature = "in-process-whisper")]
+fn in_process_backend(model: Arc<LocalModel>) -> Box<dyn TranscriptionBackend> {
+    Box::new(in_process::InProcessBackend { model })
+}
+
+#[cfg(not(feature = "in-process-whisper"))]
+fn in_process_backend(_model: Arc<LocalModel>) -> Box<dyn TranscriptionBackend> {
+    Box::new(UnavailableBackend {
+        kind: TranscriptionBackendKind::InProcess,
+        reason: "this build doesn't include in-process whisper (enable the `in-process-whisper` feature)",
+    })
+}
+
+#[cfg(feature = "in-process-whisper")]
+mod in_process {
+    use super::*;
+    use once_cell::sync::Lazy;
+    use std::sync::Mutex;
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+    use crate::word_confidence::TokenProb;
+
+    // Loading a model takes seconds; keep the last one loaded
+    static CONTEXT: Lazy<Mutex<Option<(PathBuf, WhisperContext)>>> = Lazy::new(|| Mutex::new(None));
+
+    pub(super) struct InProcessBackend {
+        pub model: Arc<LocalModel>,
+    }
+
+    fn read_samples(path: &Path) -> Result<Vec<f32>, String> {
+        let mut reader = hound::WavReader::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let spec = reader.spec();
+        if spec.sample_rate != 16000 || spec.channels != 1 || spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 {
+            return Err(format!("In-process whisper needs 16 kHz mono 16-bit WAV, got {:?}", spec));
+        }
+        let samples: Vec<i16> = reader.samples::<i16>().collect::<Result<_, _>>().map_err(|e| format!("Failed to read samples: {}", e))?;
+        let mut audio = vec![0.0f32; samples.len()];
+        whisper_rs::convert_integer_to_float_audio(&samples, &mut audio).map_err(|e| e.to_string())?;
+        Ok(audio)
+    }
+
+    impl TranscriptionBackend for InProcessBackend {
+        fn kind(&self) -> TranscriptionBackendKind {
+            TranscriptionBackendKind::InProcess
+        }
+
+        fn transcribe(&self, request: &BackendRequest) -> Result<BackendTranscript, TranscriptionError> {
+            if !self.model.path.exists() {
+                return Err(format!("Whisper model '{}' not found at {}", self.model.name, self.model.path.display()).into());
+            }
+            let audio = read_samples(&request.audio_path)?;
+
+            let mut context_guard = CONTEXT.lock().unwrap();
+            if context_guard.as_ref().map(|(path, _)| path != &self.model.path).unwrap_or(true) {
+                info!("[TranscriptionBackend] Loading {} in-process", self.model.path.display());
+                let context = WhisperContext::new_with_params(&self.model.path.to_string_lossy(), WhisperContextParameters::default())
+                    .map_err(|e| format!("Failed to load whisper model: {}", e))?;
+                *context_guard = Some((self.model.path.clone(), context));
+            }
+            let (_, context) = context_guard.as_ref().unwrap();
+            let mut state = context.create_state().map_err(|e| format!("Failed to create whisper state: {}", e))?;
+
+            let decoding = &self.model.decoding;
+            let strategy = if decoding.beam_size > 1 {
+                SamplingStrategy::BeamSearch { beam_size: decoding.beam_size as i32, patience: -1.0 }
+            } else {
+                SamplingStrategy::Greedy { best_of: decoding.best_of as i32 }
+            };
+            let mut params = FullParams::new(strategy);
+            params.set_language(Some(&request.language));
+            params.set_translate(request.translate);
+            params.set_temperature(decoding.temperature);
+            params.set_temperature_inc(if decoding.no_fallback { 0.0 } else { decoding.temperature_increment });
+            params.set_entropy_thold(decoding.entropy_threshold);
+            params.set_logprob_thold(decoding.logprob_threshold);
+            params.set_no_speech_thold(decoding.no_speech_threshold);
+            if decoding.threads > 0 {
+                params.set_n_threads(decoding.threads as i32);
+            }
+            if let Some(prompt) = &request.prompt {
+                params.set_initial_prompt(prompt);
+            }
+            params.set_token_timestamps(true);
+            params.set_print_progress(false);
+            params.set_print_realtime(false);
+            params.set_print_special(false);
+            params.set_print_timestamps(false);
+
+            state.full(params, &audio).map_err(|e| format!("Whisper failed: {}", e))?;
+
+            let n_segments = state.full_n_segments().map_err(|e| e.to_string())?;
+            let mut lines = Vec::new();
+            let mut segments = Vec::new();
+            for segment in 0..n_segments {
+                lines.push(state.full_get_segment_text(segment).map_err(|e| e.to_string())?);
+                let mut tokens = Vec::new();
+                for token in 0..state.full_n_tokens(segment).map_err(|e| e.to_string())? {
+                    let data = state.full_get_token_data(segment, token).map_err(|e| e.to_string())?;
+                    tokens.push(TokenProb {
+                        text: state.full_get_token_text(segment, token).map_err(|e| e.to_string())?,
+                        p: data.p,
+                        from_ms: data.t0 * 10, // Centiseconds
+                        to_ms: data.t1 * 10,
+                    });
+                }
+                segments.push(tokens);
+            }
+
+            let language = if request.language == language::AUTO_LANGUAGE {
+                state.full_lang_id_from_state().ok()
+                    .and_then(whisper_rs::get_lang_str)
+                    .map(str::to_string)
+            } else {
+                None
+            };
+            Ok(BackendTranscript {
+                text: lines.join("\n"),
+                language,
+                language_confidence: None, // whisper-rs doesn't expose the detection probability
+                words: if request.translate { Vec::new() } else { word_confidence::merge_tokens(segments) },
+                model_used: self.model.name.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(not(feature = "in-process-whisper"))]
+struct UnavailableBackend {
+    kind: TranscriptionBackendKind,
+    reason: &'static str,
+}
+
+#[cfg(not(feature = "in-process-whisper"))]
+impl TranscriptionBackend for UnavailableBackend {
+    fn kind(&self) -> TranscriptionBackendKind {
+        self.kind
+    }
+
+    fn transcribe(&self, _request: &BackendRequest) -> Result<BackendTranscript, TranscriptionError> {
+        Err(self.reason.to_string().into())
+    }
+}
+
+// --- HTTP backends ---
+
+// verbose_json, as returned by OpenAI and most servers that copy its API
+#[derive(Deserialize)]
+struct VerboseJson {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    language: Option<String>, // "english" from OpenAI, "en" from some servers
+    #[serde(default)]
+    detected_language_probability: Option<f32>, // whisper.cpp server
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+    #[serde(default)]
+    words: Vec<VerboseWord>,
+}
+
+#[derive(Deserialize)]
+struct VerboseSegment {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct VerboseWord {
+    word: String,
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    end: f64,
+    #[serde(default)]
+    probability: Option<f32>,
+}
+
+fn parse_verbose_json(body: &str, model_used: String) -> Result<BackendTranscript, String> {
+    let parsed: VerboseJson = serde_json::from_str(body).map_err(|e| format!("Unexpected response: {}", e))?;
+    // One line per segment, like the whisper CLI, so the hallucination filter sees the same shape
+    let text = if parsed.segments.is_empty() {
+        parsed.text
+    } else {
+        parsed.segments.iter().map(|s| s.text.trim()).collect::<Vec<_>>().join("\n")
+    };
+    // OpenAI's words have timings but no probability; without one there's nothing to gate on
+    let words = parsed.words.into_iter()
+        .filter_map(|w| w.probability.map(|confidence| WordConfidence {
+            word: w.word.trim().to_string(),
+            confidence,
+            start_ms: (w.start * 1000.0).round() as i64,
+            end_ms: (w.end * 1000.0).round() as i64,
+        }))
+        .collect();
+    let language = parsed.language.as_deref().and_then(language::code_for_language);
+    Ok(BackendTranscript {
+        text,
+        language_confidence: language.as_ref().and(parsed.detected_language_probability),
+        language,
+        words,
+        model_used,
+    })
+}
+
+fn http_client(timeout_secs: u64) -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn audio_part(path: &Path) -> Result<reqwest::blocking::multipart::Part, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    reqwest::blocking::multipart::Part::bytes(bytes)
+        .file_name("audio.wav")
+        .mime_str("audio/wav")
+        .map_err(|e| e.to_string())
+}
+
+fn request_error(stage: &str, timeout_secs: u64, error: reqwest::Error) -> TranscriptionError {
+    if error.is_timeout() {
+        TranscriptionError::TimedOut { stage: stage.to_string(), timeout_secs }
+    } else {
+        format!("Request failed: {}", error).into()
+    }
+}
+
+fn read_response(response: reqwest::blocking::Response, model_used: String) -> Result<BackendTranscript, TranscriptionError> {
+    let status = response.status();
+    let body = response.text().map_err(|e| format!("Failed to read response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status, body.trim()).into());
+    }
+    Ok(parse_verbose_json(&body, model_used)?)
+}
+
+struct OpenAiCompatibleBackend {
+    settings: OpenAiCompatibleBackendSettings,
+}
+
+impl OpenAiCompatibleBackend {
+    fn endpoint(&self, translate: bool) -> String {
+        let base = self.settings.base_url.trim_end_matches('/');
+        let base = base.strip_suffix("/v1").unwrap_or(base);
+        format!("{}/v1/audio/{}", base, if translate { "translations" } else { "transcriptions" })
+    }
+}
+
+impl TranscriptionBackend for OpenAiCompatibleBackend {
+    fn kind(&self) -> TranscriptionBackendKind {
+        TranscriptionBackendKind::OpenAiCompatible
+    }
+
+    fn transcribe(&self, request: &BackendRequest) -> Result<BackendTranscript, TranscriptionError> {
+        let mut form = reqwest::blocking::multipart::Form::new()
+            .part("file", audio_part(&request.audio_path)?)
+            .text("model", self.settings.model.clone())
+            .text("response_format", "verbose_json");
+        if !request.translate {
+            form = form
+                .text("timestamp_granularities[]", "word")
+                .text("timestamp_granularities[]", "segment");
+            // The translations endpoint has no language parameter
+            if request.language != language::AUTO_LANGUAGE {
+                form = form.text("language", request.language.clone());
+            }
+        }
+        if let Some(prompt) = &request.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        let mut builder = http_client(self.settings.timeout_secs)?
+            .post(self.endpoint(request.translate))
+            .multipart(form);
+        if !self.settings.api_key.is_empty() {
+            builder = builder.bearer_auth(&self.settings.api_key);
+        }
+        let response = builder.send().map_err(|e| request_error("OpenAI-compatible backend", self.settings.timeout_secs, e))?;
+        read_response(response, self.settings.model.clone())
+    }
+}
+
+struct WhisperServerBackend {
+    settings: WhisperServerBackendSettings,
+}
+
+impl TranscriptionBackend for WhisperServerBackend {
+    fn kind(&self) -> TranscriptionBackendKind {
+        TranscriptionBackendKind::WhisperServer
+    }
+
+    fn transcribe(&self, request: &BackendRequest) -> Result<BackendTranscript, TranscriptionError> {
+        let mut form = reqwest::blocking::multipart::Form::new()
+            .part("file", audio_part(&request.audio_path)?)
+            .text("response_format", "verbose_json")
+            .text("language", request.language.clone())
+            .text("translate", request.translate.to_string());
+        if let Some(prompt) = &request.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        let url = format!("{}{}", self.settings.base_url.trim_end_matches('/'), self.settings.endpoint_path);
+        let response = http_client(self.settings.timeout_secs)?
+            .post(url)
+            .multipart(form)
+            .send()
+            .map_err(|e| request_error("Whisper server", self.settings.timeout_secs, e))?;
+        read_response(response, "whisper-server".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_http::mock_server;
+
+    fn request(dir: &tempfile::TempDir, language: &str) -> BackendRequest {
+        let audio_path = dir.path().join("take.wav");
+        std::fs::write(&audio_path, b"RIFF fake wav").unwrap();
+        BackendRequest { audio_path, language: language.to_string(), prompt: Some("Supabase, Tauri".to_string()), translate: false, cancel: None }
+    }
+
+    const OPENAI_RESPONSE: &str = r#"{"task":"transcribe","language":"english","duration":2.1,"text":"Deploy to Supabase. Today.",
+        "segments":[{"id":0,"text":" Deploy to Supabase."},{"id":1,"text":" Today."}],
+        "words":[{"word":"Deploy","start":0.0,"end":0.4},{"word":"to","start":0.4,"end":0.5}]}"#;
+
+    #[test]
+    fn openai_compatible_sends_multipart_and_parses_verbose_json() {
+        let (url, received) = mock_server("200 OK", OPENAI_RESPONSE);
+        let backend = OpenAiCompatibleBackend {
+            settings: OpenAiCompatibleBackendSettings { base_url: format!("{}/v1/", url), api_key: "sk-test".to_string(), ..Default::default() },
+        };
+        let dir = tempfile::tempdir().unwrap();
+
+        let transcript = backend.transcribe(&request(&dir, "auto")).unwrap();
+        assert_eq!(transcript.text, "Deploy to Supabase.\nToday.");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.model_used, "whisper-1");
+        assert!(transcript.words.is_empty()); // No probabilities from OpenAI
+
+        let raw = received.recv().unwrap();
+        assert!(raw.starts_with("POST /v1/audio/transcriptions "));
+        assert!(raw.to_ascii_lowercase().contains("authorization: bearer sk-test"));
+        assert!(raw.contains("name=\"model\"\r\n\r\nwhisper-1"));
+        assert!(raw.contains("name=\"prompt\"\r\n\r\nSupabase, Tauri"));
+        assert!(raw.contains("RIFF fake wav"));
+        assert!(!raw.contains("name=\"language\"")); // "auto" means let the service detect
+    }
+
+    #[test]
+    fn whisper_server_reports_word_probabilities() {
+        let (url, received) = mock_server("200 OK", r#"{"text":" Hallo Welt","language":"de","detected_language_probability":0.93,
+            "segments":[{"text":" Hallo Welt"}],
+            "words":[{"word":" Hallo","start":0.0,"end":0.5,"probability":0.98},{"word":" Welt","start":0.5,"end":0.9,"probability":0.41}]}"#);
+        let backend = WhisperServerBackend { settings: WhisperServerBackendSettings { base_url: url, ..Default::default() } };
+        let dir = tempfile::tempdir().unwrap();
+
+        let transcript = backend.transcribe(&request(&dir, "auto")).unwrap();
+        assert_eq!(transcript.text, "Hallo Welt");
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+        assert_eq!(transcript.language_confidence, Some(0.93));
+        assert_eq!(transcript.words.len(), 2);
+        assert_eq!(transcript.words[1], WordConfidence { word: "Welt".to_string(), confidence: 0.41, start_ms: 500, end_ms: 900 });
+
+        let raw = received.recv().unwrap();
+        assert!(raw.starts_with("POST /inference "));
+        assert!(raw.contains("name=\"language\"\r\n\r\nauto"));
+    }
+
+    #[test]
+    fn falls_back_to_the_next_backend() {
+        let (failing_url, _) = mock_server("500 Internal Server Error", r#"{"error":"model overloaded"}"#);
+        let (working_url, _) = mock_server("200 OK", OPENAI_RESPONSE);
+        let backends: Vec<Box<dyn TranscriptionBackend>> = vec![
+            Box::new(WhisperServerBackend { settings: WhisperServerBackendSettings { base_url: failing_url, ..Default::default() } }),
+            Box::new(OpenAiCompatibleBackend { settings: OpenAiCompatibleBackendSettings { base_url: working_url, ..Default::default() } }),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        let transcript = transcribe_with_fallback(&backends, &request(&dir, "en")).unwrap();
+        assert_eq!(transcript.model_used, "whisper-1");
+
+        let (failing_url, _) = mock_server("500 Internal Server Error", r#"{"error":"model overloaded"}"#);
+        let backends: Vec<Box<dyn TranscriptionBackend>> = vec![
+            Box::new(WhisperServerBackend { settings: WhisperServerBackendSettings { base_url: failing_url, ..Default::default() } }),
+        ];
+        let error = transcribe_with_fallback(&backends, &request(&dir, "en")).unwrap_err();
+        assert!(error.to_string().starts_with("HTTP 500"), "{}", error);
+
+        // Cancelled before any backend ran
+        let cancel = Arc::new(JobControl::default());
+        cancel.cancel();
+        let cancelled = BackendRequest { cancel: Some(cancel), ..request(&dir, "en") };
+        assert_eq!(transcribe_with_fallback(&backends, &cancelled).unwrap_err(), TranscriptionError::Cancelled);
+    }
+}
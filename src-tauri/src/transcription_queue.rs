@@ -0,0 +1,294 @@
+// src-tauri/src/transcription_queue.rs
+//
+// Transcription jobs run one at a time, in the order they were submitted. A dictation
+// that ends while the previous one is still transcribing waits its turn instead of being
+// rejected. A job's turn lasts until it's finished - including the paste - so pastes land
+// in dictation order as well.
+//
+// Every status change is emitted as "fethr-transcription-job" with a JobInfo payload:
+// queued -> running -> done | failed | cancelled.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// Running and waiting jobs together; beyond this, new jobs are refused
+pub const MAX_JOBS: usize = 8;
+
+const JOB_EVENT: &str = "fethr-transcription-job";
+
+static QUEUE: Lazy<JobQueue> = Lazy::new(|| JobQueue::new(MAX_JOBS));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Cancellation flag shared between a job and `cancel_transcription_job`
+#[derive(Debug, Default)]
+pub struct JobControl {
+    cancelled: AtomicBool,
+}
+
+impl JobControl {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+struct JobEntry {
+    info: JobInfo,
+    control: Arc<JobControl>,
+}
+
+/// Bookkeeping and turn-taking, independent of Tauri
+pub struct JobQueue {
+    capacity: usize,
+    jobs: Mutex<Vec<JobEntry>>, // Submission order; finished jobs are removed. The first one runs.
+    changed: Notify, // A job finished or was cancelled
+}
+
+impl JobQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, jobs: Mutex::new(Vec::new()), changed: Notify::new() }
+    }
+
+    pub fn submit(&self) -> Result<(JobInfo, Arc<JobControl>), String> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.len() >= self.capacity {
+            return Err(format!("Too many transcriptions waiting ({} jobs)", jobs.len()));
+        }
+        let info = JobInfo { job_id: Uuid::new_v4().to_string(), status: JobStatus::Queued, submitted_at: Utc::now(), error: None };
+        let control = Arc::new(JobControl::default());
+        jobs.push(JobEntry { info: info.clone(), control: control.clone() });
+        Ok((info, control))
+    }
+
+    /// Wait until every earlier job has finished. Fails if the job is cancelled first.
+    pub async fn wait_turn(&self, job_id: &str, control: &JobControl) -> Result<JobInfo, String> {
+        loop {
+            // Registered before checking, so a change in between isn't missed
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if control.is_cancelled() {
+                return Err("Transcription cancelled".to_string());
+            }
+            {
+                let mut jobs = self.jobs.lock().unwrap();
+                match jobs.iter().position(|job| job.info.job_id == job_id) {
+                    None => return Err(format!("Unknown transcription job {}", job_id)),
+                    Some(0) => {
+                        jobs[0].info.status = JobStatus::Running;
+                        return Ok(jobs[0].info.clone());
+                    }
+                    Some(_) => {}
+                }
+            }
+            changed.await;
+        }
+    }
+
+    /// Record the outcome and forget the job
+    pub fn finish(&self, job_id: &str, status: JobStatus, error: Option<String>) -> Option<JobInfo> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let index = jobs.iter().position(|job| job.info.job_id == job_id)?;
+        let mut info = jobs.remove(index).info;
+        info.status = status;
+        info.error = error;
+        self.changed.notify_waiters();
+        Some(info)
+    }
+
+    pub fn cancel(&self, job_id: &str) -> Result<(), String> {
+        let jobs = self.jobs.lock().unwrap();
+        let job = jobs.iter().find(|job| job.info.job_id == job_id)
+            .ok_or_else(|| format!("No transcription job {}", job_id))?;
+        job.control.cancel();
+        self.changed.notify_waiters();
+        Ok(())
+    }
+
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.jobs.lock().unwrap().iter().map(|job| job.info.clone()).collect()
+    }
+}
+
+fn emit(app_handle: &AppHandle, info: &JobInfo) {
+    if let Err(e) = app_handle.emit_all(JOB_EVENT, info) {
+        warn!("[TranscriptionQueue] Failed to emit job event: {}", e);
+    }
+}
+
+/// A submitted job. Dropping it without `finish` records it as failed.
+pub struct Job {
+    app_handle: AppHandle,
+    id: String,
+    control: Arc<JobControl>,
+    finished: bool,
+}
+
+impl Job {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn control(&self) -> Arc<JobControl> {
+        self.control.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.control.is_cancelled()
+    }
+
+    pub async fn wait_turn(&mut self) -> Result<(), String> {
+        let info = QUEUE.wait_turn(&self.id, &self.control).await?;
+        info!("[TranscriptionQueue] Job {} running", self.id);
+        emit(&self.app_handle, &info);
+        Ok(())
+    }
+
+    /// Record the outcome and let the next job run
+    pub fn finish<T>(mut self, result: &Result<T, String>) {
+        let (status, error) = match result {
+            _ if self.is_cancelled() => (JobStatus::Cancelled, None),
+            Ok(_) => (JobStatus::Done, None),
+            Err(e) => (JobStatus::Failed, Some(e.clone())),
+        };
+        self.complete(status, error);
+    }
+
+    fn complete(&mut self, status: JobStatus, error: Option<String>) {
+        self.finished = true;
+        if let Some(info) = QUEUE.finish(&self.id, status, error) {
+            info!("[TranscriptionQueue] Job {} {:?}", self.id, info.status);
+            emit(&self.app_handle, &info);
+        }
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.complete(JobStatus::Failed, Some("Transcription job ended without a result".to_string()));
+        }
+    }
+}
+
+/// Queue a transcription; call `wait_turn` before touching whisper
+pub fn submit(app_handle: &AppHandle) -> Result<Job, String> {
+    let (info, control) = QUEUE.submit()?;
+    let ahead = QUEUE.jobs().len().saturating_sub(1);
+    if ahead > 0 {
+        info!("[TranscriptionQueue] Job {} queued behind {} other job(s)", info.job_id, ahead);
+    }
+    emit(app_handle, &info);
+    Ok(Job { app_handle: app_handle.clone(), id: info.job_id, control, finished: false })
+}
+
+/// Running and waiting jobs
+pub fn job_count() -> usize {
+    QUEUE.jobs().len()
+}
+
+#[tauri::command]
+pub fn get_transcription_jobs() -> Vec<JobInfo> {
+    QUEUE.jobs()
+}
+
+/// A queued job is dropped before it runs; a running one has its whisper/ffmpeg process killed
+#[tauri::command]
+pub fn cancel_transcription_job(job_id: String) -> Result<(), String> {
+    info!("[TranscriptionQueue] Cancelling job {}", job_id);
+    QUEUE.cancel(&job_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn refuses_jobs_beyond_capacity() {
+        let queue = JobQueue::new(2);
+        let (first, _) = queue.submit().unwrap();
+        queue.submit().unwrap();
+        assert!(queue.submit().is_err());
+
+        queue.finish(&first.job_id, JobStatus::Done, None);
+        assert!(queue.submit().is_ok());
+    }
+
+    #[tokio::test]
+    async fn runs_jobs_in_submission_order() {
+        let queue = Arc::new(JobQueue::new(8));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for n in 0..4u64 {
+            // Submitted in order, but the later jobs are quicker to ask for their turn
+            let (info, control) = queue.submit().unwrap();
+            let (queue, order) = (queue.clone(), order.clone());
+            handles.push(tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(5 * (4 - n))).await;
+                let running = queue.wait_turn(&info.job_id, &control).await.unwrap();
+                assert_eq!(running.status, JobStatus::Running);
+                order.lock().unwrap().push(n);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                queue.finish(&info.job_id, JobStatus::Done, None);
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3]);
+        assert!(queue.jobs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelling_a_queued_job_skips_it() {
+        let queue = Arc::new(JobQueue::new(8));
+        let (first, first_control) = queue.submit().unwrap();
+        let (second, second_control) = queue.submit().unwrap();
+        queue.wait_turn(&first.job_id, &first_control).await.unwrap();
+
+        let waiting = {
+            let queue = queue.clone();
+            let job_id = second.job_id.clone();
+            tokio::spawn(async move { queue.wait_turn(&job_id, &second_control).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        queue.cancel(&second.job_id).unwrap();
+        assert!(waiting.await.unwrap().is_err()); // Without waiting for the first job
+
+        let cancelled = queue.finish(&second.job_id, JobStatus::Cancelled, None).unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        assert_eq!(queue.jobs().len(), 1);
+        assert_eq!(queue.jobs()[0].status, JobStatus::Running);
+        assert!(queue.cancel("no-such-job").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+// src-tauri/src/translation.rs
+//
+// Translation dictation: holding Shift while pressing the hotkey records a take whose
+// output is translated into the configured target language before it's pasted.
+// English targets use whisper's own `--translate` pass (no network); any other target
+// goes through the "translate" AI action. History keeps the original and the translation.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::config::SETTINGS;
+use crate::language::ENGLISH;
+
+// Shift is physically held (tracked by the rdev callback)
+static MODIFIER_DOWN: AtomicBool = AtomicBool::new(false);
+// The take currently being recorded should be translated
+static TRANSLATE_CURRENT_TAKE: AtomicBool = AtomicBool::new(false);
+
+/// How a translation was produced
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationMethod {
+    Whisper,
+    AiAction,
+}
+
+/// Translated output stored alongside the original text on a history entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationRecord {
+    pub target_language: String,
+    pub text: String,
+    pub method: TranslationMethod,
+}
+
+pub fn set_modifier_down(down: bool) {
+    MODIFIER_DOWN.store(down, Ordering::SeqCst);
+}
+
+/// Called when a recording starts: remember whether this take is a translation
+pub fn arm_for_new_take() -> bool {
+    let enabled = {
+        let settings_guard = SETTINGS.lock().unwrap();
+        settings_guard.translation.enabled
+    };
+    let translate = enabled && MODIFIER_DOWN.load(Ordering::SeqCst);
+    TRANSLATE_CURRENT_TAKE.store(translate, Ordering::SeqCst);
+    translate
+}
+
+pub fn take_is_armed() -> bool {
+    TRANSLATE_CURRENT_TAKE.load(Ordering::SeqCst)
+}
+
+/// Consume the flag for the take being transcribed; returns the target language if armed
+pub fn take_target_language() -> Option<String> {
+    if !TRANSLATE_CURRENT_TAKE.swap(false, Ordering::SeqCst) {
+        return None;
+    }
+    let settings_guard = SETTINGS.lock().unwrap();
+    let target = settings_guard.translation.target_language.trim().to_lowercase();
+    if target.is_empty() { None } else { Some(target) }
+}
+
+/// A cancelled take must not leave the next one armed
+pub fn disarm() {
+    TRANSLATE_CURRENT_TAKE.store(false, Ordering::SeqCst);
+}
+
+/// Which route a translation to `target` takes, or None when the text is already in it
+pub fn route_for(source_language: Option<&str>, target_language: &str) -> Option<TranslationMethod> {
+    if source_language == Some(target_language) {
+        return None;
+    }
+    if target_language == ENGLISH {
+        Some(TranslationMethod::Whisper)
+    } else {
+        Some(TranslationMethod::AiAction)
+    }
+}
+
+/// Translate with the configured AI provider
+pub async fn translate_with_ai(app_handle: &AppHandle, text: String, target_language: String) -> Result<String, String> {
+    crate::ai_actions_manager::translate_text(app_handle, &text, &target_language).await
+        .map(|translated| translated.trim().trim_matches('"').trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_target_uses_whisper() {
+        assert_eq!(route_for(Some("de"), "en"), Some(TranslationMethod::Whisper));
+        assert_eq!(route_for(None, "en"), Some(TranslationMethod::Whisper));
+    }
+
+    #[test]
+    fn other_targets_use_ai_action() {
+        assert_eq!(route_for(Some("en"), "fr"), Some(TranslationMethod::AiAction));
+        assert_eq!(route_for(Some("de"), "fr"), Some(TranslationMethod::AiAction));
+    }
+
+    #[test]
+    fn same_language_needs_no_translation() {
+        assert_eq!(route_for(Some("en"), "en"), None);
+        assert_eq!(route_for(Some("fr"), "fr"), None);
+    }
+}
@@ -0,0 +1,373 @@
+// src-tauri/src/word_confidence.rs
+//
+// Word-level confidence from whisper's full JSON output (`-ojf`), which carries a
+// probability per token. Tokens are merged into words (a word's confidence is its
+// least certain token), stored on the history entry, and used to:
+//   - gate dictionary correction: words whisper was sure about are never rewritten
+//   - find low-confidence spans in the final text for highlighting and quick correction
+//
+// The final text has been through filtering, correction and formatting, so words are
+// matched back to whisper's words in order, tolerating small insertions and removals.
+
+use std::path::Path;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+// How far ahead to look for a word that formatting or filtering moved
+const ALIGN_WINDOW: usize = 6;
+
+/// One whisper word with its probability
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordConfidence {
+    pub word: String,
+    pub confidence: f32, // 0-1, lowest token probability in the word
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+/// A low-confidence word in a text. Offsets are UTF-16 code units, as JavaScript indexes strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceSpan {
+    pub start: usize,
+    pub end: usize,
+    pub word: String,
+    pub confidence: f32,
+}
+
+// --- whisper.cpp full JSON (-ojf) ---
+
+#[derive(Deserialize)]
+struct WhisperJson {
+    #[serde(default)]
+    transcription: Vec<WhisperJsonSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonSegment {
+    #[serde(default)]
+    tokens: Vec<WhisperJsonToken>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonToken {
+    text: String,
+    #[serde(default)]
+    p: f32,
+    #[serde(default)]
+    offsets: Option<WhisperJsonOffsets>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct WhisperJsonOffsets {
+    from: i64,
+    to: i64,
+}
+
+/// One decoded token, whichever way whisper was run
+pub(crate) struct TokenProb {
+    pub text: String,
+    pub p: f32,
+    pub from_ms: i64,
+    pub to_ms: i64,
+}
+
+fn is_special_token(text: &str) -> bool {
+    // "[_BEG_]", "[_TT_150]", "<|endoftext|>"
+    (text.starts_with("[_") && text.ends_with(']')) || (text.starts_with("<|") && text.ends_with("|>"))
+}
+
+/// Parse whisper's full JSON output into words
+pub fn parse_whisper_json(json: &str) -> Result<Vec<WordConfidence>, String> {
+    let parsed: WhisperJson = serde_json::from_str(json).map_err(|e| format!("Failed to parse whisper JSON: {}", e))?;
+    Ok(merge_tokens(parsed.transcription.into_iter().map(|segment| {
+        segment.tokens.into_iter().map(|token| {
+            let offsets = token.offsets.unwrap_or(WhisperJsonOffsets { from: 0, to: 0 });
+            TokenProb { text: token.text, p: token.p, from_ms: offsets.from, to_ms: offsets.to }
+        }).collect()
+    })))
+}
+
+/// Merge whisper's tokens into words. A token starting with a space starts a new word;
+/// punctuation-only tokens attach to the word but don't lower its confidence.
+pub(crate) fn merge_tokens(segments: impl IntoIterator<Item = Vec<TokenProb>>) -> Vec<WordConfidence> {
+    let mut words: Vec<WordConfidence> = Vec::new();
+    for segment in segments {
+        let mut current: Option<WordConfidence> = None;
+        for token in segment.into_iter().filter(|t| !is_special_token(&t.text)) {
+            let scored = token.text.chars().any(char::is_alphanumeric);
+            let starts_word = token.text.starts_with(char::is_whitespace);
+
+            match current.as_mut() {
+                Some(word) if !starts_word => {
+                    word.word.push_str(&token.text);
+                    word.end_ms = word.end_ms.max(token.to_ms);
+                    if scored {
+                        word.confidence = word.confidence.min(token.p);
+                    }
+                }
+                _ => {
+                    words.extend(current.take());
+                    current = Some(WordConfidence {
+                        word: token.text.trim_start().to_string(),
+                        confidence: if scored { token.p } else { 1.0 },
+                        start_ms: token.from_ms,
+                        end_ms: token.to_ms,
+                    });
+                }
+            }
+        }
+        words.extend(current);
+    }
+
+    // Punctuation on its own (" -") isn't a word anyone would correct
+    words.retain(|w| w.word.chars().any(char::is_alphanumeric));
+    words
+}
+
+/// Read and delete whisper's JSON output; empty if it's missing (older whisper binaries)
+pub fn take_whisper_json(path: &Path) -> Vec<WordConfidence> {
+    let words = match std::fs::read_to_string(path) {
+        Ok(json) => parse_whisper_json(&json).unwrap_or_else(|e| {
+            warn!("[WordConfidence] {}", e);
+            Vec::new()
+        }),
+        Err(e) => {
+            warn!("[WordConfidence] No whisper JSON at {}: {}", path.display(), e);
+            Vec::new()
+        }
+    };
+    let _ = std::fs::remove_file(path);
+    words
+}
+
+/// Comparison key: lowercase letters, digits and apostrophes
+fn word_key(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'' || *c == '’')
+        .flat_map(char::to_lowercase)
+        .map(|c| if c == '’' { '\'' } else { c })
+        .collect()
+}
+
+/// Walks whisper's words alongside the words of a text derived from them
+pub struct ConfidenceAligner<'a> {
+    words: &'a [WordConfidence],
+    keys: Vec<String>,
+    index: usize,
+    offset: usize, // Bytes of keys[index] already matched by earlier pieces ("gpt4o" -> "gpt", "o")
+}
+
+impl<'a> ConfidenceAligner<'a> {
+    pub fn new(words: &'a [WordConfidence]) -> Self {
+        let keys = words.iter().map(|w| word_key(&w.word)).collect();
+        Self { words, keys, index: 0, offset: 0 }
+    }
+
+    /// Confidence of the next word (or piece of a word) in the text, if it can be matched
+    pub fn next(&mut self, text_word: &str) -> Option<f32> {
+        self.next_index(text_word).map(|index| self.words[index].confidence)
+    }
+
+    /// Like `next`, but returns the index of the matched whisper word
+    pub fn next_index(&mut self, text_word: &str) -> Option<usize> {
+        let key = word_key(text_word);
+        if key.is_empty() || self.index >= self.words.len() {
+            return None;
+        }
+
+        // Rest of a whisper word that was split into several pieces
+        if self.offset > 0 {
+            if let Some(pos) = self.keys[self.index][self.offset..].find(&key) {
+                self.offset += pos + key.len();
+                return Some(self.index);
+            }
+            self.advance();
+            if self.index >= self.words.len() {
+                return None;
+            }
+        }
+
+        // Same word, possibly a few words ahead
+        let end = (self.index + ALIGN_WINDOW).min(self.words.len());
+        if let Some(found) = (self.index..end).find(|&i| self.keys[i] == key) {
+            self.index = found;
+            self.advance();
+            return Some(found);
+        }
+
+        // First piece of a whisper word
+        if self.keys[self.index].starts_with(&key) {
+            let index = self.index;
+            self.offset = key.len();
+            if self.offset >= self.keys[index].len() {
+                self.advance();
+            }
+            return Some(index);
+        }
+        None
+    }
+
+    fn advance(&mut self) {
+        self.index += 1;
+        self.offset = 0;
+    }
+}
+
+/// Decides which words dictionary correction may touch
+pub struct ConfidenceGate<'a> {
+    pub words: &'a [WordConfidence],
+    pub threshold: f32,
+}
+
+impl ConfidenceGate<'_> {
+    pub fn aligner(&self) -> ConfidenceAligner<'_> {
+        ConfidenceAligner::new(self.words)
+    }
+
+    /// Unknown words stay correctable, as they were before confidence was tracked
+    pub fn allows(&self, confidence: Option<f32>) -> bool {
+        !matches!(confidence, Some(c) if c >= self.threshold)
+    }
+}
+
+/// Whitespace-separated words of `text` with their byte offsets
+fn words_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace().map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Words of `text` whose whisper confidence is below `threshold`
+pub fn low_confidence_spans(text: &str, words: &[WordConfidence], threshold: f32) -> Vec<ConfidenceSpan> {
+    let mut aligner = ConfidenceAligner::new(words);
+    let mut spans = Vec::new();
+    for (byte_start, word) in words_with_offsets(text) {
+        let confidence = match aligner.next(word) {
+            Some(confidence) if confidence < threshold => confidence,
+            _ => continue,
+        };
+        // Highlight the word, not the punctuation around it
+        let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+        let core_start = byte_start + word.find(core).unwrap_or(0);
+        let start = utf16_len(&text[..core_start]);
+        spans.push(ConfidenceSpan { start, end: start + utf16_len(core), word: core.to_string(), confidence });
+    }
+    spans
+}
+
+/// Index into `words` of the whisper word at `byte_offset` in `text`
+pub fn word_index_at(text: &str, words: &[WordConfidence], byte_offset: usize) -> Option<usize> {
+    let mut aligner = ConfidenceAligner::new(words);
+    for (byte_start, word) in words_with_offsets(text) {
+        let index = aligner.next_index(word);
+        if (byte_start..byte_start + word.len()).contains(&byte_offset) {
+            return index;
+        }
+    }
+    None
+}
+
+/// Byte index in `text` of a UTF-16 offset from the UI
+pub fn utf16_to_byte_index(text: &str, utf16_offset: usize) -> Option<usize> {
+    let mut utf16_pos = 0;
+    for (byte_index, c) in text.char_indices() {
+        if utf16_pos == utf16_offset {
+            return Some(byte_index);
+        }
+        utf16_pos += c.len_utf16();
+    }
+    (utf16_pos == utf16_offset).then_some(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_JSON: &str = r#"{
+      "result": {"language": "en"},
+      "transcription": [{
+        "offsets": {"from": 0, "to": 2600},
+        "text": " Deploy to Supabase, then GPT4o.",
+        "tokens": [
+          {"text": "[_BEG_]", "offsets": {"from": 0, "to": 0}, "id": 50364, "p": 0.91},
+          {"text": " Deploy", "offsets": {"from": 0, "to": 400}, "id": 1, "p": 0.98},
+          {"text": " to", "offsets": {"from": 400, "to": 520}, "id": 2, "p": 0.99},
+          {"text": " Sup", "offsets": {"from": 520, "to": 800}, "id": 3, "p": 0.42},
+          {"text": "abase", "offsets": {"from": 800, "to": 1100}, "id": 4, "p": 0.88},
+          {"text": ",", "offsets": {"from": 1100, "to": 1100}, "id": 5, "p": 0.30},
+          {"text": " then", "offsets": {"from": 1100, "to": 1400}, "id": 6, "p": 0.97},
+          {"text": " GPT", "offsets": {"from": 1400, "to": 1900}, "id": 7, "p": 0.35},
+          {"text": "4", "offsets": {"from": 1900, "to": 2100}, "id": 8, "p": 0.61},
+          {"text": "o", "offsets": {"from": 2100, "to": 2300}, "id": 9, "p": 0.44},
+          {"text": ".", "offsets": {"from": 2300, "to": 2600}, "id": 10, "p": 0.95},
+          {"text": "[_TT_130]", "offsets": {"from": 2600, "to": 2600}, "id": 50494, "p": 0.10}
+        ]
+      }]
+    }"#;
+
+    fn words() -> Vec<WordConfidence> {
+        parse_whisper_json(SAMPLE_JSON).unwrap()
+    }
+
+    #[test]
+    fn merges_tokens_into_words() {
+        let words = words();
+        let texts: Vec<&str> = words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(texts, vec!["Deploy", "to", "Supabase,", "then", "GPT4o."]);
+        // Lowest token wins; the comma's probability doesn't count
+        assert_eq!(words[2].confidence, 0.42);
+        assert_eq!(words[4].confidence, 0.35);
+        assert_eq!((words[2].start_ms, words[2].end_ms), (520, 1100));
+    }
+
+    #[test]
+    fn aligns_split_and_reformatted_words() {
+        let words = words();
+        let mut aligner = ConfidenceAligner::new(&words);
+        assert_eq!(aligner.next("Deploy"), Some(0.98));
+        assert_eq!(aligner.next("to"), Some(0.99));
+        assert_eq!(aligner.next("supabase"), Some(0.42));
+        assert_eq!(aligner.next("then"), Some(0.97));
+        // The dictionary corrector splits on digits
+        assert_eq!(aligner.next("GPT"), Some(0.35));
+        assert_eq!(aligner.next("o"), Some(0.35));
+        assert_eq!(aligner.next("unrelated"), None);
+    }
+
+    #[test]
+    fn finds_low_confidence_spans_in_final_text() {
+        let words = words();
+        let text = "Deploy to Supabase, then GPT4o.";
+        let spans = low_confidence_spans(text, &words, 0.5);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0], ConfidenceSpan { start: 10, end: 18, word: "Supabase".into(), confidence: 0.42 });
+        assert_eq!(&text[spans[1].start..spans[1].end], "GPT4o");
+
+        // A dropped filler word doesn't throw the alignment off
+        let spans = low_confidence_spans("Deploy to Supabase then", &words, 0.5);
+        assert_eq!(spans.iter().map(|s| s.word.as_str()).collect::<Vec<_>>(), vec!["Supabase"]);
+
+        assert_eq!(word_index_at(text, &words, text.find("then").unwrap()), Some(3));
+        assert_eq!(word_index_at(text, &words, text.len()), None);
+    }
+
+    #[test]
+    fn gate_blocks_confident_words_only() {
+        let words = words();
+        let gate = ConfidenceGate { words: &words, threshold: 0.5 };
+        assert!(!gate.allows(Some(0.98)));
+        assert!(gate.allows(Some(0.42)));
+        assert!(gate.allows(None));
+    }
+
+    #[test]
+    fn converts_utf16_offsets() {
+        let text = "héllo 👋 there";
+        assert_eq!(utf16_to_byte_index(text, 0), Some(0));
+        assert_eq!(utf16_to_byte_index(text, 9), Some(text.find("there").unwrap()));
+        assert_eq!(utf16_to_byte_index(text, 14), Some(text.len()));
+        assert_eq!(utf16_to_byte_index(text, 50), None);
+    }
+}
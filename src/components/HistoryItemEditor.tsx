@@ -1,5 +1,5 @@
 import React, { useState, useEffect, useCallback, useMemo } from 'react';
-import type { HistoryEntry } from '../types'; // Adjust path if necessary
+import type { AiError, HistoryEntry } from '../types'; // Adjust path if necessary
 import { Button } from "@/components/ui/button";
 import TextareaAutosize from 'react-textarea-autosize'; // For custom prompt input
 import { format } from 'date-fns'; // For formatting the timestamp
@@ -16,8 +16,22 @@ interface HistoryItemEditorProps {
   user: User | null;
 }
 
-// Define the character limit constant (can be defined outside the component or as a const inside)
-const CUSTOM_PROMPT_MAX_LENGTH = 500;
+// Prompt length is limited by the AI provider's token budget (checked by the backend);
+// the counter shows the same rough estimate of ~4 characters per token
+const estimateTokens = (text: string) => Math.ceil(text.length / 4);
+
+const aiErrorMessage = (error: AiError | string | undefined, fallback: string): string => {
+  if (typeof error === 'string') return error;
+  switch (error?.kind) {
+    case 'cancelled': return 'The AI action was cancelled.';
+    case 'rate_limited':
+      return error.retry_after ? `Rate limit reached - try again in ${error.retry_after} seconds.` : error.message;
+    case 'quota_exceeded': return `Your AI usage quota is used up. ${error.message}`;
+    case 'unauthorized': return `Check your API key or log in again. ${error.message}`;
+    case undefined: return fallback;
+    default: return error.message;
+  }
+};
 
 const HistoryItemEditor: React.FC<HistoryItemEditorProps> = ({ entry, onSave, onCancel, user }) => {
   const [editedText, setEditedText] = useState<string>(entry.text);
@@ -116,14 +130,15 @@ const HistoryItemEditor: React.FC<HistoryItemEditorProps> = ({ entry, onSave, on
     try {
         const result = await invoke<string>('perform_ai_action', {
             action: actionType,
-            text: editedText
+            text: editedText,
+            historyTimestamp: entry.timestamp
         });
         console.log(`[AI Action] Received result for '${actionType}': "${result.substring(0, 50)}..."`);
         setEditedText(result);
         toast({ title: successTitle, description: successDescription });
     } catch (error: any) {
         console.error(`[AI Action] Error performing '${actionType}':`, error);
-        const errorMessage = typeof error === 'string' ? error : (error?.message || `Failed to perform ${actionType}.`);
+        const errorMessage = aiErrorMessage(error, `Failed to perform ${actionType}.`);
         toast({ variant: "destructive", title: errorTitle, description: `The ${actionType} action encountered an issue. ${errorMessage}` });
     } finally {
         setIsAiLoading(null);
@@ -170,12 +185,13 @@ const HistoryItemEditor: React.FC<HistoryItemEditorProps> = ({ entry, onSave, on
               action: "custom_direct_prompt", 
               text: editedText,
               directPrompt: customUserPrompt,
+              historyTimestamp: entry.timestamp,
           });
           setEditedText(result);
           toast({title: "Custom Prompt Applied", description: "The text has been transformed."});
       } catch (error) {
           console.error("Failed to apply custom prompt:", error);
-          const errorMessage = typeof error === 'string' ? error : "An unexpected error occurred.";
+          const errorMessage = aiErrorMessage(error as AiError, "An unexpected error occurred.");
           toast({ variant: "destructive", title: "Custom Prompt Error", description: errorMessage });
       } finally {
           setIsApplyingCustomPrompt(false);
@@ -258,15 +274,14 @@ const HistoryItemEditor: React.FC<HistoryItemEditorProps> = ({ entry, onSave, on
                 minRows={2}
                 maxRows={6}
                 disabled={!!isApplyingCustomPrompt || isAiLoading !== null} 
-                maxLength={CUSTOM_PROMPT_MAX_LENGTH} 
             />
             {/* Helper text with reduced top margin */}
             <p className="text-xs text-neutral-500 mt-0.5 mb-1.5"> {/* Adjusted top margin, slightly increased bottom margin for spacing before counter */}
-                Optional: Use <code className="bg-neutral-750 px-1 py-0.5 rounded text-neutral-300 text-[0.7rem]">${'{text}'}</code> in your prompt to specify where the current transcription text should be inserted. If omitted, your prompt will be used as a general instruction for the text.
+                Optional: Use <code className="bg-neutral-750 px-1 py-0.5 rounded text-neutral-300 text-[0.7rem]">${'{text}'}</code> in your prompt to specify where the current transcription text should be inserted. If omitted, your prompt will be used as a general instruction for the text. Other variables such as <code className="bg-neutral-750 px-1 py-0.5 rounded text-neutral-300 text-[0.7rem]">${'{clipboard}'}</code> and <code className="bg-neutral-750 px-1 py-0.5 rounded text-neutral-300 text-[0.7rem]">${'{app_name}'}</code> work too.
             </p>
             {/* Character counter, moved here and right-aligned */}
             <div className="text-xs text-neutral-500 text-right">
-                {customUserPrompt.length} / {CUSTOM_PROMPT_MAX_LENGTH}
+                ~{estimateTokens(customUserPrompt)} tokens
             </div>
             
             {/* Container for button, now only button, aligned right */}
@@ -111,7 +111,7 @@ const editIconPath = "/Icons/edit icon.png";
 
 const RecordingPill: React.FC<RecordingPillProps> = ({ currentState, duration, transcription, error, backendError, showUpgradePrompt, isResizing, onEditClick, onErrorDismiss, onUpgradeClick }) => {
     const isIdle = currentState === RecordingState.IDLE;
-    const isRecordingState = currentState === RecordingState.RECORDING || currentState === RecordingState.LOCKED_RECORDING;
+    const isRecordingState = currentState === RecordingState.RECORDING || currentState === RecordingState.LOCKED_RECORDING || currentState === RecordingState.PAUSED;
     const isProcessingState = currentState === RecordingState.TRANSCRIBING || currentState === RecordingState.PASTING;
     const isSuccessState = currentState === RecordingState.SUCCESS; // CRITICAL FIX: Handle SUCCESS state
     const isEditPending = currentState === RecordingState.IDLE_EDIT_READY;
@@ -180,7 +180,7 @@ const RecordingPill: React.FC<RecordingPillProps> = ({ currentState, duration, t
         if (currentPillState === RecordingState.IDLE) {
             console.log('[RecordingPill] --> Emitting fethr-start-recording');
             emit('fethr-start-recording', {}).catch(err => console.error("Error emitting fethr-start-recording:", err));
-        } else if (currentPillState === RecordingState.RECORDING || currentPillState === RecordingState.LOCKED_RECORDING) {
+        } else if (currentPillState === RecordingState.RECORDING || currentPillState === RecordingState.LOCKED_RECORDING || currentPillState === RecordingState.PAUSED) {
             console.log('[RecordingPill] --> Emitting fethr-stop-and-transcribe');
             emit('fethr-stop-and-transcribe', true).catch(err => console.error("Error emitting fethr-stop-and-transcribe:", err));
         } else {
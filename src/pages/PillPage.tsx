@@ -74,6 +74,7 @@ function PillPage() {
                 break;
             case RecordingState.RECORDING:
             case RecordingState.LOCKED_RECORDING:
+            case RecordingState.PAUSED:
             case RecordingState.TRANSCRIBING:
             case RecordingState.PASTING:
             case RecordingState.SUCCESS:
@@ -454,6 +455,7 @@ function PillPage() {
                          switch (stateUppercase) {
                             case "RECORDING": newTsState = RecordingState.RECORDING; break;
                             case "LOCKEDRECORDING": newTsState = RecordingState.LOCKED_RECORDING; break;
+                            case "PAUSED": newTsState = RecordingState.PAUSED; break;
                             case "TRANSCRIBING": newTsState = RecordingState.TRANSCRIBING; break;
                             case "IDLE": newTsState = RecordingState.IDLE; break; 
                             case "ERROR": newTsState = RecordingState.ERROR; break; 
@@ -503,7 +505,7 @@ function PillPage() {
                     }
 
                     // Timer management
-                    const shouldBeRunning = newTsState === RecordingState.RECORDING || newTsState === RecordingState.LOCKED_RECORDING;
+                    const shouldBeRunning = newTsState === RecordingState.RECORDING || newTsState === RecordingState.LOCKED_RECORDING || newTsState === RecordingState.PAUSED;
                     const isRunning = timerIntervalRef.current !== null;
                     if (shouldBeRunning && !isRunning) {
                         startTimeRef.current = Date.now();
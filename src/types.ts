@@ -7,7 +7,8 @@ export enum RecordingState {
   PASTING = 5,         // Add pasting state
   SUCCESS = 6,
   IDLE_EDIT_READY = 7, // Keep this if still used by SettingsPage
-  SUCCESS_EDIT_PENDING = 8 // <-- Add new state for immediate edit
+  SUCCESS_EDIT_PENDING = 8, // <-- Add new state for immediate edit
+  PAUSED = 9,          // Locked recording on hold (Pause key / toggle_pause_recording)
   // Intermediate backend states don't need direct UI representation
   // CANCELLING might map to IDLE visually
 }
@@ -34,6 +35,7 @@ export interface AudioSettings {
   input_gain: number;
   noise_suppression: boolean;
   auto_gain_control: boolean;
+  input_channel?: number | null; // 0-based channel on multi-input devices; unset = downmix
 }
 
 export interface SoundSettings {
@@ -49,9 +51,40 @@ export interface SmartFormattingSettings {
   list_detection: boolean;
 }
 
+export interface ModelStatus {
+  id: string;
+  file_name: string;
+  approx_size_mb: number;
+  multilingual: boolean;
+  quantization?: string | null;
+  sha256?: string | null;
+  downloaded: boolean;
+  bundled: boolean;
+  downloading: boolean;
+  path?: string | null;
+}
+
+export interface ModelDownloadProgress {
+  model_id: string;
+  downloaded_bytes: number;
+  total_bytes?: number | null;
+}
+
+export type AudioRetentionFormat = 'flac' | 'opus';
+
+export interface AudioRetentionSettings {
+  enabled: boolean;
+  format: AudioRetentionFormat;
+  max_total_mb: number;  // 0 = no size limit
+  max_age_days: number;  // 0 = keep forever
+}
+
 export interface AppSettings {
   model_name: string;
+  models_dir?: string | null; // Download location; unset = app data dir
+  auto_model_latency_budget_ms?: number; // Used when model_name is 'auto'
   language: string;
+  allowed_languages?: string[]; // Limits 'auto' detection; empty = any language
   auto_paste: boolean;
   pill_enabled: boolean;
   supabase_url: string;
@@ -65,12 +98,301 @@ export interface AppSettings {
   audio: AudioSettings;
   sounds: SoundSettings;
   smart_formatting: SmartFormattingSettings;
+  audio_retention?: AudioRetentionSettings;
+  translation?: TranslationSettings;
+  whisper_decoding?: WhisperDecodingSettings;
+  hallucination_filter?: HallucinationFilterSettings;
+  word_confidence?: WordConfidenceSettings;
+  transcription_backends?: TranscriptionBackendSettings;
+  process_timeouts?: ProcessTimeoutSettings;
+  ai_provider?: AiProviderSettings;
+  ai_chain?: AiChainSettings;
+  command_mode?: CommandModeSettings;
+  pause_hotkey?: string; // Pauses/resumes a locked recording; same key names as action hotkeys
+}
+
+// Select text in any app, hold the hotkey and speak an instruction; the result replaces
+// the selection. Failures arrive as "fethr-command-failed" events.
+export interface CommandModeSettings {
+  enabled: boolean;
+  hotkey: string; // Same key names as action hotkeys, e.g. "F8"
+}
+
+// AI actions applied to every dictation before it's pasted
+export interface AiChainSettings {
+  enabled: boolean;
+  actions: string[]; // Action ids in order, at most 5
+  latency_budget_ms: number; // 500-120000; past this the raw text is pasted
+}
+
+// Where AI action prompts are sent; only the selected provider's settings are used
+export type AiProviderKind = 'proxy' | 'openai_compatible' | 'anthropic' | 'local';
+
+export interface AiProviderSettings {
+  provider: AiProviderKind;
+  proxy: {
+    url: string;
+    model: string; // Empty = the relay's default
+    timeout_secs: number; // 1-600
+    max_prompt_tokens: number; // 100-1000000; longer prompts are refused
+  };
+  openai_compatible: {
+    base_url: string; // e.g. http://localhost:11434/v1 for Ollama
+    api_key: string; // Empty = no Authorization header
+    model: string;
+    timeout_secs: number; // 1-600
+    max_prompt_tokens: number; // 100-1000000; longer prompts are refused
+  };
+  anthropic: {
+    base_url: string;
+    api_key: string;
+    model: string;
+    max_tokens: number;
+    timeout_secs: number; // 1-600
+    max_prompt_tokens: number; // 100-1000000; longer prompts are refused
+  };
+  // Offline: llama.cpp on the CPU; only written_form, summarize and email among the built-in actions
+  local: {
+    model_id: string; // LocalModelStatus.id
+    max_tokens: number; // 16-4096
+    threads: number; // 0 = llama.cpp default
+    timeout_secs: number; // 1-600
+    max_prompt_tokens: number;
+  };
+}
+
+// list_local_models; download progress arrives as "fethr-local-model-download-progress"
+export interface LocalModelStatus {
+  id: string;
+  file_name: string;
+  url: string;
+  approx_size_mb: number;
+  chat_format: 'chat_ml' | 'llama3';
+  sha256: string | null;
+  downloaded: boolean;
+  downloading: boolean;
+}
+
+// whisper and ffmpeg are killed when they run longer than this
+export interface ProcessTimeoutSettings {
+  whisper_timeout_secs: number; // 10-3600
+  ffmpeg_timeout_secs: number; // 5-600
+}
+
+// "fethr-transcription-error" event payload
+export type TranscriptionError =
+  | { kind: 'timed_out'; stage: string; timeout_secs: number }
+  | { kind: 'cancelled' }
+  | { kind: 'failed'; message: string };
+
+// Where speech-to-text runs; backends are tried in order until one succeeds
+export type TranscriptionBackendKind = 'local_cli' | 'in_process' | 'openai_compatible' | 'whisper_server';
+
+export interface TranscriptionBackendSettings {
+  order: TranscriptionBackendKind[];
+  openai_compatible: {
+    base_url: string; // e.g. https://api.openai.com/v1
+    api_key: string; // Empty = no Authorization header
+    model: string;
+    timeout_secs: number; // 1-600
+    max_prompt_tokens: number; // 100-1000000; longer prompts are refused
+  };
+  whisper_server: {
+    base_url: string;
+    endpoint_path: string; // '/inference' for whisper.cpp's server
+    timeout_secs: number; // 1-600
+    max_prompt_tokens: number; // 100-1000000; longer prompts are refused
+  };
+}
+
+export interface WordConfidenceSettings {
+  gate_corrections: boolean; // Dictionary correction only rewrites low-confidence words
+  low_confidence_threshold: number; // 0-1
+}
+
+export interface WordConfidence {
+  word: string;
+  confidence: number; // 0-1
+  start_ms: number;
+  end_ms: number;
+}
+
+// From get_low_confidence_spans; offsets index the entry text directly (UTF-16, like JS strings)
+export interface ConfidenceSpan {
+  start: number;
+  end: number;
+  word: string;
+  confidence: number;
+}
+
+// Filtering of whisper hallucinations ("Thanks for watching!", repetition loops)
+export interface HallucinationFilterSettings {
+  mode: 'off' | 'flag' | 'drop';
+  compression_ratio_threshold: number; // 1-10
+  min_speech_seconds: number; // 0-5
+}
+
+export interface SuspectSegment {
+  text: string;
+  reason: 'known_phrase' | 'annotation' | 'repetition' | 'compression_ratio' | 'no_speech' | 'speech_rate_mismatch';
+  dropped: boolean;
+}
+
+// Entry of get_hallucination_review_log (newest first)
+export interface HallucinationReviewEntry {
+  timestamp: string;
+  model_used: string;
+  speech_seconds?: number;
+  raw_output: string;
+  suspects: SuspectSegment[];
+}
+
+// whisper.cpp decoding parameters (validated by the backend)
+export interface WhisperDecodingSettings {
+  beam_size: number; // 1-8; 1 = greedy
+  best_of: number; // 1-8
+  temperature: number; // 0-1
+  temperature_increment: number; // 0-1, fallback step
+  no_fallback: boolean;
+  entropy_threshold: number; // 0-10
+  logprob_threshold: number; // -10-0
+  threads: number; // 0 = whisper default
+  no_speech_threshold: number; // 0-1
+}
+
+// Result of run_decoding_ab_test
+export interface DecodingRun {
+  params: WhisperDecodingSettings;
+  text: string;
+  timings_ms: number[];
+  mean_ms: number;
+  real_time_factor: number;
+}
+
+export interface DiffSegment {
+  op: 'equal' | 'removed' | 'added'; // removed = only in A, added = only in B
+  text: string;
+}
+
+export interface DecodingAbReport {
+  model_name: string;
+  audio_seconds: number;
+  a: DecodingRun;
+  b: DecodingRun;
+  diff: DiffSegment[];
+  changed_words: number;
+}
+
+// Translation dictation: hold Shift with the hotkey
+export interface TranslationSettings {
+  enabled: boolean;
+  target_language: string; // ISO code; 'en' uses whisper's built-in translation
+}
+
+export interface TranslationRecord {
+  target_language: string;
+  text: string;
+  method: 'whisper' | 'ai_action';
+}
+
+// "fethr-transcription-job" event payload and get_transcription_jobs entries
+export type TranscriptionJobStatus = 'queued' | 'running' | 'done' | 'failed' | 'cancelled';
+
+export interface TranscriptionJob {
+  job_id: string;
+  status: TranscriptionJobStatus;
+  submitted_at: string;
+  error?: string;
+}
+
+// "fethr-ai-action-chunk" event payload: the next piece of a streaming perform_ai_action result.
+// Pass your own requestId to perform_ai_action to match chunks and to call cancel_ai_action.
+export interface AiActionChunk {
+  request_id: string;
+  delta: string;
+}
+
+// Writing style added to AI action prompts (get_style_profile / save_style_profile / build_style_profile)
+export interface PreferredSpelling {
+  prefer: string;
+  avoid: string;
+}
+
+export interface StyleProfile {
+  version: number;
+  enabled: boolean;
+  tone: string;
+  generated_tone?: string; // Tone written by the last build; a different tone was typed and is kept
+  sign_offs: string[];
+  preferred_spellings: PreferredSpelling[];
+  forbidden_phrases: string[];
+  samples: string[]; // The user's own texts, analysed by build_style_profile
+  mine_history: boolean; // Opt-in: build_style_profile also analyses edited history entries
+  apply_to: string[]; // Action ids whose prompts get the style guide
+}
+
+// perform_ai_action failure. Network, timeout, rate-limit and 5xx errors were already retried.
+export type AiError =
+  | { kind: 'network'; message: string }
+  | { kind: 'timeout'; message: string }
+  | { kind: 'rate_limited'; retry_after?: number | null; message: string } // retry_after in seconds
+  | { kind: 'unauthorized'; message: string }
+  | { kind: 'quota_exceeded'; message: string }
+  | { kind: 'provider_error'; status?: number | null; message: string }
+  | { kind: 'invalid_template'; message: string }
+  | { kind: 'cancelled' };
+
+// User-defined AI action (get_ai_actions / save_ai_action)
+export type ActionOutputMode = 'replace' | 'append' | 'clipboard';
+
+export interface UserAction {
+  id: string; // Empty when creating; assigned by save_ai_action
+  name: string;
+  template: string; // Must use ${text}; see prompt_template.rs for ${if ...} and the other variables
+  output_mode: ActionOutputMode;
+  model?: string; // Unset: the provider's default model
+  hotkey?: string; // e.g. "F9"; dictates and then runs the action
 }
 
 // History entry for transcription results
 export interface HistoryEntry {
   timestamp: string; // ISO string format from chrono::DateTime<Utc>
   text: string;     // The transcribed text
+  model_used?: string; // Whisper model that produced the text
+  audio_file?: string; // Retained compressed audio, if audio retention was on
+  language?: string; // ISO code the text is in
+  language_confidence?: number; // Detection probability, when auto-detected
+  translation?: TranslationRecord; // Translation dictation output; text keeps the original
+  suspect_segments?: SuspectSegment[]; // Whisper output the hallucination filter dropped or flagged
+  word_confidence?: WordConfidence[]; // Whisper's per-word confidence, before correction and formatting
+  revisions?: TranscriptRevision[]; // Re-transcriptions, oldest first
+  ai_output?: AiChainRecord; // Automatic AI chain output; text keeps the raw transcription
+  derived_versions?: DerivedVersion[]; // AI action results for this entry, oldest first
+  edited?: boolean; // Text was changed by hand (editor or word correction)
+}
+
+// An AI action's result kept with its history entry (list_derived_versions / restore_derived_version)
+export interface DerivedVersion {
+  id: string;
+  timestamp: string;
+  action: string; // "edit" for text replaced by a restore
+  text: string;
+  provider?: AiProviderKind;
+  model?: string;
+  cache_key?: string;
+}
+
+export interface AiChainRecord {
+  actions: string[];
+  text: string;
+  elapsed_ms: number;
+}
+
+export interface TranscriptRevision {
+  timestamp: string;
+  text: string;
+  model_used: string;
+  language?: string;
 }
 
 // You can add other shared interfaces or types here later if needed